@@ -1,33 +1,35 @@
 use crate::utils::{
-    c, cal_table_minenum_recursion, chunk_matrixes, combine, find_a_border_cell, laymine,
-    laymine_op, refresh_board, refresh_matrixs, refresh_matrixses, unsolvable_structure,
+    c, cal_area, cal_table_minenum_recursion, chunk_matrixes, combine,
+    find_a_border_cell, get_covered_game_board, laymine_op_rng, laymine_rng, refresh_board,
+    refresh_board_with_topology, refresh_matrixs, refresh_matrixs_with_rows,
+    refresh_matrixs_with_topology, refresh_matrixses, unsolvable_structure,
 };
+use crate::topology::Topology;
 #[cfg(any(feature = "py", feature = "rs"))]
 use crate::utils::{cal_bbbv_exp, legalize_board};
 
 use crate::videos::{GameBoardState, MinesweeperBoard};
+#[cfg(any(feature = "py", feature = "rs"))]
+use crate::board::{Board, BoardFilter};
 
 use crate::big_number::BigNumber;
-
-#[cfg(feature = "js")]
-use crate::utils::JsShuffle;
+use crate::probability_engine::MoveSuggestion;
+use crate::seeded_rng::SeededRng;
+use num_bigint::BigUint;
 
 #[cfg(any(feature = "py", feature = "rs"))]
 use crate::obr::ImageBoard;
 
 use itertools::Itertools;
 
-#[cfg(any(feature = "py", feature = "rs"))]
-use rand::seq::SliceRandom;
-#[cfg(any(feature = "py", feature = "rs"))]
-use rand::thread_rng;
 // use tract_onnx::tract_hir::ops::array::Flatten;
 
 use std::cmp::{max, min};
 #[cfg(any(feature = "py", feature = "rs"))]
-use std::sync::mpsc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::collections::HashMap;
 #[cfg(any(feature = "py", feature = "rs"))]
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, OnceLock};
 #[cfg(any(feature = "py", feature = "rs"))]
 use std::thread;
 
@@ -50,6 +52,17 @@ pub fn solve_minus(
     xs: &mut Vec<Vec<(usize, usize)>>,
     bs: &mut Vec<Vec<i32>>,
     board_of_game: &mut Vec<Vec<i32>>,
+) -> Result<(Vec<(usize, usize)>, Vec<(usize, usize)>), usize> {
+    solve_minus_traced(a_mats, xs, bs, board_of_game, None)
+}
+
+// 同solve_minus，传入sink时记录推理出的每个格子及作为依据的数字
+fn solve_minus_traced(
+    a_mats: &mut Vec<Vec<Vec<i32>>>,
+    xs: &mut Vec<Vec<(usize, usize)>>,
+    bs: &mut Vec<Vec<i32>>,
+    board_of_game: &mut Vec<Vec<i32>>,
+    mut sink: Option<&mut SolveSink>,
 ) -> Result<(Vec<(usize, usize)>, Vec<(usize, usize)>), usize> {
     let block_num = bs.len();
     // let mut flag = false;
@@ -59,6 +72,8 @@ pub fn solve_minus(
     for b in (0..block_num).rev() {
         let mut not_mine_rel = vec![];
         let mut is_mine_rel = vec![];
+        // 推理出每个格子的两行，只保留第一次推理出时的
+        let mut rel_rows: Vec<(usize, (usize, usize))> = vec![];
         let matrix_column = xs[b].len();
         let matrix_row = bs[b].len();
         if matrix_row <= 1 {
@@ -82,6 +97,13 @@ pub fn solve_minus(
                 }
                 if flag_adj {
                     let bdval = bs[b][i] - bs[b][j];
+                    if adval1.len() as i32 == bdval || advaln1.len() as i32 == -bdval {
+                        for &k in adval1.iter().chain(advaln1.iter()) {
+                            if !rel_rows.iter().any(|&(kk, _)| kk == k) {
+                                rel_rows.push((k, (i, j)));
+                            }
+                        }
+                    }
                     if adval1.len() as i32 == bdval {
                         is_mine_rel.append(&mut adval1);
                         not_mine_rel.append(&mut advaln1);
@@ -96,6 +118,30 @@ pub fn solve_minus(
         is_mine_rel.dedup();
         not_mine_rel.sort();
         not_mine_rel.dedup();
+        if let Some(sink) = sink.as_deref_mut() {
+            let rel = not_mine_rel
+                .iter()
+                .map(|&k| (k, false))
+                .chain(is_mine_rel.iter().map(|&k| (k, true)));
+            let mut found = vec![];
+            for (k, m) in rel {
+                let &(_, (i, j)) = rel_rows.iter().find(|&&(kk, _)| kk == k).unwrap();
+                // 格子所在的那一行排在前面
+                let (i, j) = if a_mats[b][i][k] >= 1 { (i, j) } else { (j, i) };
+                let mut witnesses = sink.row(b, i)?;
+                merge_witnesses(&mut witnesses, &sink.row(b, j)?);
+                found.push((k, m, witnesses));
+            }
+            // 推理出的格子从矩阵中删去后，涉及它的行都依赖推理它的两行
+            for (k, m, witnesses) in found {
+                for t in 0..matrix_row {
+                    if a_mats[b][t][k] >= 1 {
+                        sink.merge(b, t, &witnesses);
+                    }
+                }
+                sink.record(xs[b][k], m, witnesses);
+            }
+        }
         for i in &not_mine_rel {
             not_mine.push(xs[b][*i]);
             board_of_game[xs[b][*i].0][xs[b][*i].1] = 12;
@@ -126,11 +172,14 @@ pub fn solve_minus(
         a_mats.remove(b);
         bs.remove(b);
         xs.remove(b);
+        if let Some(sink) = sink.as_deref_mut() {
+            sink.rows.remove(b);
+        }
     }
-    let (mut not, mut is) = solve_direct(a_mats, xs, bs, board_of_game)?; // 没错，双集合判雷的最后一步是用单集合再过一轮。理由：（1）这样才不会报错（2）单集合复杂度很低，不费事
+    let (mut not, mut is) = solve_direct_traced(a_mats, xs, bs, board_of_game, sink.as_deref_mut())?; // 没错，双集合判雷的最后一步是用单集合再过一轮。理由：（1）这样才不会报错（2）单集合复杂度很低，不费事
     not_mine.append(&mut not);
     is_mine.append(&mut is);
-    chunk_matrixes(a_mats, xs, bs);
+    chunk_matrixes(a_mats, xs, bs, sink.map(|sink| &mut sink.rows));
     Ok((not_mine, is_mine))
 }
 
@@ -144,6 +193,17 @@ pub fn solve_direct(
     xs: &mut Vec<Vec<(usize, usize)>>,
     bs: &mut Vec<Vec<i32>>,
     board_of_game: &mut Vec<Vec<i32>>,
+) -> Result<(Vec<(usize, usize)>, Vec<(usize, usize)>), usize> {
+    solve_direct_traced(a_mats, xs, bs, board_of_game, None)
+}
+
+// 同solve_direct，传入sink时记录推理出的每个格子及作为依据的数字
+fn solve_direct_traced(
+    a_mats: &mut Vec<Vec<Vec<i32>>>,
+    xs: &mut Vec<Vec<(usize, usize)>>,
+    bs: &mut Vec<Vec<i32>>,
+    board_of_game: &mut Vec<Vec<i32>>,
+    mut sink: Option<&mut SolveSink>,
 ) -> Result<(Vec<(usize, usize)>, Vec<(usize, usize)>), usize> {
     let mut is_mine = vec![];
     let mut not_mine = vec![];
@@ -156,6 +216,15 @@ pub fn solve_direct(
             if a_mats[b][i].iter().sum::<i32>() == bs[b][i] {
                 for k in (0..matrix_column).rev() {
                     if a_mats[b][i][k] >= 1 {
+                        if let Some(sink) = sink.as_deref_mut() {
+                            let witnesses = sink.row(b, i)?;
+                            for t in 0..matrix_row {
+                                if t != i && a_mats[b][t][k] >= 1 {
+                                    sink.merge(b, t, &witnesses);
+                                }
+                            }
+                            sink.record(xs[b][k], true, witnesses);
+                        }
                         is_mine.push((xs[b][k].0, xs[b][k].1));
                         board_of_game[xs[b][k].0][xs[b][k].1] = 11;
                         xs[b].remove(k);
@@ -168,6 +237,9 @@ pub fn solve_direct(
                 }
                 a_mats[b].remove(i);
                 bs[b].remove(i);
+                if let Some(sink) = sink.as_deref_mut() {
+                    sink.rows[b].remove(i);
+                }
                 matrix_row -= 1;
             }
         }
@@ -175,6 +247,15 @@ pub fn solve_direct(
             if bs[b][i] == 0 {
                 for k in (0..matrix_column).rev() {
                     if a_mats[b][i][k] >= 1 {
+                        if let Some(sink) = sink.as_deref_mut() {
+                            let witnesses = sink.row(b, i)?;
+                            for t in 0..matrix_row {
+                                if t != i && a_mats[b][t][k] >= 1 {
+                                    sink.merge(b, t, &witnesses);
+                                }
+                            }
+                            sink.record(xs[b][k], false, witnesses);
+                        }
                         not_mine.push(xs[b][k]);
                         board_of_game[xs[b][k].0][xs[b][k].1] = 12;
                         xs[b].remove(k);
@@ -186,6 +267,9 @@ pub fn solve_direct(
                 }
                 a_mats[b].remove(i);
                 bs[b].remove(i);
+                if let Some(sink) = sink.as_deref_mut() {
+                    sink.rows[b].remove(i);
+                }
                 matrix_row -= 1;
             }
         }
@@ -193,6 +277,9 @@ pub fn solve_direct(
             a_mats.remove(b);
             bs.remove(b);
             xs.remove(b);
+            if let Some(sink) = sink.as_deref_mut() {
+                sink.rows.remove(b);
+            }
         }
     }
     let ans = bs.iter().find(|&b| match b.iter().find(|&&x| x < 0) {
@@ -203,7 +290,7 @@ pub fn solve_direct(
         Some(_) => return Err(6),
         None => {}
     }
-    chunk_matrixes(a_mats, xs, bs);
+    chunk_matrixes(a_mats, xs, bs, sink.map(|sink| &mut sink.rows));
     Ok((not_mine, is_mine))
 }
 
@@ -231,7 +318,7 @@ pub fn cal_probability_enum(
     let (mut matrix_a_s, mut matrix_x_s, mut matrix_b_s, mut inside_cell, is_minenum) =
         refresh_matrixs(&board_of_game);
     let raw_total = if minenum < 1.0 {
-        ((board_of_game.len() * board_of_game[0].len()) as f64 * minenum) as usize
+        (cal_area(board_of_game) as f64 * minenum) as usize
     } else {
         minenum as usize
     };
@@ -290,7 +377,7 @@ pub fn cal_probability_enum(
     }
     let max_minenum_uncapped = max_minenum;
     let minenum = if minenum < 1.0 {
-        let mn = ((board_of_game.len() * board_of_game[0].len()) as f64 * minenum) as usize;
+        let mn = (cal_area(board_of_game) as f64 * minenum) as usize;
         min(max(mn - is_minenum, min_minenum), max_minenum + inside_cell)
     } else {
         let mm = (minenum as usize).overflowing_sub(is_minenum);
@@ -464,9 +551,18 @@ pub fn cal_probability_enum(
 pub fn cal_probability_onboard(
     board_of_game: &Vec<Vec<i32>>,
     minenum: f64,
+) -> Result<(Vec<Vec<f64>>, [usize; 3]), usize> {
+    cal_probability_onboard_with_topology(board_of_game, minenum, Topology::Plane)
+}
+
+/// 在指定的拓扑结构上计算概率，按照所在的位置返回。输入、返回值和错误码同cal_probability_onboard。
+pub fn cal_probability_onboard_with_topology(
+    board_of_game: &Vec<Vec<i32>>,
+    minenum: f64,
+    topology: Topology,
 ) -> Result<(Vec<Vec<f64>>, [usize; 3]), usize> {
     let mut p = vec![vec![-1.0; board_of_game[0].len()]; board_of_game.len()];
-    let pp = cal_probability_csp(&board_of_game, minenum)?;
+    let pp = cal_probability_csp_with_topology(&board_of_game, minenum, topology)?;
     for i in pp.0 {
         p[i.0 .0][i.0 .1] = i.1;
     }
@@ -498,6 +594,149 @@ pub fn cal_probability_csp(
     crate::probability_engine::cal_probability_csp(board_of_game, minenum)
 }
 
+/// 在指定的拓扑结构上用概率引擎计算概率。输入、返回值和错误码同cal_probability_csp。
+pub fn cal_probability_csp_with_topology(
+    board_of_game: &Vec<Vec<i32>>,
+    minenum: f64,
+    topology: Topology,
+) -> Result<(Vec<((usize, usize), f64)>, f64, [usize; 3], usize), usize> {
+    crate::probability_engine::cal_probability_csp_with_topology(board_of_game, minenum, topology)
+}
+
+/// 精确计算与局面相容的雷的排布总数，以及每个格子是雷的排布数，用于研究或验证极小的概率。  
+/// 格子是雷的概率等于其排布数除以排布总数，计算过程中不经过浮点数。  
+/// - 输入：局面、总雷数（>=1时为总雷数，<1时为雷的比例）。  
+/// - 返回：排布总数、与局面位置对应的是雷的排布数（已打开或标为安全的格子为0，标雷的格子等于排布总数）。  
+/// - 错误码：1=盘面矛盾, 2=枚举过长, 3=输入参数非法。
+/// # Example
+/// ```
+/// use ms_toollib::cal_solutions_count_exact;
+/// use num_bigint::BigUint;
+/// let game_board = vec![
+///     vec![ 1, 10, 10],
+///     vec![10, 10, 10],
+/// ];
+/// let (solutions, mine_counts) = cal_solutions_count_exact(&game_board, 2.0).unwrap();
+/// // 数字1周围有一个雷，另一个雷在其余2个格子中
+/// assert_eq!(solutions, BigUint::from(6u32));
+/// assert_eq!(mine_counts[0][1], BigUint::from(2u32));
+/// assert_eq!(mine_counts[0][2], BigUint::from(3u32));
+/// ```
+pub fn cal_solutions_count_exact(
+    board_of_game: &Vec<Vec<i32>>,
+    minenum: f64,
+) -> Result<(BigUint, Vec<Vec<BigUint>>), usize> {
+    crate::probability_engine::cal_solutions_count_exact(board_of_game, minenum)
+}
+
+/// 计算边缘与内部未打开的格子中雷数的概率分布，可用于估计内部还有多少雷。  
+/// 边缘格子指与数字相邻的未打开的格子，内部格子指其余未打开的格子，均不包括已经标出的雷。  
+/// - 输入：局面、总雷数（>=1时为总雷数，<1时为雷的比例）。  
+/// - 返回：边缘雷数的分布、内部雷数的分布，均为按雷数从小到大排列的（雷数, 概率），只包含概率不为0的雷数。  
+/// - 错误码：1=盘面矛盾, 2=枚举过长, 3=输入参数非法。
+/// # Example
+/// ```
+/// use ms_toollib::cal_mine_count_distribution;
+/// let game_board = vec![
+///     vec![ 1, 10, 10],
+///     vec![10, 10, 10],
+/// ];
+/// let (edge, off_edge) = cal_mine_count_distribution(&game_board, 2.0).unwrap();
+/// assert_eq!(edge, vec![(1, 1.0)]);
+/// assert_eq!(off_edge, vec![(1, 1.0)]);
+/// ```
+pub fn cal_mine_count_distribution(
+    board_of_game: &Vec<Vec<i32>>,
+    minenum: f64,
+) -> Result<(Vec<(usize, f64)>, Vec<(usize, f64)>), usize> {
+    crate::probability_engine::cal_mine_count_distribution(board_of_game, minenum)
+}
+
+/// 基于JSMinesweeper概率引擎的走法建议，用于训练、提示等场景。  
+/// 存在必然安全的格子时，只返回所有必然安全的格子；否则返回安全率接近最佳的格子。
+/// 进展概率指点开该格后，能推理出新的必然安全的格子的概率。死格指点开后显示的数字不能提供任何新信息的格子。  
+/// - 输入：局面、总雷数（>=1时为总雷数，<1时为雷的比例）。自动纠正错误的标雷。  
+/// - 返回：按推荐程度从高到低排序的候选格子，包括位置、安全率、进展概率、是否是死格。  
+/// - 错误码：1=盘面矛盾, 2=枚举过长, 3=输入参数非法。
+/// - 注意：只适用于平面局面，环面局面的概率见cal_probability_onboard_with_topology。
+/// # Example
+/// ```
+/// use ms_toollib::suggest_moves;
+/// let game_board = vec![
+///     vec![10, 10,  1,  1, 10,  1,  0,  0],
+///     vec![10, 10,  1, 10, 10,  3,  2,  1],
+///     vec![10, 10, 10, 10, 10, 10, 10, 10],
+///     vec![10, 10, 10, 10, 10, 10, 10, 10],
+///     vec![10, 10, 10, 10, 10, 10, 10, 10],
+///     vec![10, 10, 10, 10,  2, 10, 10, 10],
+///     vec![10, 10, 10, 10, 10, 10, 10, 10],
+///     vec![10, 10, 10, 10, 10, 10, 10, 10],
+/// ];
+/// let moves = suggest_moves(&game_board, 10.0).unwrap();
+/// // 第一个候选格子的安全率最高
+/// assert!(moves.iter().all(|m| m.safety <= moves[0].safety || m.dead));
+/// ```
+pub fn suggest_moves(
+    board_of_game: &Vec<Vec<i32>>,
+    minenum: f64,
+) -> Result<Vec<MoveSuggestion>, usize> {
+    let mut game_board = board_of_game.clone();
+    mark_board(&mut game_board, true).map_err(|_| 1usize)?;
+    crate::probability_engine::suggest_moves(&game_board, minenum)
+}
+
+/// 检查局面中是否有无法避免的猜测。  
+/// 无法避免的猜测指某个数字周围的未知格子，无论先打开其他哪些格子，都不能获得更多关于它们的信息，最终必须猜。  
+/// - 输入：局面、总雷数（>=1时为总雷数，<1时为雷的比例）。自动纠正错误的标雷。  
+/// - 返回：需要猜的格子（非死格优先），没有则返回None。  
+/// - 错误码：1=盘面矛盾, 2=枚举过长, 3=输入参数非法。
+pub fn check_for_unavoidable_guess(
+    board_of_game: &Vec<Vec<i32>>,
+    minenum: f64,
+) -> Result<Option<Vec<(usize, usize)>>, usize> {
+    let mut game_board = board_of_game.clone();
+    mark_board(&mut game_board, true).map_err(|_| 1usize)?;
+    crate::probability_engine::check_for_unavoidable_guess(&game_board, minenum)
+}
+
+/// 检查局面中是否有无法避免的50/50，包括由若干个二选一首尾相连组成的链。  
+/// - 输入：局面、总雷数（>=1时为总雷数，<1时为雷的比例）。自动纠正错误的标雷。  
+/// - 返回：50/50涉及的格子（非死格优先），没有则返回None。  
+/// - 错误码：1=盘面矛盾, 2=枚举过长, 3=输入参数非法。
+/// # Example
+/// ```
+/// use ms_toollib::check_for_unavoidable_5050;
+/// let game_board = vec![
+///     vec![0, 0],
+///     vec![1, 1],
+///     vec![10, 10],
+/// ];
+/// let tiles = check_for_unavoidable_5050(&game_board, 1.0).unwrap();
+/// assert_eq!(tiles, Some(vec![(2, 0), (2, 1)]));
+/// ```
+pub fn check_for_unavoidable_5050(
+    board_of_game: &Vec<Vec<i32>>,
+    minenum: f64,
+) -> Result<Option<Vec<(usize, usize)>>, usize> {
+    let mut game_board = board_of_game.clone();
+    mark_board(&mut game_board, true).map_err(|_| 1usize)?;
+    crate::probability_engine::check_for_unavoidable_5050(&game_board, minenum)
+}
+
+/// 检查局面中是否有无法避免的50/50或伪50/50。  
+/// 伪50/50指一个数字周围的多个格子中，有两个格子构成的、无法通过其他格子获得信息的二选一。  
+/// - 输入：局面、总雷数（>=1时为总雷数，<1时为雷的比例）。自动纠正错误的标雷。  
+/// - 返回：涉及的格子（非死格优先），没有则返回None。  
+/// - 错误码：1=盘面矛盾, 2=枚举过长, 3=输入参数非法。
+pub fn check_for_unavoidable_5050_or_pseudo(
+    board_of_game: &Vec<Vec<i32>>,
+    minenum: f64,
+) -> Result<Option<Vec<(usize, usize)>>, usize> {
+    let mut game_board = board_of_game.clone();
+    mark_board(&mut game_board, true).map_err(|_| 1usize)?;
+    crate::probability_engine::check_for_unavoidable_5050_or_pseudo(&game_board, minenum)
+}
+
 /// 计算开空概率算法。  
 /// - 输入：局面、总雷数、位置（可以同时输入多个）。  
 /// - 返回：坐标处开空的概率。  
@@ -560,6 +799,16 @@ pub fn cal_probability_cells_not_mine(
     game_board: &Vec<Vec<i32>>,
     minenum: f64,
     cells: &Vec<(usize, usize)>,
+) -> f64 {
+    cal_probability_cells_not_mine_with_topology(game_board, minenum, cells, Topology::Plane)
+}
+
+/// 在指定的拓扑结构上计算多个格子同时不是雷的概率，规则同cal_probability_cells_not_mine。
+pub fn cal_probability_cells_not_mine_with_topology(
+    game_board: &Vec<Vec<i32>>,
+    minenum: f64,
+    cells: &Vec<(usize, usize)>,
+    topology: Topology,
 ) -> f64 {
     let mut poss = 1.0;
     let mut game_board_modified = game_board.clone();
@@ -569,7 +818,9 @@ pub fn cal_probability_cells_not_mine(
         } else if game_board[x][y] == 11 {
             return 0.0;
         } else {
-            let (board_poss, _) = cal_probability_onboard(&game_board_modified, minenum).unwrap();
+            let (board_poss, _) =
+                cal_probability_onboard_with_topology(&game_board_modified, minenum, topology)
+                    .unwrap();
             poss *= 1.0 - board_poss[x][y];
             game_board_modified[x][y] = 12;
         }
@@ -603,8 +854,19 @@ pub fn solve_enumerate(
     xs: &Vec<Vec<(usize, usize)>>,
     bs: &Vec<Vec<i32>>,
 ) -> (Vec<(usize, usize)>, Vec<(usize, usize)>) {
+    // 不记录时不会出错
+    solve_enumerate_traced(a_mats, xs, bs, None).unwrap()
+}
+
+// 同solve_enumerate，传入sink时记录推理出的每个格子，依据是格子所在的段中的所有数字
+fn solve_enumerate_traced(
+    a_mats: &Vec<Vec<Vec<i32>>>,
+    xs: &Vec<Vec<(usize, usize)>>,
+    bs: &Vec<Vec<i32>>,
+    mut sink: Option<&mut SolveSink>,
+) -> Result<(Vec<(usize, usize)>, Vec<(usize, usize)>), usize> {
     if bs.is_empty() {
-        return (vec![], vec![]);
+        return Ok((vec![], vec![]));
     }
     let mut not_mine = vec![];
     let mut is_mine = vec![];
@@ -615,7 +877,7 @@ pub fn solve_enumerate(
     let mut matrixx_squeeze_s: Vec<Vec<(usize, usize)>> = vec![];
     for i in 0..block_num {
         if xs[i].len() > ENUM_LIMIT {
-            return (not_mine, is_mine);
+            return Ok((not_mine, is_mine));
         }
         let (matrix_a_squeeze, matrixx_squeeze, combination_relationship) =
             combine(&a_mats[i], &xs[i]);
@@ -636,18 +898,27 @@ pub fn solve_enumerate(
             for ii in 0..table_cell_minenum_i.len() {
                 s_num += table_cell_minenum_i[ii][jj];
             }
-            if s_num == 0 {
-                for kk in &comb_relp_s[i][jj] {
-                    not_mine.push(xs[i][*kk]);
-                }
+            let m = if s_num == 0 {
+                false
             } else if s_num == table_minenum_i[1].iter().sum::<usize>() * comb_relp_s[i][jj].len() {
-                for kk in &comb_relp_s[i][jj] {
+                true
+            } else {
+                continue;
+            };
+            for kk in &comb_relp_s[i][jj] {
+                if let Some(sink) = sink.as_deref_mut() {
+                    let witnesses = sink.block(i)?;
+                    sink.record(xs[i][*kk], m, witnesses);
+                }
+                if m {
                     is_mine.push(xs[i][*kk]);
+                } else {
+                    not_mine.push(xs[i][*kk]);
                 }
             }
         }
     }
-    (not_mine, is_mine)
+    Ok((not_mine, is_mine))
 }
 
 // 判断当前是否获胜，单次
@@ -689,7 +960,8 @@ impl IsVictory {
                     return false; // 安全性相关（发生作弊）
                 }
             }
-            if game_board[self.pointer_x][j] >= 10 && board[self.pointer_x][j] != -1 {
+            // 被遮挡的位置（-2）不需要打开
+            if game_board[self.pointer_x][j] >= 10 && board[self.pointer_x][j] >= 0 {
                 self.pointer_y = j;
                 return false;
             }
@@ -701,7 +973,7 @@ impl IsVictory {
                         return false; // 安全性相关（发生作弊）
                     }
                 }
-                if game_board[i][j] >= 10 && board[i][j] != -1 {
+                if game_board[i][j] >= 10 && board[i][j] >= 0 {
                     self.pointer_x = i;
                     self.pointer_y = j;
                     return false;
@@ -716,50 +988,46 @@ impl IsVictory {
 /// - 注意：周围一圈都是雷，那么若中间是雷不算猜，若中间不是雷算有猜。  
 /// - 注意：不考虑剩余雷数。
 pub fn is_solvable(board: &Vec<Vec<i32>>, x0: usize, y0: usize) -> bool {
+    is_solvable_with_topology(board, x0, y0, Topology::Plane)
+}
+
+/// 在指定的拓扑结构上，从指定位置开始扫，判断局面是否无猜。规则同is_solvable。
+/// - 注意：环面上不检查不可判雷结构，只用逻辑推理判断。
+pub fn is_solvable_with_topology(
+    board: &Vec<Vec<i32>>,
+    x0: usize,
+    y0: usize,
+    topology: Topology,
+) -> bool {
     if board[x0][y0] == -1 {
         // 踩雷肯定是非无猜
         return false;
     }
-    if unsolvable_structure(&board) {
+    let row = board.len();
+    let column = board[0].len();
+    // 不可判雷结构是按矩形的平面局面的边界总结的，带遮挡的局面、环面都不适用
+    if topology == Topology::Plane
+        && cal_area(board) == row * column
+        && unsolvable_structure(board)
+    {
         //若包含不可判雷结构，则不是无猜
         return false;
     }
-    let row = board.len();
-    let column = board[0].len();
-    let mut game_board = vec![vec![10; column]; row];
+    let mut game_board = get_covered_game_board(board);
     // 10是未打开，11是标雷
-    // 局面大小必须超过6*6
-    refresh_board(board, &mut game_board, vec![(x0, y0)]);
+    refresh_board_with_topology(board, &mut game_board, vec![(x0, y0)], topology);
     let mut judge = IsVictory::new(row, column);
-    if judge.is_victory(&game_board, &board) {
+    if judge.is_victory(&game_board, board) {
         return true; // 暂且认为点一下就扫开也是可以的
     }
     loop {
-        let (mut a_mats, mut xs, mut bs, _, _) = refresh_matrixs(&game_board);
-        let ans = solve_direct(&mut a_mats, &mut xs, &mut bs, &mut game_board).unwrap();
-        let not_mine;
-        if ans.0.is_empty() && ans.1.is_empty() {
-            let ans = solve_minus(&mut a_mats, &mut xs, &mut bs, &mut game_board).unwrap();
-            if ans.0.is_empty() && ans.1.is_empty() {
-                let ans = solve_enumerate(&a_mats, &xs, &bs);
-                if ans.0.is_empty() && ans.1.is_empty() {
-                    return false;
-                } else {
-                    not_mine = ans.0
-                }
-                if !ans.1.is_empty() {
-                    for (o, p) in ans.1 {
-                        game_board[o][p] = 11;
-                    }
-                }
-            } else {
-                not_mine = ans.0
+        match solve_round(&mut game_board, topology, 0, None).unwrap() {
+            Some((_, not_mine, _)) => {
+                refresh_board_with_topology(board, &mut game_board, not_mine, topology)
             }
-        } else {
-            not_mine = ans.0
+            None => return false,
         }
-        refresh_board(board, &mut game_board, not_mine);
-        if judge.is_victory(&game_board, &board) {
+        if judge.is_victory(&game_board, board) {
             return true;
         }
     }
@@ -770,9 +1038,7 @@ pub fn is_solvable(board: &Vec<Vec<i32>>, x0: usize, y0: usize) -> bool {
 /// - 注意：不考虑剩余雷数。
 /// - 返回：游戏局面的残局、解决的bbbv数
 pub fn try_solve(board: &Vec<Vec<i32>>, x0: usize, y0: usize) -> (Vec<Vec<i32>>, usize) {
-    let row = board.len();
-    let column = board[0].len();
-    let mut game_board = vec![vec![10; column]; row];
+    let mut game_board = get_covered_game_board(board);
     if board[x0][y0] == -1 {
         // 踩雷
         game_board[x0][y0] = 15;
@@ -833,9 +1099,614 @@ pub fn try_solve(board: &Vec<Vec<i32>>, x0: usize, y0: usize) -> (Vec<Vec<i32>>,
     }
 }
 
+/// 判雷引擎推理出一个格子时所用的判雷方法。
+/// 做ffi传递时用字符串"direct"、"minus"、"enumerate"。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SolveRule {
+    /// 单集合判雷（solve_direct），依据是一个数字
+    Direct,
+    /// 双集合判雷（solve_minus），依据是两个数字
+    Minus,
+    /// 枚举判雷（solve_enumerate），依据是同一段中的所有数字
+    Enumerate,
+}
+
+impl SolveRule {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SolveRule::Direct => "direct",
+            SolveRule::Minus => "minus",
+            SolveRule::Enumerate => "enumerate",
+        }
+    }
+}
+
+/// 判雷过程中推理出的一个格子。见try_solve_trace。
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SolveStep {
+    /// 第几轮推理，从0开始。同一轮中的格子是在同一个局面上推理出来的
+    pub step: usize,
+    /// 格子的位置（行，列）
+    pub cell: (usize, usize),
+    /// 是雷还是非雷
+    pub is_mine: bool,
+    /// 判雷方法
+    pub rule: SolveRule,
+    /// 作为依据的数字的位置（行，列）。第一个是推理出这个格子的数字，其后是推理出同一轮中先前的格子所用的数字
+    pub witnesses: Vec<(usize, usize)>,
+}
+
+// 推理过程的记录，见solve_direct_traced等。rows与常数向量的形状相同，是每一行依赖的数字的位置：
+// 第一个是这一行本身的数字，其后是推理出从这一行中删去的格子时用到的数字
+struct SolveSink {
+    step: usize,
+    rule: SolveRule,
+    rows: Vec<Vec<Vec<(usize, usize)>>>,
+    found: Vec<SolveStep>,
+}
+
+// 把from中没有的数字依次加到into的后面
+fn merge_witnesses(into: &mut Vec<(usize, usize)>, from: &[(usize, usize)]) {
+    for w in from {
+        if !into.contains(w) {
+            into.push(*w);
+        }
+    }
+}
+
+impl SolveSink {
+    // 第b段第i行依赖的数字。没有记录时返回错误码4
+    fn row(&self, b: usize, i: usize) -> Result<Vec<(usize, usize)>, usize> {
+        match self.rows.get(b).and_then(|rows| rows.get(i)) {
+            Some(witnesses) if !witnesses.is_empty() => Ok(witnesses.clone()),
+            _ => Err(4),
+        }
+    }
+    // 第b段中所有行依赖的数字
+    fn block(&self, b: usize) -> Result<Vec<(usize, usize)>, usize> {
+        let mut witnesses: Vec<(usize, usize)> =
+            self.rows.get(b).ok_or(4usize)?.iter().flatten().copied().collect();
+        witnesses.sort_unstable();
+        witnesses.dedup();
+        if witnesses.is_empty() {
+            return Err(4);
+        }
+        Ok(witnesses)
+    }
+    fn merge(&mut self, b: usize, i: usize, witnesses: &[(usize, usize)]) {
+        merge_witnesses(&mut self.rows[b][i], witnesses);
+    }
+    fn record(&mut self, cell: (usize, usize), is_mine: bool, witnesses: Vec<(usize, usize)>) {
+        self.found.push(SolveStep {
+            step: self.step,
+            cell,
+            is_mine,
+            rule: self.rule,
+            witnesses,
+        });
+    }
+}
+
+// 推理一轮：依次尝试单集合、双集合、枚举判雷，使用最简单的、能判出格子的方法，在局面上标出是雷（11）。
+// 返回所用的方法、非雷的格子、是雷的格子，都判不出时返回None。
+// 传入trace时，把推理出的格子及作为依据的数字记录到trace中，记为第step轮。
+fn solve_round(
+    game_board: &mut Vec<Vec<i32>>,
+    topology: Topology,
+    step: usize,
+    trace: Option<&mut Vec<SolveStep>>,
+) -> Result<Option<(SolveRule, Vec<(usize, usize)>, Vec<(usize, usize)>)>, usize> {
+    let (mut a_mats, mut xs, mut bs, rows, _, _) = refresh_matrixs_with_rows(game_board, topology);
+    let mut sink = trace.as_ref().map(|_| SolveSink {
+        step,
+        rule: SolveRule::Direct,
+        rows: rows
+            .into_iter()
+            .map(|block| block.into_iter().map(|w| vec![w]).collect())
+            .collect(),
+        found: vec![],
+    });
+    for rule in [SolveRule::Direct, SolveRule::Minus, SolveRule::Enumerate] {
+        if let Some(sink) = sink.as_mut() {
+            sink.rule = rule;
+        }
+        let (not_mine, is_mine) = match rule {
+            SolveRule::Direct => {
+                solve_direct_traced(&mut a_mats, &mut xs, &mut bs, game_board, sink.as_mut())?
+            }
+            SolveRule::Minus => {
+                solve_minus_traced(&mut a_mats, &mut xs, &mut bs, game_board, sink.as_mut())?
+            }
+            SolveRule::Enumerate => solve_enumerate_traced(&a_mats, &xs, &bs, sink.as_mut())?,
+        };
+        if not_mine.is_empty() && is_mine.is_empty() {
+            continue;
+        }
+        if rule == SolveRule::Enumerate {
+            for &(o, p) in &is_mine {
+                game_board[o][p] = 11;
+            }
+        }
+        if let (Some(trace), Some(sink)) = (trace, sink) {
+            trace.extend(sink.found);
+        }
+        return Ok(Some((rule, not_mine, is_mine)));
+    }
+    Ok(None)
+}
+
+/// 带推理过程的try_solve。从指定位置开始扫，与is_solvable相同，每轮依次尝试单集合、双集合、枚举判雷，
+/// 并记录推理出的每个格子、所用的判雷方法、作为依据的数字。
+/// - 输入：真实局面、起手位置（行，列）。
+/// - 返回：推理过程，按推理的先后排列；是否无猜扫开（与is_solvable相同）。
+/// - 错误码：局面矛盾时为6，推理出的格子找不到作为依据的数字时为4。
+/// - 注意：不考虑剩余雷数。起手和随空一起打开的格子不记录。
+/// - 注意：同一轮中先推理出的格子会从矩阵中删去，后推理出的格子的依据除了推理它的数字（排在第一个），
+/// 还包括推理出这些格子所用的数字。双集合判雷的最后会用单集合再过一轮，这样推理出的格子也记为双集合判雷。
+/// # Example
+/// ```
+/// use ms_toollib::{try_solve_trace, laymine_solvable_seeded, is_solvable, SolveRule};
+/// let (board, _) = laymine_solvable_seeded(8, 8, 10, 0, 0, 10000, 42);
+/// let (trace, solved) = try_solve_trace(&board, 0, 0).unwrap();
+/// assert_eq!(solved, is_solvable(&board, 0, 0));
+/// for s in &trace {
+///     assert_eq!(s.is_mine, board[s.cell.0][s.cell.1] == -1);
+///     assert!(!s.witnesses.is_empty());
+///     if s.rule == SolveRule::Enumerate {
+///         assert!(s.witnesses.len() >= 2);
+///     }
+/// }
+/// ```
+pub fn try_solve_trace(
+    board: &Vec<Vec<i32>>,
+    x0: usize,
+    y0: usize,
+) -> Result<(Vec<SolveStep>, bool), usize> {
+    let mut trace = vec![];
+    if board[x0][y0] == -1 {
+        return Ok((trace, false));
+    }
+    let mut game_board = get_covered_game_board(board);
+    refresh_board(board, &mut game_board, vec![(x0, y0)]);
+    let mut judge = IsVictory::new(board.len(), board[0].len());
+    let mut step = 0;
+    while !judge.is_victory(&game_board, board) {
+        match solve_round(&mut game_board, Topology::Plane, step, Some(&mut trace))? {
+            Some((_, not_mine, _)) => refresh_board(board, &mut game_board, not_mine),
+            None => return Ok((trace, false)),
+        }
+        step += 1;
+    }
+    Ok((trace, true))
+}
+
+/// 提示的操作。见hint。
+/// 做ffi传递时用字符串"left"、"right"，与cal_zini_ops中的操作相同。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HintAction {
+    /// 左键打开
+    Left,
+    /// 右键标雷
+    Right,
+}
+
+impl HintAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HintAction::Left => "left",
+            HintAction::Right => "right",
+        }
+    }
+}
+
+/// 提示：推荐的下一步操作，以及理由。见hint。
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hint {
+    /// 位置（行，列）
+    pub cell: (usize, usize),
+    /// 操作：打开或标雷
+    pub action: HintAction,
+    /// 判雷方法。None代表没有能确定的格子，按概率选择
+    pub rule: Option<SolveRule>,
+    /// 作为依据的数字的位置（行，列），与try_solve_trace相同。按概率选择时为空
+    pub witnesses: Vec<(usize, usize)>,
+    /// 不是雷的概率
+    pub safety: f64,
+}
+
+/// 提示下一步。依次用单集合、双集合、枚举判雷，使用最简单的、能判出格子的方法，
+/// 在判出的格子中选择离光标最近的；都判不出时，用总雷数计算概率，选择最安全的格子中离光标最近的。
+/// - 输入：游戏局面、总雷数（>=1时为总雷数，<1时为雷的比例）、光标位置（行，列）。
+/// - 返回：提示，没有未打开的格子时返回None。
+/// - 错误码：局面矛盾时为1或6，枚举过长时为2，参数非法时为3。
+/// - 注意：玩家标的雷可能是错的，推理时不使用；推理出的雷若已经标出，则不再提示。标错的雷可能被提示为打开。
+/// - 注意：只适用于平面局面。
+/// # Example
+/// ```
+/// use ms_toollib::{hint, HintAction, SolveRule};
+/// let game_board = vec![
+///     vec![0, 0, 0, 0],
+///     vec![1, 2, 2, 1],
+///     vec![10, 10, 10, 10],
+/// ];
+/// // 1-2-2-1，由1和2判出中间的两个雷，选离光标近的一个
+/// let h = hint(&game_board, 2.0, (2, 3)).unwrap().unwrap();
+/// assert_eq!(h.cell, (2, 2));
+/// assert_eq!(h.action, HintAction::Right);
+/// assert_eq!(h.rule, Some(SolveRule::Minus));
+/// assert_eq!(h.witnesses, vec![(1, 1), (1, 0)]);
+/// ```
+pub fn hint(
+    game_board: &Vec<Vec<i32>>,
+    mine_num: f64,
+    cursor: (usize, usize),
+) -> Result<Option<Hint>, usize> {
+    let mut board = game_board.clone();
+    for row in board.iter_mut() {
+        for num in row.iter_mut() {
+            if *num == 11 || *num == 12 {
+                *num = 10;
+            }
+        }
+    }
+    let distance =
+        |&(x, y): &(usize, usize)| x.abs_diff(cursor.0).pow(2) + y.abs_diff(cursor.1).pow(2);
+    loop {
+        let mut marked = board.clone();
+        let mut trace = vec![];
+        let is_mine = match solve_round(&mut marked, Topology::Plane, 0, Some(&mut trace))? {
+            Some((_, _, is_mine)) => is_mine,
+            None => break,
+        };
+        // 推理出的雷若已经标出，则不再提示。只提示依据最少、即不依赖本轮其他推理结果的格子
+        trace.retain(|s| !(s.is_mine && game_board[s.cell.0][s.cell.1] == 11));
+        let fewest = trace.iter().map(|s| s.witnesses.len()).min();
+        let best = trace
+            .into_iter()
+            .filter(|s| Some(s.witnesses.len()) == fewest)
+            .min_by_key(|s| distance(&s.cell))
+            .map(|s| Hint {
+                cell: s.cell,
+                action: if s.is_mine {
+                    HintAction::Right
+                } else {
+                    HintAction::Left
+                },
+                rule: Some(s.rule),
+                witnesses: s.witnesses,
+                safety: if s.is_mine { 0.0 } else { 1.0 },
+            });
+        if best.is_some() {
+            return Ok(best);
+        }
+        // 推理出的都是已经标出的雷，确认以后重新推理
+        for (o, p) in is_mine {
+            board[o][p] = 11;
+        }
+    }
+    let (poss, _) = cal_probability_onboard(&board, mine_num)?;
+    let mut best: Option<Hint> = None;
+    for i in 0..board.len() {
+        for j in 0..board[0].len() {
+            if board[i][j] != 10 || game_board[i][j] != 10 {
+                continue;
+            }
+            let safety = 1.0 - poss[i][j];
+            let better = match &best {
+                None => true,
+                Some(h) => {
+                    safety > h.safety + 1e-9
+                        || (safety > h.safety - 1e-9 && distance(&(i, j)) < distance(&h.cell))
+                }
+            };
+            if better {
+                best = Some(Hint {
+                    cell: (i, j),
+                    action: HintAction::Left,
+                    rule: None,
+                    witnesses: vec![],
+                    safety,
+                });
+            }
+        }
+    }
+    Ok(best)
+}
+
+/// 局面的解题复杂度，即从起手开始，用各种判雷方法判出的格子数，以及必须猜的次数。见cal_solution_complexity。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SolutionComplexity {
+    /// 用单集合判雷（solve_direct）判出的格子数，包括雷和非雷
+    pub direct: usize,
+    /// 用双集合判雷（solve_minus）判出的格子数
+    pub minus: usize,
+    /// 用枚举判雷（solve_enumerate）或总雷数判出的格子数
+    pub enumerate: usize,
+    /// 必须猜的次数，每次猜概率最小的格子，包括猜到雷的
+    pub guess: usize,
+}
+
+/// 计算局面的解题复杂度。从起手位置开始，与is_solvable相同，依次尝试单集合、双集合、枚举判雷，
+/// 每次使用最简单的、能判出格子的方法，并统计各方法判出的格子数。
+/// 都判不出时，先用总雷数计算概率，概率为0的格子计入枚举；仍然没有时，必须猜一次，
+/// 猜的是概率最小的格子（并列时取最靠前的），选择时不读取真实局面；猜到雷时把它标出并继续。
+/// - 输入：真实局面、起手位置（行，列）。
+/// - 返回：各方法判出的格子数与必须猜的次数。起手位置是雷时全为0。
+/// - 注意：随空一起打开的格子不计入任何一种方法。
+/// # Example
+/// ```
+/// use ms_toollib::{cal_solution_complexity, laymine_solvable_seeded, is_solvable};
+/// let (board, _) = laymine_solvable_seeded(8, 8, 10, 0, 0, 10000, 42);
+/// let complexity = cal_solution_complexity(&board, 0, 0);
+/// assert_eq!(complexity.guess == 0, is_solvable(&board, 0, 0));
+/// ```
+pub fn cal_solution_complexity(board: &Vec<Vec<i32>>, x0: usize, y0: usize) -> SolutionComplexity {
+    cal_solution_complexity_with_topology(board, x0, y0, Topology::Plane)
+}
+
+/// 在指定的拓扑结构上计算局面的解题复杂度，规则同cal_solution_complexity。
+pub fn cal_solution_complexity_with_topology(
+    board: &Vec<Vec<i32>>,
+    x0: usize,
+    y0: usize,
+    topology: Topology,
+) -> SolutionComplexity {
+    let mut complexity = SolutionComplexity::default();
+    if board[x0][y0] == -1 {
+        return complexity;
+    }
+    let row = board.len();
+    let column = board[0].len();
+    let minenum = board.iter().flatten().filter(|&&x| x == -1).count();
+    let mut game_board = get_covered_game_board(board);
+    refresh_board_with_topology(board, &mut game_board, vec![(x0, y0)], topology);
+    let mut judge = IsVictory::new(row, column);
+    while !judge.is_victory(&game_board, board) {
+        if let Some((rule, not_mine, is_mine)) =
+            solve_round(&mut game_board, topology, 0, None).unwrap()
+        {
+            let num = not_mine.len() + is_mine.len();
+            match rule {
+                SolveRule::Direct => complexity.direct += num,
+                SolveRule::Minus => complexity.minus += num,
+                SolveRule::Enumerate => complexity.enumerate += num,
+            }
+            refresh_board_with_topology(board, &mut game_board, not_mine, topology);
+            continue;
+        }
+        // 用总雷数计算概率。局面过大、无法计算时，所有未知格子的概率视为相同
+        let poss = cal_probability_onboard_with_topology(&game_board, minenum as f64, topology)
+            .map(|(p, _)| p)
+            .unwrap_or_else(|_| vec![vec![0.5; column]; row]);
+        let mut safe_cells = vec![];
+        let mut best: Option<((usize, usize), f64)> = None;
+        for i in 0..row {
+            for j in 0..column {
+                if game_board[i][j] != 10 {
+                    continue;
+                }
+                if poss[i][j] <= 0.0 {
+                    safe_cells.push((i, j));
+                }
+                if best.is_none_or(|(_, p)| poss[i][j] < p) {
+                    best = Some(((i, j), poss[i][j]));
+                }
+            }
+        }
+        if !safe_cells.is_empty() {
+            complexity.enumerate += safe_cells.len();
+            refresh_board_with_topology(board, &mut game_board, safe_cells, topology);
+        } else if let Some((cell, _)) = best {
+            complexity.guess += 1;
+            if board[cell.0][cell.1] == -1 {
+                // 猜到雷时把它标出，继续推理
+                game_board[cell.0][cell.1] = 11;
+            } else {
+                refresh_board_with_topology(board, &mut game_board, vec![cell], topology);
+            }
+        } else {
+            break;
+        }
+    }
+    complexity
+}
+
+/// 按概率引擎的最佳候选玩局面时，猜的次数与存活率的统计。见cal_guess_stats。
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GuessStats {
+    /// 是否是精确计算的结果。否则是模拟的结果
+    pub is_exact: bool,
+    /// 精确计算时为不同走法的数量，模拟时为模拟的局数
+    pub runs: usize,
+    /// 平均猜的次数，包括猜错的那一次
+    pub mean_guesses: f64,
+    /// 猜的次数的分布，下标为猜的次数，元素为概率
+    pub distribution: Vec<f64>,
+    /// 扫开局面的概率，即各次猜的安全率的乘积的均值
+    pub survival: f64,
+    /// 猜的位置（行，列）、在此处猜的概率、在此处猜时的平均安全率，按概率从大到小排列
+    pub guesses: Vec<((usize, usize), f64, f64)>,
+}
+
+// 猜的位置（行，列）及其安全率
+type GuessedCells = Vec<((usize, usize), f64)>;
+
+// 从当前的游戏局面开始，打开所有能判出的格子。扫开时返回None，否则返回概率引擎的最佳候选
+// （安全率、进展概率、是否死格都与第一个候选相同的格子）及其安全率。
+fn advance_to_guess(
+    board: &Vec<Vec<i32>>,
+    game_board: &mut Vec<Vec<i32>>,
+    judge: &mut IsVictory,
+    minenum: usize,
+) -> Result<Option<GuessedCells>, usize> {
+    loop {
+        if judge.is_victory(game_board, board) {
+            return Ok(None);
+        }
+        if let Some((_, not_mine, _)) = solve_round(game_board, Topology::Plane, 0, None)? {
+            refresh_board(board, game_board, not_mine);
+            continue;
+        }
+        let moves = suggest_moves(game_board, minenum as f64)?;
+        let safe_cells: Vec<(usize, usize)> = moves
+            .iter()
+            .filter(|m| m.safety >= 1.0 - 1e-9)
+            .map(|m| m.pos)
+            .collect();
+        if !safe_cells.is_empty() {
+            refresh_board(board, game_board, safe_cells);
+            continue;
+        }
+        let best = moves.first().ok_or(1usize)?;
+        return Ok(Some(
+            moves
+                .iter()
+                .filter(|m| {
+                    (m.safety - best.safety).abs() < 1e-9
+                        && (m.progress - best.progress).abs() < 1e-9
+                        && m.dead == best.dead
+                })
+                .map(|m| (m.pos, m.safety))
+                .collect(),
+        ));
+    }
+}
+
+/// 估计按最优策略玩某个局面时，猜的次数与存活率，用于给生成的或录像中的局面评级。  
+/// 从起手位置开始，能判的格子都判出来；判不出时，在概率引擎的最佳候选（见suggest_moves）中等概率地选一个猜，
+/// 猜的结果按真实局面揭开，猜到雷则这种走法结束。最佳候选并列时走法会分叉：不同走法的数量不超过runs时精确计算，
+/// 否则用种子模拟runs局。
+/// - 输入：真实局面、起手位置（行，列）、精确计算的走法数上限兼模拟的局数。
+/// - 返回：猜的次数的均值、分布，存活率，以及猜的位置。起手位置是雷时存活率为0，且不计入猜的次数。
+/// - 错误码：1=盘面矛盾, 2=枚举过长。
+/// - 注意：与is_solvable不同，判不出时不会停下，而是继续猜。
+/// - 注意：存活率不是在这个局面上是否猜对，而是每种走法上各次猜的安全率（概率引擎给出）的乘积的均值，
+///   因此即使只有一种走法，存活率也可以在0和1之间。在这个局面上猜到雷的走法无法继续，之后不再有猜测计入。
+/// - 注意：只适用于平面局面。
+/// # Example
+/// ```
+/// use ms_toollib::cal_guess_stats_seeded;
+/// // 右边的两个格子是50/50
+/// let board = vec![vec![0, 1, -1], vec![0, 1, 1]];
+/// let stats = cal_guess_stats_seeded(&board, 0, 0, 100, 42).unwrap();
+/// assert!(stats.is_exact);
+/// assert_eq!(stats.distribution, vec![0.0, 1.0]);
+/// assert_eq!(stats.survival, 0.5);
+/// ```
+pub fn cal_guess_stats(
+    board: &Vec<Vec<i32>>,
+    x0: usize,
+    y0: usize,
+    runs: usize,
+) -> Result<GuessStats, usize> {
+    cal_guess_stats_seeded(board, x0, y0, runs, SeededRng::random_seed())
+}
+
+/// 用指定种子估计按最优策略玩某个局面时，猜的次数与存活率。见cal_guess_stats。
+pub fn cal_guess_stats_seeded(
+    board: &Vec<Vec<i32>>,
+    x0: usize,
+    y0: usize,
+    runs: usize,
+    seed: u64,
+) -> Result<GuessStats, usize> {
+    let runs = runs.max(1);
+    if board[x0][y0] == -1 {
+        return Ok(GuessStats {
+            is_exact: true,
+            runs: 1,
+            mean_guesses: 0.0,
+            distribution: vec![1.0],
+            survival: 0.0,
+            guesses: vec![],
+        });
+    }
+    let row = board.len();
+    let column = board[0].len();
+    let minenum = board.iter().flatten().filter(|&&x| x == -1).count();
+    let mut start = get_covered_game_board(board);
+    refresh_board(board, &mut start, vec![(x0, y0)]);
+    // 每种走法的（权重、猜的位置及安全率）
+    let mut leaves: Vec<(f64, GuessedCells)> = vec![];
+    let mut is_exact = true;
+    let mut stack = vec![(start.clone(), 1.0, vec![])];
+    while let Some((mut game_board, weight, guessed)) = stack.pop() {
+        // IsVictory只向前扫描，每个分支都要重新判断
+        let mut judge = IsVictory::new(row, column);
+        let ties = match advance_to_guess(board, &mut game_board, &mut judge, minenum)? {
+            Some(ties) => ties,
+            None => {
+                leaves.push((weight, guessed));
+                continue;
+            }
+        };
+        let weight = weight / ties.len() as f64;
+        for (cell, safety) in ties {
+            let mut guessed = guessed.clone();
+            guessed.push((cell, safety));
+            if board[cell.0][cell.1] == -1 {
+                leaves.push((weight, guessed));
+            } else {
+                let mut game_board = game_board.clone();
+                refresh_board(board, &mut game_board, vec![cell]);
+                stack.push((game_board, weight, guessed));
+            }
+        }
+        if leaves.len() + stack.len() > runs {
+            is_exact = false;
+            break;
+        }
+    }
+    if !is_exact {
+        leaves.clear();
+        let mut rng = SeededRng::new(seed);
+        for _ in 0..runs {
+            let mut game_board = start.clone();
+            let mut guessed = vec![];
+            let mut judge = IsVictory::new(row, column);
+            while let Some(ties) = advance_to_guess(board, &mut game_board, &mut judge, minenum)? {
+                let (cell, safety) = ties[rng.gen_below(ties.len())];
+                guessed.push((cell, safety));
+                if board[cell.0][cell.1] == -1 {
+                    break;
+                }
+                refresh_board(board, &mut game_board, vec![cell]);
+            }
+            leaves.push((1.0 / runs as f64, guessed));
+        }
+    }
+    let mut stats = GuessStats {
+        is_exact,
+        runs: leaves.len(),
+        ..GuessStats::default()
+    };
+    let mut cells: HashMap<(usize, usize), (f64, f64)> = HashMap::new();
+    for (weight, guessed) in &leaves {
+        if stats.distribution.len() <= guessed.len() {
+            stats.distribution.resize(guessed.len() + 1, 0.0);
+        }
+        stats.distribution[guessed.len()] += weight;
+        stats.mean_guesses += weight * guessed.len() as f64;
+        stats.survival += weight * guessed.iter().map(|g| g.1).product::<f64>();
+        for &(cell, safety) in guessed {
+            let entry = cells.entry(cell).or_insert((0.0, 0.0));
+            entry.0 += weight;
+            entry.1 += weight * safety;
+        }
+    }
+    stats.guesses = cells
+        .into_iter()
+        .map(|(cell, (p, s))| (cell, p, s / p))
+        .collect();
+    stats
+        .guesses
+        .sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    Ok(stats)
+}
+
 /// 删选法多（8）线程无猜埋雷。对于雷密度很高的局面，多线程比单线程更快。  
 /// - 输入：高、宽、雷数、第几行、第几列、最大尝试次数。  
-/// - 返回: (是否成功、3BV、该线程的尝试次数)。  
+/// - 返回: (局面、是否成功)。需要线程数、进度、取消或尝试次数时，用laymine_solvable_thread_with_control。  
 /// - 注意：若不成功返回最后生成的局面，此时则不一定无猜。
 /// - 局限：雷的密度无法设得很大。以高级的局面为例，雷数最多设到大约130左右。
 /// - 用python调用时的示例：
@@ -850,60 +1721,305 @@ pub fn laymine_solvable_thread(
     minenum: usize,
     x0: usize,
     y0: usize,
-    mut max_times: usize,
+    max_times: usize,
 ) -> (Vec<Vec<i32>>, bool) {
-    let mut game_board = vec![vec![0; column]; row];
-    let mut handles = vec![];
-    let flag_exit = Arc::new(Mutex::new(0));
-    let (tx, rx) = mpsc::channel(); // mpsc 是多个发送者，一个接收者
-                                    // println!("{:?}", thread::available_parallelism().unwrap());
-    for ii in (1..=8).rev() {
-        let tx_ = mpsc::Sender::clone(&tx);
-        let max_time = max_times / ii;
-        max_times -= max_time;
-        let flag_exit = Arc::clone(&flag_exit);
-        let handle = thread::spawn(move || {
-            // let Num3BV;
-            let mut counter = 0;
-            let mut board = vec![vec![0; column]; row];
-            // let mut para = [0, 0, 0];
-            while counter < max_time {
-                {
-                    let f = flag_exit.lock().unwrap();
-                    if *f == 1 {
-                        break;
-                    }
-                } // 这段用花括号控制生命周期
-                let board_ = laymine_op(row, column, minenum, x0, y0);
-                counter += 1;
-                if is_solvable(&board_, x0, y0) {
-                    for i in 0..row {
-                        for j in 0..column {
-                            board[i][j] = board_[i][j];
+    laymine_solvable_thread_seeded(row, column, minenum, x0, y0, max_times, SeededRng::random_seed())
+}
+
+/// 带种子的删选法多（8）线程无猜埋雷。
+/// - 输入：高、宽、雷数、第几行、第几列、最大尝试次数、种子。
+/// - 返回：局面、是否成功。
+/// - 第k次尝试使用`SeededRng::new_stream(seed, k)`埋雷，返回序号最小的成功的尝试，
+///   因此结果与线程的调度无关，并且与相同参数的laymine_solvable_seeded完全相同。
+#[cfg(any(feature = "py", feature = "rs"))]
+pub fn laymine_solvable_thread_seeded(
+    row: usize,
+    column: usize,
+    minenum: usize,
+    x0: usize,
+    y0: usize,
+    max_times: usize,
+    seed: u64,
+) -> (Vec<Vec<i32>>, bool) {
+    let (board, flag, _) = laymine_solvable_thread_with_control(
+        row,
+        column,
+        minenum,
+        x0,
+        y0,
+        max_times,
+        seed,
+        &ThreadControl::default(),
+    );
+    (board, flag)
+}
+
+/// 多线程计算的控制器：指定线程数、查询进度、提前取消。
+/// - 克隆得到的控制器共享同一个进度计数器和取消标志，因此可以把克隆交给其他线程，
+///   在计算进行时查询进度或取消计算。
+/// - 每次开始计算时，进度会被清零；取消标志不会被清除，被取消的控制器要先用reset复位才能再次使用。
+/// # Example
+/// ```
+/// use ms_toollib::{laymine_solvable_thread_with_control, ThreadControl};
+/// let control = ThreadControl::new(2);
+/// let (board, flag, attempts) =
+///     laymine_solvable_thread_with_control(8, 8, 10, 0, 0, 100000, 1, &control);
+/// assert!(flag);
+/// assert_eq!(control.get_progress(), attempts);
+/// ```
+#[cfg(any(feature = "py", feature = "rs"))]
+#[derive(Clone, Debug)]
+pub struct ThreadControl {
+    thread_num: usize,
+    progress: Arc<AtomicUsize>,
+    cancelled: Arc<AtomicBool>,
+}
+
+#[cfg(any(feature = "py", feature = "rs"))]
+impl Default for ThreadControl {
+    fn default() -> Self {
+        ThreadControl::new(8)
+    }
+}
+
+#[cfg(any(feature = "py", feature = "rs"))]
+impl ThreadControl {
+    /// 指定线程数。为0时使用系统可用的并行度。
+    pub fn new(thread_num: usize) -> ThreadControl {
+        let thread_num = if thread_num == 0 {
+            thread::available_parallelism().map_or(1, |n| n.get())
+        } else {
+            thread_num
+        };
+        ThreadControl {
+            thread_num,
+            progress: Arc::new(AtomicUsize::new(0)),
+            cancelled: Arc::new(AtomicBool::new(false)),
+        }
+    }
+    pub fn get_thread_num(&self) -> usize {
+        self.thread_num
+    }
+    /// 已经完成的尝试次数（埋雷）或采样局数（3BV采样）。
+    pub fn get_progress(&self) -> usize {
+        self.progress.load(Ordering::Relaxed)
+    }
+    /// 请求取消计算。正在进行的计算会尽快返回已有的结果。
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+    /// 清除进度和取消标志，使被取消的控制器可以再次使用。不要在计算进行时调用。
+    pub fn reset(&self) {
+        self.progress.store(0, Ordering::Relaxed);
+        self.cancelled.store(false, Ordering::Relaxed);
+    }
+}
+
+/// 可控制的删选法多线程无猜埋雷。规则同laymine_solvable_thread_seeded。
+/// - 输入：高、宽、雷数、第几行、第几列、最大尝试次数、种子、控制器。
+/// - 返回：局面、是否成功、所有线程实际的总尝试次数。
+/// - 未被取消时，局面与线程数无关，与相同参数的laymine_solvable_seeded完全相同；
+///   总尝试次数可能略多于成功的尝试的序号，因为其他线程可能同时在尝试。
+/// - 被取消时，返回已经找到的任意一个无猜局面，或者不成功。
+#[cfg(any(feature = "py", feature = "rs"))]
+pub fn laymine_solvable_thread_with_control(
+    row: usize,
+    column: usize,
+    minenum: usize,
+    x0: usize,
+    y0: usize,
+    max_times: usize,
+    seed: u64,
+    control: &ThreadControl,
+) -> (Vec<Vec<i32>>, bool, usize) {
+    let (board, k) = laymine_thread_search(
+        row,
+        column,
+        minenum,
+        x0,
+        y0,
+        true,
+        max_times,
+        seed,
+        |board| is_solvable(board, x0, y0),
+        control,
+    );
+    (board, k < max_times, control.get_progress())
+}
+
+/// 多线程筛选埋雷的公共部分。第k次尝试使用`SeededRng::new_stream(seed, k)`埋雷，
+/// 返回满足条件的序号最小的局面及其序号。全部失败时，返回`SeededRng::new_stream(seed, max_times)`
+/// 埋雷的局面及max_times。
+/// - op_start为true时按laymine_op的规则埋雷，否则按laymine的规则埋雷。
+#[cfg(any(feature = "py", feature = "rs"))]
+fn laymine_thread_search<F>(
+    row: usize,
+    column: usize,
+    minenum: usize,
+    x0: usize,
+    y0: usize,
+    op_start: bool,
+    max_times: usize,
+    seed: u64,
+    accept: F,
+    control: &ThreadControl,
+) -> (Vec<Vec<i32>>, usize)
+where
+    F: Fn(&Vec<Vec<i32>>) -> bool + Sync,
+{
+    let thread_num = control.thread_num;
+    control.progress.store(0, Ordering::Relaxed);
+    let lay = |k: usize| {
+        let mut rng = SeededRng::new_stream(seed, k as u64);
+        if op_start {
+            laymine_op_rng(row, column, minenum, x0, y0, &mut rng)
+        } else {
+            laymine_rng(row, column, minenum, x0, y0, &mut rng)
+        }
+    };
+    // 目前找到的序号最小的成功的尝试
+    let found = Mutex::new((max_times, vec![]));
+    thread::scope(|scope| {
+        for t in 0..thread_num {
+            let (found, lay, accept) = (&found, &lay, &accept);
+            scope.spawn(move || {
+                for k in (t..max_times).step_by(thread_num) {
+                    {
+                        let f = found.lock().unwrap();
+                        if f.0 < k || control.is_cancelled() {
+                            break;
                         }
+                    } // 这段用花括号控制生命周期
+                    let board = lay(k);
+                    control.progress.fetch_add(1, Ordering::Relaxed);
+                    if accept(&board) {
+                        let mut f = found.lock().unwrap();
+                        if k < f.0 {
+                            *f = (k, board);
+                        }
+                        break;
                     }
-                    let mut f = flag_exit.lock().unwrap();
-                    *f = 1;
-                    tx_.send((board, true)).unwrap();
-                    break;
                 }
-            }
-            let board_ = laymine_op(row, column, minenum, x0, y0);
-            // Num3BV = cal_bbbv(&board_);
-            tx_.send((board_, false)).unwrap();
-        });
-        handles.push(handle);
-    }
-    for handle in handles {
-        handle.join().unwrap();
-    }
-    let received = rx.recv().unwrap(); // 尝试次数仅仅为单个线程的次数，并不准
-    for i in 0..row {
-        for j in 0..column {
-            game_board[i][j] = received.0[i][j];
+            });
         }
+    });
+    let (k, board) = found.into_inner().unwrap();
+    if k < max_times {
+        return (board, k);
     }
-    (game_board, received.1)
+    (lay(max_times), max_times)
+}
+
+/// 筛选法多（8）线程埋雷。反复埋雷，直到局面满足筛选条件（3BV、op、isl、ZiNi、HZiNi、各数字的个数、是否无猜的范围）。
+/// - 输入：高、宽、雷数、起手行数、起手列数、是否起手开空（true时按laymine_op的规则埋雷，否则按laymine的规则）、筛选条件、最大尝试次数。
+/// - 返回：局面、是否成功、被拒绝的局面数。
+/// - 注意：若不成功返回最后生成的局面，此时则不一定满足条件。
+/// - 用python调用时的示例：
+/// ```python
+/// import ms_toollib as ms
+/// (board, flag, rejected) = ms.laymine_filtered(16, 30, 99, 3, 20, bbbv=(150, 180), op=(0, 3))
+/// ```
+#[cfg(any(feature = "py", feature = "rs"))]
+pub fn laymine_filtered(
+    row: usize,
+    column: usize,
+    minenum: usize,
+    x0: usize,
+    y0: usize,
+    op_start: bool,
+    filter: &BoardFilter,
+    max_times: usize,
+) -> (Vec<Vec<i32>>, bool, usize) {
+    laymine_filtered_seeded(
+        row,
+        column,
+        minenum,
+        x0,
+        y0,
+        op_start,
+        filter,
+        max_times,
+        SeededRng::random_seed(),
+    )
+}
+
+/// 带种子的筛选法多（8）线程埋雷。规则同laymine_filtered，结果与线程的调度无关。
+/// - 第k次（从0开始）尝试使用`SeededRng::new_stream(seed, k)`埋雷，返回序号最小的满足条件的局面，
+///   因此被拒绝的局面数就是该序号。
+#[cfg(any(feature = "py", feature = "rs"))]
+pub fn laymine_filtered_seeded(
+    row: usize,
+    column: usize,
+    minenum: usize,
+    x0: usize,
+    y0: usize,
+    op_start: bool,
+    filter: &BoardFilter,
+    max_times: usize,
+    seed: u64,
+) -> (Vec<Vec<i32>>, bool, usize) {
+    laymine_filtered_with_control(
+        row,
+        column,
+        minenum,
+        x0,
+        y0,
+        op_start,
+        filter,
+        max_times,
+        seed,
+        &ThreadControl::default(),
+    )
+}
+
+/// 可控制的筛选法多线程埋雷。规则同laymine_filtered_seeded。
+/// - 输入：高、宽、雷数、起手行数、起手列数、是否起手开空、筛选条件、最大尝试次数、种子、控制器。
+/// - 返回：局面、是否成功、被拒绝的局面数。所有线程实际的总尝试次数见控制器的进度。
+/// - 未被取消时，局面与线程数无关，与相同参数的laymine_filtered_seeded完全相同。
+/// - 被取消时，返回已经找到的满足条件的局面，或者不成功。
+/// # Example
+/// ```
+/// use ms_toollib::{laymine_filtered_with_control, BoardFilter, ThreadControl};
+/// let filter = BoardFilter {
+///     bbbv: Some((10, 20)),
+///     ..BoardFilter::default()
+/// };
+/// let control = ThreadControl::new(2);
+/// let (board, flag, rejected) =
+///     laymine_filtered_with_control(8, 8, 10, 0, 0, false, &filter, 100000, 1, &control);
+/// assert!(flag);
+/// assert!(control.get_progress() > rejected);
+/// assert_eq!(board.len(), 8);
+/// ```
+#[cfg(any(feature = "py", feature = "rs"))]
+pub fn laymine_filtered_with_control(
+    row: usize,
+    column: usize,
+    minenum: usize,
+    x0: usize,
+    y0: usize,
+    op_start: bool,
+    filter: &BoardFilter,
+    max_times: usize,
+    seed: u64,
+    control: &ThreadControl,
+) -> (Vec<Vec<i32>>, bool, usize) {
+    let (board, k) = laymine_thread_search(
+        row,
+        column,
+        minenum,
+        x0,
+        y0,
+        op_start,
+        max_times,
+        seed,
+        |board| filter.is_satisfied(&mut Board::new(board.clone()), x0, y0),
+        control,
+    );
+    (board, k < max_times, k)
 }
 
 /// 删选法单线程无猜埋雷。不可以生成任意雷密度的无猜局面。但雷满足均匀分布。  
@@ -923,17 +2039,32 @@ pub fn laymine_solvable(
     y0: usize,
     max_times: usize,
 ) -> (Vec<Vec<i32>>, bool) {
-    let mut times = 0;
-    let mut board;
-    while times < max_times {
-        board = laymine_op(row, column, minenum, x0, y0);
-        times += 1;
+    laymine_solvable_seeded(row, column, minenum, x0, y0, max_times, SeededRng::random_seed())
+}
+
+/// 带种子的删选法单线程无猜埋雷。相同的种子在各个平台上生成相同的局面。
+/// - 输入：高、宽、雷数、起手行数、起手列数、最大尝试次数、种子。
+/// - 返回：局面、是否成功。
+/// - 第k次（从0开始）尝试使用`SeededRng::new_stream(seed, k)`埋雷（规则同laymine_op_seeded），
+///   全部失败时，用`SeededRng::new_stream(seed, max_times)`埋雷并返回。
+pub fn laymine_solvable_seeded(
+    row: usize,
+    column: usize,
+    minenum: usize,
+    x0: usize,
+    y0: usize,
+    max_times: usize,
+    seed: u64,
+) -> (Vec<Vec<i32>>, bool) {
+    for k in 0..max_times {
+        let mut rng = SeededRng::new_stream(seed, k as u64);
+        let board = laymine_op_rng(row, column, minenum, x0, y0, &mut rng);
         if is_solvable(&board, x0, y0) {
             return (board, true);
         }
     }
-    board = laymine_op(row, column, minenum, x0, y0);
-    (board, false)
+    let mut rng = SeededRng::new_stream(seed, max_times as u64);
+    (laymine_op_rng(row, column, minenum, x0, y0, &mut rng), false)
 }
 
 /// 调整法无猜埋雷。可以生成任意雷密度的无猜局面。但雷不满足均匀分布。  
@@ -953,8 +2084,23 @@ pub fn laymine_solvable_adjust(
     minenum: usize,
     x0: usize,
     y0: usize,
+) -> (Vec<Vec<i32>>, bool) {
+    laymine_solvable_adjust_seeded(row, column, minenum, x0, y0, SeededRng::random_seed())
+}
+
+/// 带种子的调整法无猜埋雷。规则同laymine_solvable_adjust，相同的种子在各个平台上生成相同的局面。
+/// - 输入：高、宽、雷数、起手行数、起手列数、种子
+/// - 返回局面、是否成功
+pub fn laymine_solvable_adjust_seeded(
+    row: usize,
+    column: usize,
+    minenum: usize,
+    x0: usize,
+    y0: usize,
+    seed: u64,
 ) -> (Vec<Vec<i32>>, bool) {
     // 利用局面调整算法，无猜埋雷
+    let mut rng = SeededRng::new(seed);
     let mut area_op = 9;
     if x0 == 0 || y0 == 0 || x0 == row - 1 || y0 == column - 1 {
         if x0 == 0 && y0 == 0
@@ -969,7 +2115,7 @@ pub fn laymine_solvable_adjust(
     }
     if row * column - area_op < minenum {
         // 雷数太多以致起手无法开空，此时放弃无猜，返回任意一种局面
-        let t = laymine(row, column, minenum, x0, y0);
+        let t = laymine_rng(row, column, minenum, x0, y0, &mut rng);
         if row * column - minenum == 1 {
             return (t, true);
         } else {
@@ -977,7 +2123,7 @@ pub fn laymine_solvable_adjust(
         }
     }
     if row * column == area_op + minenum {
-        return (laymine_op(row, column, minenum, x0, y0), true);
+        return (laymine_op_rng(row, column, minenum, x0, y0, &mut rng), true);
     }
 
     // 重试循环：adjust_step失败或有猜时重新调整，最多10次
@@ -1012,6 +2158,7 @@ pub fn laymine_solvable_adjust(
             max_total_calls,
             x0,
             y0,
+            &mut rng,
         );
         if !flag || b.is_empty() {
             continue;
@@ -1040,7 +2187,7 @@ pub fn laymine_solvable_adjust(
             return (b, true);
         }
     }
-    (laymine_op(row, column, minenum, x0, y0), false)
+    (laymine_op_rng(row, column, minenum, x0, y0, &mut rng), false)
 }
 
 // fn print_positions(matrix: &Vec<Vec<i32>>, num: i32) {
@@ -1082,6 +2229,7 @@ fn adjust_step(
     max_total_calls: usize,
     x0: usize,
     y0: usize,
+    rng: &mut SeededRng,
 ) -> (Vec<Vec<i32>>, bool, bool) {
     *total_calls += 1;
     if *total_calls > max_total_calls || depth >= max_depth {
@@ -1160,6 +2308,7 @@ fn adjust_step(
             max_total_calls,
             x0,
             y0,
+            rng,
         );
     }
 
@@ -1202,7 +2351,7 @@ fn adjust_step(
     }) {
         let loop_time = if first_loop_flag { 5 } else { 1 };
         for _ in 0..loop_time {
-            adjust_the_area_on_board(&mut board_clone, &front_xs_0, minenum);
+            adjust_the_area_on_board(&mut board_clone, &front_xs_0, minenum, rng);
             let mut not_mine = vec![];
             let mut is_mine = vec![];
             for (i, mut b_s) in bses.clone().into_iter().enumerate() {
@@ -1259,6 +2408,7 @@ fn adjust_step(
                     max_total_calls,
                     x0,
                     y0,
+                    rng,
                 );
                 if a_ok {
                     if !a_board.is_empty() {
@@ -1283,6 +2433,7 @@ fn adjust_the_area_on_board(
     board: &mut Vec<Vec<i32>>,
     area_current_adjust: &Vec<(usize, usize)>,
     minenum: usize,
+    rng: &mut SeededRng,
 ) {
     // let row = board.len();
     // let column = board[0].len();
@@ -1290,15 +2441,7 @@ fn adjust_the_area_on_board(
     let cell_num = area_current_adjust.len();
     let mut b = vec![0; cell_num - minenum];
     b.append(&mut vec![-1; minenum]);
-
-    #[cfg(any(feature = "py", feature = "rs"))]
-    let mut rng = thread_rng();
-    // let mut rng = StdRng::seed_from_u64(532);
-    #[cfg(any(feature = "py", feature = "rs"))]
-    b.shuffle(&mut rng);
-
-    #[cfg(feature = "js")]
-    b.shuffle_();
+    rng.shuffle(&mut b);
 
     let mut id = 0;
     // for i in area_current_adjust {
@@ -1316,26 +2459,275 @@ fn adjust_the_area_on_board(
 /// 埋雷并计算高级局面3BV的引擎，用于研究高级3BV的分布。16线程。传入局数，例如1000 000。试一下你的电脑算的有多块吧。  
 #[cfg(any(feature = "py", feature = "rs"))]
 pub fn sample_bbbvs_exp(x0: usize, y0: usize, n: usize) -> [usize; 382] {
+    sample_bbbvs_exp_seeded(x0, y0, n, SeededRng::random_seed())
+}
+
+/// 带种子的高级3BV采样，结果可复现。规则见sample_bbbvs_exp_with_control。
+#[cfg(any(feature = "py", feature = "rs"))]
+pub fn sample_bbbvs_exp_seeded(x0: usize, y0: usize, n: usize, seed: u64) -> [usize; 382] {
+    sample_bbbvs_exp_with_control(x0, y0, n, seed, &ThreadControl::new(16)).0
+}
+
+/// 可控制的高级3BV采样。
+/// - 输入：起手行数、起手列数、局数、种子、控制器。
+/// - 返回：3BV的分布（下标为3BV）、实际的采样局数。
+/// - 采样按每1024局分为一组，第i组使用`SeededRng::new_stream(seed, i)`，
+///   因此未被取消时，结果与线程数无关。
+/// - 被取消时，返回已经完成的组的统计结果。
+#[cfg(any(feature = "py", feature = "rs"))]
+pub fn sample_bbbvs_exp_with_control(
+    x0: usize,
+    y0: usize,
+    n: usize,
+    seed: u64,
+    control: &ThreadControl,
+) -> ([usize; 382], usize) {
     // 从标准高级中采样计算3BV
-    // 16线程计算
-    let n0 = n / 16;
-    let mut threads = vec![];
-    for _i in 0..16 {
-        let join_item = thread::spawn(move || -> [usize; 382] { laymine_study_exp(x0, y0, n0) });
-        threads.push(join_item);
+    let aa = sample_in_chunks(
+        n,
+        seed,
+        control,
+        || [0; 382],
+        |size, rng| laymine_study_exp(x0, y0, size, rng),
+        |aa, r| aa.iter_mut().zip(r).for_each(|(a, b)| *a += b),
+    );
+    (aa, control.get_progress())
+}
+
+/// 局面指标的分布，由sample_board_histograms采样得到。
+/// - 各个直方图的下标为指标的值，元素为该值出现的局数。
+#[cfg(any(feature = "py", feature = "rs"))]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BoardHistograms {
+    /// 采样的局数
+    pub samples: usize,
+    pub bbbv: Vec<usize>,
+    pub op: Vec<usize>,
+    pub isl: Vec<usize>,
+    pub zini: Vec<usize>,
+}
+
+#[cfg(any(feature = "py", feature = "rs"))]
+impl BoardHistograms {
+    /// 3BV为bbbv的局面在分布中的百分位（0~100）。规则见percentile。
+    pub fn bbbv_percentile(&self, bbbv: usize) -> f64 {
+        percentile(&self.bbbv, bbbv)
+    }
+    /// ZiNi为zini的局面在分布中的百分位（0~100）。规则见percentile。
+    pub fn zini_percentile(&self, zini: usize) -> f64 {
+        percentile(&self.zini, zini)
+    }
+    fn record(&mut self, board: Vec<Vec<i32>>) {
+        fn add(histogram: &mut Vec<usize>, value: usize) {
+            if histogram.len() <= value {
+                histogram.resize(value + 1, 0);
+            }
+            histogram[value] += 1;
+        }
+        let mut board = Board::new(board);
+        add(&mut self.bbbv, board.get_bbbv());
+        add(&mut self.op, board.get_op());
+        add(&mut self.isl, board.get_isl());
+        add(&mut self.zini, board.get_zini());
+        self.samples += 1;
+    }
+    fn merge(&mut self, other: &BoardHistograms) {
+        fn add(histogram: &mut Vec<usize>, other: &[usize]) {
+            if histogram.len() < other.len() {
+                histogram.resize(other.len(), 0);
+            }
+            histogram.iter_mut().zip(other).for_each(|(a, b)| *a += b);
+        }
+        add(&mut self.bbbv, &other.bbbv);
+        add(&mut self.op, &other.op);
+        add(&mut self.isl, &other.isl);
+        add(&mut self.zini, &other.zini);
+        self.samples += other.samples;
     }
-    let mut aa = [0; 382];
-    for i in threads.into_iter().map(|c| c.join().unwrap()) {
-        for ii in 0..382 {
-            aa[ii] += i[ii];
+}
+
+/// 任意尺寸的局面指标采样引擎，用于研究3BV、op、isl、ZiNi的分布。
+/// - 输入：高、宽、雷数、起手行数、起手列数、是否起手开空（true时按laymine_op的规则埋雷，
+///   否则按laymine的规则埋雷）、局数、种子、控制器。
+/// - 返回：各指标的分布。
+/// - 采样按每1024局分为一组，第i组使用`SeededRng::new_stream(seed, i)`，
+///   因此未被取消时，结果与线程数无关。被取消时，返回已经完成的组的统计结果。
+/// # Example
+/// ```
+/// use ms_toollib::{sample_board_histograms, ThreadControl};
+/// let h = sample_board_histograms(8, 8, 10, 0, 0, true, 100, 0, &ThreadControl::new(2));
+/// assert_eq!(h.samples, 100);
+/// assert_eq!(h.bbbv.iter().sum::<usize>(), 100);
+/// ```
+#[cfg(any(feature = "py", feature = "rs"))]
+pub fn sample_board_histograms(
+    row: usize,
+    column: usize,
+    minenum: usize,
+    x0: usize,
+    y0: usize,
+    op_start: bool,
+    n: usize,
+    seed: u64,
+    control: &ThreadControl,
+) -> BoardHistograms {
+    sample_in_chunks(
+        n,
+        seed,
+        control,
+        BoardHistograms::default,
+        |size, rng| {
+            let mut h = BoardHistograms::default();
+            for _ in 0..size {
+                h.record(if op_start {
+                    laymine_op_rng(row, column, minenum, x0, y0, rng)
+                } else {
+                    laymine_rng(row, column, minenum, x0, y0, rng)
+                });
+            }
+            h
+        },
+        |histograms, h| histograms.merge(&h),
+    )
+}
+
+/// 多线程分组采样的公共部分。n局按每1024局分为一组，第i组使用`SeededRng::new_stream(seed, i)`，
+/// 由sample(局数, 随机数生成器)得到该组的结果，再用merge合并，因此未被取消时，结果与线程数无关。
+/// 被取消时，返回已经完成的组的合并结果。进度按局数计。
+#[cfg(any(feature = "py", feature = "rs"))]
+fn sample_in_chunks<R, I, S, M>(
+    n: usize,
+    seed: u64,
+    control: &ThreadControl,
+    init: I,
+    sample: S,
+    merge: M,
+) -> R
+where
+    R: Send,
+    I: Fn() -> R + Sync,
+    S: Fn(usize, &mut SeededRng) -> R + Sync,
+    M: Fn(&mut R, R) + Sync,
+{
+    const CHUNK: usize = 1024;
+    let chunk_num = n.div_ceil(CHUNK);
+    let next_chunk = AtomicUsize::new(0);
+    control.progress.store(0, Ordering::Relaxed);
+    let mut result = init();
+    thread::scope(|scope| {
+        let mut threads = vec![];
+        for _ in 0..control.thread_num {
+            let (next_chunk, init, sample, merge) = (&next_chunk, &init, &sample, &merge);
+            threads.push(scope.spawn(move || {
+                let mut r = init();
+                loop {
+                    let c = next_chunk.fetch_add(1, Ordering::Relaxed);
+                    if c >= chunk_num || control.is_cancelled() {
+                        break;
+                    }
+                    let size = min(CHUNK, n - c * CHUNK);
+                    merge(&mut r, sample(size, &mut SeededRng::new_stream(seed, c as u64)));
+                    control.progress.fetch_add(size, Ordering::Relaxed);
+                }
+                r
+            }));
         }
+        for r in threads.into_iter().map(|c| c.join().unwrap()) {
+            merge(&mut result, r);
+        }
+    });
+    result
+}
+
+/// 直方图中，值为value的样本的百分位（0~100）：小于value的样本的比例，加上等于value的样本的比例的一半。
+/// 直方图为空时返回50。
+#[cfg(any(feature = "py", feature = "rs"))]
+fn percentile(histogram: &[usize], value: usize) -> f64 {
+    let total: usize = histogram.iter().sum();
+    if total == 0 {
+        return 50.0;
     }
-    aa
+    let below: usize = histogram.iter().take(value).sum();
+    let equal = histogram.get(value).copied().unwrap_or(0);
+    (below as f64 + equal as f64 / 2.0) / total as f64 * 100.0
 }
 
 #[cfg(any(feature = "py", feature = "rs"))]
-fn laymine_study_exp(x0: usize, y0: usize, n: usize) -> [usize; 382] {
-    let mut rng = thread_rng();
+type HistogramsKey = (usize, usize, usize, usize, usize, bool);
+
+// 只保存用set_board_histograms设置过的分布表，不会自动增长
+#[cfg(any(feature = "py", feature = "rs"))]
+static HISTOGRAMS_CACHE: OnceLock<Mutex<HashMap<HistogramsKey, Arc<BoardHistograms>>>> =
+    OnceLock::new();
+
+/// 设置某种局面的指标分布表，例如sample_board_histograms的结果，或者从文件中读取的预先计算好的表。
+/// 计算这种局面的百分位之前，必须先设置分布表。再次设置同一种局面时，替换原来的表。
+/// - 输入：高、宽、雷数、起手位置、是否起手开空、分布表。
+/// # Example
+/// ```
+/// use ms_toollib::{cal_board_percentile, sample_board_histograms, set_board_histograms, ThreadControl};
+/// let board = vec![vec![0, 0, 0], vec![0, 1, 1], vec![0, 1, -1]];
+/// assert!(cal_board_percentile(&board, 0, 0, true).is_err());
+/// let h = sample_board_histograms(3, 3, 1, 0, 0, true, 100, 0, &ThreadControl::new(2));
+/// set_board_histograms(3, 3, 1, 0, 0, true, h);
+/// assert!(cal_board_percentile(&board, 0, 0, true).is_ok());
+/// ```
+#[cfg(any(feature = "py", feature = "rs"))]
+pub fn set_board_histograms(
+    row: usize,
+    column: usize,
+    minenum: usize,
+    x0: usize,
+    y0: usize,
+    op_start: bool,
+    histograms: BoardHistograms,
+) {
+    HISTOGRAMS_CACHE
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap()
+        .insert(
+            (row, column, minenum, x0, y0, op_start),
+            Arc::new(histograms),
+        );
+}
+
+/// 获取用set_board_histograms设置过的某种局面的指标分布表。不会采样，没有设置过时返回None。
+/// - 输入：高、宽、雷数、起手位置、是否起手开空。
+#[cfg(any(feature = "py", feature = "rs"))]
+pub fn get_board_histograms(
+    row: usize,
+    column: usize,
+    minenum: usize,
+    x0: usize,
+    y0: usize,
+    op_start: bool,
+) -> Option<Arc<BoardHistograms>> {
+    HISTOGRAMS_CACHE
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap()
+        .get(&(row, column, minenum, x0, y0, op_start))
+        .cloned()
+}
+
+/// 计算局面的3BV与ZiNi在同尺寸、同雷数、同起手位置、同起手规则的局面中的百分位（0~100），越大越难。
+/// - 输入：局面、起手位置、是否起手开空（true时与laymine_op的局面比较，否则与laymine的局面比较）。
+/// - 返回：(3BV的百分位, ZiNi的百分位)。
+/// - 错误码：1=没有设置这种局面的分布表。
+/// - 注意：分布表须先用set_board_histograms设置，见get_board_histograms。
+#[cfg(any(feature = "py", feature = "rs"))]
+pub fn cal_board_percentile(
+    board: &Vec<Vec<i32>>,
+    x0: usize,
+    y0: usize,
+    op_start: bool,
+) -> Result<(f64, f64), usize> {
+    Board::new(board.clone()).get_percentiles(x0, y0, op_start)
+}
+
+#[cfg(any(feature = "py", feature = "rs"))]
+fn laymine_study_exp(x0: usize, y0: usize, n: usize, rng: &mut SeededRng) -> [usize; 382] {
     // let area: usize = 16 * 30 - 1;
     let pointer = x0 + y0 * 16;
     let mut bv_record = [0; 382];
@@ -1345,7 +2737,7 @@ fn laymine_study_exp(x0: usize, y0: usize, n: usize) -> [usize; 382] {
             board1_dim[i] = -1;
         }
 
-        board1_dim.shuffle(&mut rng);
+        rng.shuffle(&mut board1_dim);
         let mut board1_dim_2 = [0; 480];
         // Board1Dim_2.reserve(area + 1);
 
@@ -1489,6 +2881,15 @@ pub fn obr_board(
 pub fn mark_board(
     game_board: &mut Vec<Vec<i32>>,
     remark: bool,
+) -> Result<(Vec<(usize, usize)>, Vec<(usize, usize)>), usize> {
+    mark_board_with_topology(game_board, remark, Topology::Plane)
+}
+
+/// 在指定的拓扑结构上标出局面中能判出的雷和非雷，规则同mark_board。
+pub fn mark_board_with_topology(
+    game_board: &mut Vec<Vec<i32>>,
+    remark: bool,
+    topology: Topology,
 ) -> Result<(Vec<(usize, usize)>, Vec<(usize, usize)>), usize> {
     if remark {
         for row in game_board.iter_mut() {
@@ -1499,7 +2900,7 @@ pub fn mark_board(
             }
         }
     }
-    let (mut a_mats, mut xs, mut bs, _, _) = refresh_matrixs(&game_board);
+    let (mut a_mats, mut xs, mut bs, _, _) = refresh_matrixs_with_topology(game_board, topology);
     let mut not_mine = vec![];
     let mut is_mine = vec![];
     let (mut not, mut is) = solve_direct(&mut a_mats, &mut xs, &mut bs, game_board)?;
@@ -1545,7 +2946,7 @@ pub fn get_all_not_and_is_mine_on_board(
 pub fn is_guess_while_needless(board_of_game: &mut Vec<Vec<i32>>, xy: &(usize, usize)) -> i32 {
     board_of_game.iter_mut().for_each(|x| {
         x.iter_mut().for_each(|xx| {
-            if *xx > 10 {
+            if *xx > 10 && *xx != 19 {
                 *xx = 10
             }
         })
@@ -1611,7 +3012,7 @@ pub fn is_guess_while_needless(board_of_game: &mut Vec<Vec<i32>>, xy: &(usize, u
 pub fn is_able_to_solve(board_of_game: &mut Vec<Vec<i32>>, xy: &(usize, usize)) -> bool {
     board_of_game.iter_mut().for_each(|x| {
         x.iter_mut().for_each(|xx| {
-            if *xx > 10 {
+            if *xx > 10 && *xx != 19 {
                 *xx = 10
             }
         })
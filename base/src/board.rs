@@ -1,7 +1,19 @@
 ﻿// 局面相关的类，录像在video
-use crate::algorithms::{cal_probability_onboard, solve_direct, solve_enumerate, solve_minus};
-use crate::utils::{cal_bbbv_on_island, cal_cell_nums, cal_isl, cal_op, refresh_matrixs};
-use crate::{cal_zini, cal_hzini};
+#[cfg(any(feature = "py", feature = "rs"))]
+use crate::algorithms::get_board_histograms;
+use crate::algorithms::{
+    cal_probability_onboard_with_topology, cal_solution_complexity_with_topology,
+    is_solvable_with_topology, solve_direct, solve_enumerate, solve_minus, SolutionComplexity,
+};
+use crate::topology::Topology;
+use crate::utils::{
+    cal_bbbv_labels_with_topology, cal_bbbv_on_island_with_topology, cal_cell_nums,
+    cal_isl_with_topology, cal_islands_with_topology, cal_op_with_topology,
+    cal_openings_with_topology, refresh_matrixs_with_topology,
+};
+use crate::{cal_hzini_with_topology, cal_zini_with_topology};
+use crate::solver_session::SolverSession;
+use std::sync::{Arc, Mutex};
 
 /// 静态游戏局面的包装类。  
 /// 所有计算过的属性都会保存在这里。缓存计算结果的局面。  
@@ -18,6 +30,11 @@ pub struct GameBoard {
     basic_is_mine: Vec<(usize, usize)>,
     enum_not_mine: Vec<(usize, usize)>,
     enum_is_mine: Vec<(usize, usize)>,
+    /// 局面的拓扑结构，判雷和计算概率时按此决定格子的相邻关系
+    topology: Topology,
+    /// 增量求解会话。设置后，计算概率时复用其中缓存的区块枚举结果。
+    /// 用Arc<Mutex>共享，使GameBoard仍然可以跨线程传递
+    solver_session: Option<Arc<Mutex<SolverSession>>>,
 }
 
 // impl Default for GameBoard {
@@ -50,13 +67,26 @@ impl GameBoard {
             basic_not_mine: vec![],
             enum_is_mine: vec![],
             enum_not_mine: vec![],
+            topology: Topology::Plane,
+            solver_session: None,
         }
     }
+    /// 设置局面的拓扑结构，须在计算概率、判雷之前设置。共用的增量求解会话须使用相同的拓扑结构。
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.topology = topology;
+    }
+    /// 设置增量求解会话。相继的局面共用一个会话时，计算概率只需重新枚举发生变化的区块。
+    pub fn set_solver_session(&mut self, solver_session: Arc<Mutex<SolverSession>>) {
+        self.solver_session = Some(solver_session);
+    }
+    pub fn get_solver_session(&self) -> Option<Arc<Mutex<SolverSession>>> {
+        self.solver_session.clone()
+    }
     pub fn set_game_board(&mut self, board: &Vec<Vec<i32>>) {
         let mut game_board_marked = board.clone();
         for i in 0..game_board_marked.len() {
             for j in 0..game_board_marked[0].len() {
-                if game_board_marked[i][j] > 10 {
+                if game_board_marked[i][j] > 10 && game_board_marked[i][j] != 19 {
                     game_board_marked[i][j] = 10;
                 }
             }
@@ -70,7 +100,8 @@ impl GameBoard {
         if self.is_marked {
             return;
         }
-        let (mut a_s, mut x_s, mut b_s, _, _) = refresh_matrixs(&self.game_board_marked);
+        let (mut a_s, mut x_s, mut b_s, _, _) =
+            refresh_matrixs_with_topology(&self.game_board_marked, self.topology);
         let mut ans = solve_direct(&mut a_s, &mut x_s, &mut b_s, &mut self.game_board_marked)
             .unwrap()
             .0;
@@ -105,9 +136,22 @@ impl GameBoard {
         if !self.has_poss {
             self.mark();
             // println!("{:?}, {:?}", self.game_board_marked, self.mine_num);
-            self.poss = cal_probability_onboard(&self.game_board_marked, self.mine_num as f64)
-                .unwrap()
-                .0;
+            self.poss = match &self.solver_session {
+                Some(solver_session) => {
+                    let mut solver_session = solver_session.lock().unwrap();
+                    solver_session.set_game_board(&self.game_board_marked);
+                    solver_session.get_poss().unwrap().clone()
+                }
+                None => {
+                    cal_probability_onboard_with_topology(
+                        &self.game_board_marked,
+                        self.mine_num as f64,
+                        self.topology,
+                    )
+                    .unwrap()
+                    .0
+                }
+            };
             self.has_poss = true;
         }
         &self.poss
@@ -160,11 +204,15 @@ impl GameBoard {
 #[derive(Clone)]
 pub struct Board {
     pub board: Vec<Vec<i32>>,
+    // 局面的拓扑结构，决定3BV、Op、Isl等指标中格子的相邻关系
+    topology: Topology,
     bbbv: Option<usize>,
     zini: Option<usize>,
     hzini: Option<usize>,
     openings: Option<usize>,
     islands: Option<usize>,
+    // 解题复杂度与对应的起手位置
+    solution_complexity: Option<((usize, usize), SolutionComplexity)>,
     cell0: usize,
     cell1: usize,
     cell2: usize,
@@ -179,13 +227,20 @@ pub struct Board {
 
 impl Board {
     pub fn new(board: Vec<Vec<i32>>) -> Board {
+        Board::new_with_topology(board, Topology::Plane)
+    }
+    /// 指定拓扑结构的局面。局面上的数字须已按此拓扑结构计算，见cal_board_numbers_with_topology。
+    /// 百分位只适用于平面局面。
+    pub fn new_with_topology(board: Vec<Vec<i32>>, topology: Topology) -> Board {
         Board {
             board,
+            topology,
             bbbv: None,
             zini: None,
             hzini: None,
             openings: None,
             islands: None,
+            solution_complexity: None,
             cell0: 0,
             cell1: 0,
             cell2: 0,
@@ -202,28 +257,65 @@ impl Board {
         if let Some(value) = self.bbbv {
             return value;
         }
-        self.bbbv = Some(cal_bbbv_on_island(&self.board) + self.get_op());
+        let bbbv_on_island = cal_bbbv_on_island_with_topology(&self.board, self.topology);
+        self.bbbv = Some(bbbv_on_island + self.get_op());
         return self.bbbv.unwrap();
     }
     pub fn get_zini(&mut self) -> usize {
         if let Some(value) = self.zini {
             return value;
         }
-        self.zini = Some(cal_zini(&self.board));
+        self.zini = Some(cal_zini_with_topology(&self.board, self.topology));
         return self.zini.unwrap();
     }
     pub fn get_hzini(&mut self) -> usize {
         if let Some(value) = self.hzini {
             return value;
         }
-        self.hzini = Some(cal_hzini(&self.board));
+        self.hzini = Some(cal_hzini_with_topology(&self.board, self.topology));
         return self.hzini.unwrap();
     }
     pub fn get_op(&mut self) -> usize {
-        *self.openings.get_or_insert_with(|| cal_op(&self.board))
+        let topology = self.topology;
+        *self
+            .openings
+            .get_or_insert_with(|| cal_op_with_topology(&self.board, topology))
     }
     pub fn get_isl(&mut self) -> usize {
-        *self.islands.get_or_insert_with(|| cal_isl(&self.board))
+        let topology = self.topology;
+        *self
+            .islands
+            .get_or_insert_with(|| cal_isl_with_topology(&self.board, topology))
+    }
+    pub fn get_topology(&self) -> Topology {
+        self.topology
+    }
+    /// 从(x0, y0)起手的解题复杂度：各判雷方法判出的格子数、必须猜的次数。见cal_solution_complexity。
+    pub fn get_solution_complexity(&mut self, x0: usize, y0: usize) -> SolutionComplexity {
+        if let Some((pos, value)) = self.solution_complexity {
+            if pos == (x0, y0) {
+                return value;
+            }
+        }
+        let value = cal_solution_complexity_with_topology(&self.board, x0, y0, self.topology);
+        self.solution_complexity = Some(((x0, y0), value));
+        value
+    }
+    /// 每一片空的格子，包括其中的0和边缘上的数字。见cal_openings。
+    pub fn openings(&mut self) -> Vec<Vec<(usize, usize)>> {
+        let openings = cal_openings_with_topology(&self.board, self.topology);
+        self.openings = Some(openings.len());
+        openings
+    }
+    /// 每一个岛的格子，岛的3BV即格子数。见cal_islands。
+    pub fn islands(&mut self) -> Vec<Vec<(usize, usize)>> {
+        let islands = cal_islands_with_topology(&self.board, self.topology);
+        self.islands = Some(islands.len());
+        islands
+    }
+    /// 每个格子在3BV中的分类。见cal_bbbv_labels。
+    pub fn bbbv_labels(&self) -> Vec<Vec<i32>> {
+        cal_bbbv_labels_with_topology(&self.board, self.topology)
     }
     fn cal_cell_nums(&mut self) {
         let ans = cal_cell_nums(&self.board);
@@ -301,4 +393,101 @@ impl Board {
         self.cal_cell_nums();
         return self.cell8;
     }
+    /// 3BV与ZiNi在同尺寸、同雷数、同起手位置、同起手规则的局面中的百分位（0~100）。
+    /// 见cal_board_percentile。分布表须先用set_board_histograms设置，不会自动采样；
+    /// 除了查表，只需计算本局面的3BV与ZiNi（已经计算过时直接复用）。
+    /// - 错误码：1=没有设置这种局面的分布表，2=不是平面局面。
+    #[cfg(any(feature = "py", feature = "rs"))]
+    pub fn get_percentiles(
+        &mut self,
+        x0: usize,
+        y0: usize,
+        op_start: bool,
+    ) -> Result<(f64, f64), usize> {
+        if self.topology != Topology::Plane {
+            return Err(2);
+        }
+        let minenum = self.board.iter().flatten().filter(|&&x| x == -1).count();
+        let (row, column) = (self.board.len(), self.board[0].len());
+        let histograms =
+            get_board_histograms(row, column, minenum, x0, y0, op_start).ok_or(1usize)?;
+        Ok((
+            histograms.bbbv_percentile(self.get_bbbv()),
+            histograms.zini_percentile(self.get_zini()),
+        ))
+    }
+    /// 综合难度（0~100），为3BV与ZiNi的百分位的平均值，越大越难。代价与错误码同get_percentiles。
+    #[cfg(any(feature = "py", feature = "rs"))]
+    pub fn get_difficulty(&mut self, x0: usize, y0: usize, op_start: bool) -> Result<f64, usize> {
+        let (bbbv_percentile, zini_percentile) = self.get_percentiles(x0, y0, op_start)?;
+        Ok((bbbv_percentile + zini_percentile) / 2.0)
+    }
+}
+
+/// 筛选局面的条件，用于laymine_filtered。
+/// - 每一项都是闭区间(最小值, 最大值)，None代表不限制。
+/// - cells的第i项为数字i的个数的范围。
+/// - solvable为Some(true)时要求从起手位置无猜，Some(false)时要求有猜。
+/// # Example
+/// ```
+/// use ms_toollib::{Board, BoardFilter};
+/// let filter = BoardFilter {
+///     bbbv: Some((2, 4)),
+///     ..BoardFilter::default()
+/// };
+/// let mut board = Board::new(vec![vec![0, 1, -1], vec![0, 2, 2], vec![0, 1, -1]]);
+/// assert!(filter.is_satisfied(&mut board, 0, 0));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BoardFilter {
+    pub bbbv: Option<(usize, usize)>,
+    pub op: Option<(usize, usize)>,
+    pub isl: Option<(usize, usize)>,
+    pub zini: Option<(usize, usize)>,
+    pub hzini: Option<(usize, usize)>,
+    pub cells: [Option<(usize, usize)>; 9],
+    pub solvable: Option<bool>,
+}
+
+impl BoardFilter {
+    /// 局面是否满足全部条件。计算量小的指标先检查，不满足时直接返回。
+    /// - 输入：局面、起手的行、列。起手位置只用于判断是否无猜。
+    pub fn is_satisfied(&self, board: &mut Board, x0: usize, y0: usize) -> bool {
+        let in_range = |range: Option<(usize, usize)>, value: usize| match range {
+            Some((low, high)) => low <= value && value <= high,
+            None => true,
+        };
+        if self.cells.iter().any(|c| c.is_some()) {
+            let cell_nums = cal_cell_nums(&board.board);
+            if self
+                .cells
+                .iter()
+                .zip(cell_nums.iter())
+                .any(|(&range, &num)| !in_range(range, num))
+            {
+                return false;
+            }
+        }
+        if self.op.is_some() && !in_range(self.op, board.get_op()) {
+            return false;
+        }
+        if self.isl.is_some() && !in_range(self.isl, board.get_isl()) {
+            return false;
+        }
+        if self.bbbv.is_some() && !in_range(self.bbbv, board.get_bbbv()) {
+            return false;
+        }
+        if self.zini.is_some() && !in_range(self.zini, board.get_zini()) {
+            return false;
+        }
+        if self.hzini.is_some() && !in_range(self.hzini, board.get_hzini()) {
+            return false;
+        }
+        match self.solvable {
+            Some(solvable) => {
+                is_solvable_with_topology(&board.board, x0, y0, board.topology) == solvable
+            }
+            None => true,
+        }
+    }
 }
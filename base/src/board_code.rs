@@ -0,0 +1,256 @@
+// 局面的短字符串编码，用于分享局面
+use crate::topology::Topology;
+use crate::utils::cal_board_numbers_with_topology;
+
+/// 当前的编码版本号
+const BOARD_CODE_VERSION: u8 = 1;
+/// 标志位：带有起手位置
+const FLAG_FIRST_CLICK: u8 = 0b01;
+/// 标志位：带有遮挡
+const FLAG_MASK: u8 = 0b10;
+/// 标志位：环面局面，数字按环面计算
+const FLAG_TORUS: u8 = 0b100;
+/// base64url字母表，编码结果可以直接放在网址里
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// 把真实局面编码成短字符串，便于分享。
+/// - 格式（版本1）：版本号（1字节）、标志位（1字节）、行数、列数（各2字节）、
+///   可选的起手位置（行、列各2字节）、雷的位图、可选的遮挡位图、CRC-16校验（2字节），
+///   按base64url编码，不带填充。位图按行优先排列，每字节高位在前。
+/// - 输入：真实局面（-1代表雷，-2代表被遮挡的位置，其余的数字被忽略）、可选的起手位置（行，列）。
+/// - 错误码：1=局面的行数或列数为0或超过65535，2=起手位置超出局面。
+/// # Example
+/// ```
+/// use ms_toollib::{decode_board, encode_board, laymine_op_seeded};
+/// let board = laymine_op_seeded(16, 30, 99, 3, 4, 42);
+/// let code = encode_board(&board, Some((3, 4))).unwrap();
+/// assert_eq!(code.len(), 96);
+/// assert_eq!(decode_board(&code), Ok((board, Some((3, 4)))));
+/// ```
+pub fn encode_board(
+    board: &Vec<Vec<i32>>,
+    first_click: Option<(usize, usize)>,
+) -> Result<String, usize> {
+    encode_board_with_topology(board, first_click, Topology::Plane)
+}
+
+/// 带拓扑结构的encode_board。环面局面在标志位中记录，解码时按环面计算数字。
+/// - 输入：真实局面、可选的起手位置（行，列）、拓扑结构。
+/// - 错误码：同encode_board。
+/// # Example
+/// ```
+/// use ms_toollib::{decode_board, decode_board_with_topology, encode_board_with_topology};
+/// use ms_toollib::{laymine_op_seeded, Topology};
+/// let board = laymine_op_seeded(8, 8, 10, 0, 0, 42);
+/// let code = encode_board_with_topology(&board, None, Topology::Torus).unwrap();
+/// let (_, _, topology) = decode_board_with_topology(&code).unwrap();
+/// assert_eq!(topology, Topology::Torus);
+/// assert_eq!(decode_board(&code), Err(5));
+/// ```
+pub fn encode_board_with_topology(
+    board: &Vec<Vec<i32>>,
+    first_click: Option<(usize, usize)>,
+    topology: Topology,
+) -> Result<String, usize> {
+    let row = board.len();
+    let column = board.first().map_or(0, |r| r.len());
+    if row == 0 || column == 0 || row > u16::MAX as usize || column > u16::MAX as usize {
+        return Err(1);
+    }
+    let has_mask = board.iter().flatten().any(|&c| c == -2);
+    let mut flags = 0;
+    if first_click.is_some() {
+        flags |= FLAG_FIRST_CLICK;
+    }
+    if has_mask {
+        flags |= FLAG_MASK;
+    }
+    if topology == Topology::Torus {
+        flags |= FLAG_TORUS;
+    }
+    let mut bytes = vec![BOARD_CODE_VERSION, flags];
+    bytes.extend_from_slice(&(row as u16).to_be_bytes());
+    bytes.extend_from_slice(&(column as u16).to_be_bytes());
+    if let Some((x0, y0)) = first_click {
+        if x0 >= row || y0 >= column {
+            return Err(2);
+        }
+        bytes.extend_from_slice(&(x0 as u16).to_be_bytes());
+        bytes.extend_from_slice(&(y0 as u16).to_be_bytes());
+    }
+    push_bitmap(&mut bytes, board, -1);
+    if has_mask {
+        push_bitmap(&mut bytes, board, -2);
+    }
+    let crc = crc16(&bytes);
+    bytes.extend_from_slice(&crc.to_be_bytes());
+    Ok(base64_encode(&bytes))
+}
+
+/// 把encode_board生成的字符串解码成真实局面，并重新计算数字。
+/// - 返回：真实局面、可选的起手位置（行，列）。
+/// - 错误码：1=含有非法字符或长度不对，2=校验不通过，3=不支持的版本号，4=内容不合法（尺寸为0、长度与尺寸不符、起手位置超出局面等），
+///   5=环面局面，需要用decode_board_with_topology解码。
+pub fn decode_board(code: &str) -> Result<(Vec<Vec<i32>>, Option<(usize, usize)>), usize> {
+    match decode_board_with_topology(code)? {
+        (board, first_click, Topology::Plane) => Ok((board, first_click)),
+        (_, _, Topology::Torus) => Err(5),
+    }
+}
+
+/// 带拓扑结构的decode_board，按编码中记录的拓扑结构计算数字。
+/// - 返回：真实局面、可选的起手位置（行，列）、拓扑结构。
+/// - 错误码：1~4同decode_board。
+pub fn decode_board_with_topology(
+    code: &str,
+) -> Result<(Vec<Vec<i32>>, Option<(usize, usize)>, Topology), usize> {
+    let bytes = base64_decode(code.trim()).ok_or(1usize)?;
+    if bytes.len() < 3 {
+        return Err(1);
+    }
+    let (data, crc) = bytes.split_at(bytes.len() - 2);
+    if crc16(data).to_be_bytes() != crc {
+        return Err(2);
+    }
+    if data[0] != BOARD_CODE_VERSION {
+        return Err(3);
+    }
+    if data.len() < 6 || data[1] & !(FLAG_FIRST_CLICK | FLAG_MASK | FLAG_TORUS) != 0 {
+        return Err(4);
+    }
+    let flags = data[1];
+    let row = u16::from_be_bytes([data[2], data[3]]) as usize;
+    let column = u16::from_be_bytes([data[4], data[5]]) as usize;
+    if row == 0 || column == 0 {
+        return Err(4);
+    }
+    let mut pointer = 6;
+    let first_click = if flags & FLAG_FIRST_CLICK != 0 {
+        if data.len() < pointer + 4 {
+            return Err(4);
+        }
+        let x0 = u16::from_be_bytes([data[pointer], data[pointer + 1]]) as usize;
+        let y0 = u16::from_be_bytes([data[pointer + 2], data[pointer + 3]]) as usize;
+        pointer += 4;
+        if x0 >= row || y0 >= column {
+            return Err(4);
+        }
+        Some((x0, y0))
+    } else {
+        None
+    };
+    let bitmap_len = (row * column).div_ceil(8);
+    let bitmap_num = if flags & FLAG_MASK != 0 { 2 } else { 1 };
+    if data.len() != pointer + bitmap_len * bitmap_num {
+        return Err(4);
+    }
+    let mut board = vec![vec![0; column]; row];
+    read_bitmap(&data[pointer..pointer + bitmap_len], &mut board, -1, None)?;
+    if bitmap_num == 2 {
+        pointer += bitmap_len;
+        read_bitmap(
+            &data[pointer..pointer + bitmap_len],
+            &mut board,
+            -2,
+            Some(-1),
+        )?;
+    }
+    let topology = if flags & FLAG_TORUS != 0 {
+        Topology::Torus
+    } else {
+        Topology::Plane
+    };
+    cal_board_numbers_with_topology(&mut board, topology);
+    Ok((board, first_click, topology))
+}
+
+/// 把局面中等于value的位置按行优先写成位图
+fn push_bitmap(bytes: &mut Vec<u8>, board: &[Vec<i32>], value: i32) {
+    let mut byte = 0u8;
+    let mut n = 0;
+    for &c in board.iter().flatten() {
+        byte = (byte << 1) | (c == value) as u8;
+        n += 1;
+        if n == 8 {
+            bytes.push(byte);
+            byte = 0;
+            n = 0;
+        }
+    }
+    if n > 0 {
+        bytes.push(byte << (8 - n));
+    }
+}
+
+/// 把位图中为1的位置写成value。位置上已经是conflict时，内容不合法；末尾补齐的位必须为0
+fn read_bitmap(
+    bitmap: &[u8],
+    board: &mut [Vec<i32>],
+    value: i32,
+    conflict: Option<i32>,
+) -> Result<(), usize> {
+    let column = board[0].len();
+    let area = board.len() * column;
+    for id in 0..bitmap.len() * 8 {
+        if bitmap[id / 8] >> (7 - id % 8) & 1 == 0 {
+            continue;
+        }
+        if id >= area {
+            return Err(4);
+        }
+        let cell = &mut board[id / column][id % column];
+        if Some(*cell) == conflict {
+            return Err(4);
+        }
+        *cell = value;
+    }
+    Ok(())
+}
+
+/// CRC-16/CCITT-FALSE
+fn crc16(bytes: &[u8]) -> u16 {
+    let mut crc = 0xFFFFu16;
+    for &b in bytes {
+        crc ^= (b as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut s = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..=chunk.len() {
+            s.push(ALPHABET[(n >> (18 - 6 * i) & 0x3F) as usize] as char);
+        }
+    }
+    s
+}
+
+fn base64_decode(s: &str) -> Option<Vec<u8>> {
+    if s.len() % 4 == 1 {
+        return None;
+    }
+    let mut bytes = Vec::with_capacity(s.len() * 3 / 4);
+    for chunk in s.as_bytes().chunks(4) {
+        let mut n = 0u32;
+        for (i, &c) in chunk.iter().enumerate() {
+            let v = ALPHABET.iter().position(|&a| a == c)? as u32;
+            n |= v << (18 - 6 * i);
+        }
+        for i in 0..chunk.len() - 1 {
+            bytes.push((n >> (16 - 8 * i)) as u8);
+        }
+    }
+    Some(bytes)
+}
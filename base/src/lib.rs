@@ -19,6 +19,7 @@
 //!      - `15`表示踩到了雷游戏失败了对应红雷（踩中的地雷）
 //!      - `16`表示背景不透明的白雷，失败后显示出来的其他的雷
 //!      - `18`表示局面中，由于双击的高亮，导致看起来像0的格子
+//!      - `19`表示被遮挡的位置，即非矩形的局面中不存在的格子。它既不是雷，也不能点击，计算周围的数字时跳过
 //!    - 第一个索引是行，第二个索引是列。例如：高级中，`game_board[0][0]`代表最左上角位置，`game_board[15][29]`代表最右下角位置。
 //!    - **注意**：游戏局面中`11`的作用类似于游戏时的标雷，但是区别在于，玩家标出的雷可能是错误的，而算法的判断一定是正确的。这两种情况都用同一个数字表示。通俗地讲，因为算法需要保证百分百的正确性，所以玩家标出来的雷，算法一个也不相信，这意味着这两种含义不可能同时出现。
 //! 1. “真实局面”或“局面”
@@ -27,6 +28,7 @@
 //!      - `0`代表空
 //!      - `1`到`8`代表数字1到8
 //!      - `-1`代表雷
+//!      - `-2`代表被遮挡的位置，对应游戏局面中的`19`
 //! 1. 游戏局面和局面的区别
 //!    - 游戏局面是游戏时玩家看见的局面，随鼠标的点击操作而变化。
 //!    - 真实局面是可以看见雷的实际局面，不会随操作而变化。
@@ -66,55 +68,101 @@ mod utils;
 #[cfg(any(feature = "js"))]
 pub use utils::get_random_int;
 pub use utils::{
-    cal_all_solution, cal_bbbv, cal_board_numbers, cal_cell_nums, cal_isl, cal_op,
-    cal_table_minenum_recursion, combine, is_good_chording, laymine, laymine_op, refresh_board,
-    refresh_matrix, refresh_matrixs, refresh_matrixses, unsolvable_structure,
+    cal_all_solution, cal_area, cal_bbbv, cal_bbbv_labels, cal_bbbv_labels_with_topology,
+    cal_bbbv_with_topology, cal_board_numbers, cal_board_numbers_with_topology, cal_cell_nums,
+    cal_isl, cal_isl_with_topology, cal_islands, cal_islands_with_topology, cal_op,
+    cal_op_with_topology, cal_openings, cal_openings_with_topology, cal_table_minenum_recursion,
+    combine, get_covered_game_board, is_good_chording, laymine, laymine_masked,
+    laymine_masked_seeded, laymine_op, laymine_op_seeded, laymine_seeded, laymine_with_topology,
+    laymine_with_topology_seeded, refresh_board, refresh_board_with_topology, refresh_matrix,
+    refresh_matrixs, refresh_matrixs_with_topology, refresh_matrixses, unsolvable_structure,
 };
 
+mod topology;
+pub use topology::Topology;
+
+mod seeded_rng;
+pub use seeded_rng::SeededRng;
+
 mod miscellaneous;
 
 mod algorithms;
 #[cfg(any(feature = "py", feature = "rs"))]
-pub use algorithms::{laymine_solvable_thread, sample_bbbvs_exp, obr_board};
+pub use algorithms::{
+    cal_board_percentile, get_board_histograms, laymine_filtered, laymine_filtered_seeded,
+    laymine_filtered_with_control, laymine_solvable_thread, laymine_solvable_thread_seeded,
+    laymine_solvable_thread_with_control, obr_board, sample_bbbvs_exp, sample_bbbvs_exp_seeded,
+    sample_bbbvs_exp_with_control, sample_board_histograms, set_board_histograms, BoardHistograms,
+    ThreadControl,
+};
 
 #[cfg(any(feature = "py", feature = "rs"))]
 #[deprecated]
-pub use {
-    algorithms::sample_bbbvs_exp as sample_3BVs_exp,
-    algorithms::obr_board as OBR_board,
-};
+pub use {algorithms::obr_board as OBR_board, algorithms::sample_bbbvs_exp as sample_3BVs_exp};
 
 pub use algorithms::{
-    cal_probability_cells_is_op, cal_probability_enum, cal_probability_csp,
-    cal_probability_onboard, get_all_not_and_is_mine_on_board, is_able_to_solve,
-    is_guess_while_needless, is_solvable, laymine_solvable, laymine_solvable_adjust, mark_board,
-    solve_direct, solve_enumerate, solve_minus, try_solve, cal_probability_cells_not_mine
+    cal_guess_stats, cal_guess_stats_seeded, cal_mine_count_distribution,
+    cal_probability_cells_is_op, cal_probability_cells_not_mine,
+    cal_probability_cells_not_mine_with_topology, cal_probability_csp,
+    cal_probability_csp_with_topology, cal_probability_enum, cal_probability_onboard,
+    cal_probability_onboard_with_topology, cal_solution_complexity,
+    cal_solution_complexity_with_topology, cal_solutions_count_exact, check_for_unavoidable_5050,
+    check_for_unavoidable_5050_or_pseudo, check_for_unavoidable_guess,
+    get_all_not_and_is_mine_on_board, hint, is_able_to_solve, is_guess_while_needless, is_solvable,
+    is_solvable_with_topology, laymine_solvable, laymine_solvable_adjust,
+    laymine_solvable_adjust_seeded, laymine_solvable_seeded, mark_board, mark_board_with_topology,
+    solve_direct, solve_enumerate, solve_minus, suggest_moves, try_solve, try_solve_trace,
+    GuessStats, Hint, HintAction, SolutionComplexity, SolveRule, SolveStep,
 };
 // #[cfg(any(feature = "rs"))]
 // pub use algorithms::{mark_board, solve_direct, solve_enumerate, solve_minus};
 
 mod board;
-pub use board::{Board, GameBoard};
+pub use board::{Board, BoardFilter, GameBoard};
+
+mod board_code;
+pub use board_code::{
+    decode_board, decode_board_with_topology, encode_board, encode_board_with_topology,
+};
+
+mod symmetry;
+pub use symmetry::{board_symmetries, canonical_board, canonical_board_hash};
 
 mod evfs;
 pub use evfs::{Evfs, EvfsCell};
 
 pub mod videos;
 pub use videos::{
-    valid_time_period, AvfVideo, BaseVideo, EvfVideo, GameBoardState, MinesweeperBoard, MouseState,
-    MvfVideo, RmvVideo,BoardEvent, ErrReadVideoReason, Event, GameDynamicParams, GameStateEvent, IndexEvent,
-    IndexValue, KeyDynamicParams, MouseEvent, VideoActionStateRecorder, VideoAnalyseParams,
-    VideoDynamicParams
+    valid_time_period, AvfVideo, BaseVideo, BoardEvent, ErrReadVideoReason, Event, EvfVideo,
+    FirstClickPolicy, GameBoardState, GameDynamicParams, GameStateEvent, IndexEvent, IndexValue,
+    KeyDynamicParams, MinesweeperBoard, MouseEvent, MouseState, MvfVideo, RmvVideo,
+    VideoActionStateRecorder, VideoAnalyseParams, VideoDynamicParams,
 };
 
+mod patterns;
+pub use patterns::{find_patterns, PatternMatch};
+
+mod optimal_clicks;
+pub use optimal_clicks::{cal_optimal_clicks, OptimalClicksLimits};
+
 mod zini;
-pub use zini::{cal_zini, cal_hzini, cal_rzini};
+pub use zini::{
+    cal_hzini, cal_hzini_ops, cal_hzini_ops_with_topology, cal_hzini_with_topology, cal_nf_zini,
+    cal_nf_zini_ops, cal_nf_zini_ops_with_topology, cal_nf_zini_with_topology, cal_partial_zini,
+    cal_rzini, cal_rzini_ops, cal_rzini_ops_with_topology, cal_rzini_with_topology, cal_zini,
+    cal_zini_ops, cal_zini_ops_with_topology, cal_zini_with_topology, zini_ops_to_step_flow,
+    ZiniOp,
+};
 
 mod big_number;
 
 mod tile;
 mod binomial;
 mod probability_engine;
+pub use probability_engine::MoveSuggestion;
+
+mod solver_session;
+pub use solver_session::SolverSession;
 
 #[cfg(any(feature = "py", feature = "rs"))]
 mod obr;
@@ -128,6 +176,5 @@ pub use crate::safe_board::SafeBoard;
 #[cfg(any(feature = "py", feature = "rs"))]
 pub use crate::safe_board::SafeBoardRow;
 
-
 // 假如限时1秒，本项目最大枚举长度可以达到约70。除可猜模式几乎没有录像能达到，所以取消最大枚举长度限制。
 const ENUM_LIMIT: usize = 99999;
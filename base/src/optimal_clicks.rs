@@ -0,0 +1,409 @@
+// 精确求解扫完局面所需的最少点击次数（左键 + 右键 + 双击），适用于小局面
+use crate::zini::{cal_zini_ops, ZiniOp};
+use std::collections::HashMap;
+use web_time::Instant;
+
+/// cal_optimal_clicks的搜索限制。任一限制达到时停止搜索，返回目前找到的最好的结果。
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OptimalClicksLimits {
+    /// 最多搜索的状态数
+    pub max_nodes: usize,
+    /// 最长搜索时间，单位为毫秒
+    pub max_millis: u64,
+}
+
+impl Default for OptimalClicksLimits {
+    fn default() -> Self {
+        OptimalClicksLimits {
+            max_nodes: 2_000_000,
+            max_millis: 10_000,
+        }
+    }
+}
+
+/// 搜索中的一步宏操作：可选的左键，标出周围所有未标的雷，再双击
+#[derive(Clone, Copy)]
+struct Action {
+    cell: usize,
+    // 是否先左键点开该格子
+    left: bool,
+    // 是否在之后双击该格子
+    chord: bool,
+}
+
+impl Action {
+    // 用于给可交换的操作规定先后顺序
+    fn key(&self) -> usize {
+        self.cell * 4 + (self.left as usize) * 2 + self.chord as usize
+    }
+}
+
+struct Solver {
+    row: usize,
+    column: usize,
+    words: usize,
+    number: Vec<i32>,
+    neighbors: Vec<Vec<usize>>,
+    // 每个格子所在的空的编号，不在空里（包括空的边缘的数字）为None
+    opening_of: Vec<Option<usize>>,
+    // 每个空所包含的所有格子，包括边缘的数字
+    opening_cells: Vec<Vec<usize>>,
+    // 3BV单元：空的第一个格子，或不与空相邻的数字（岛）。点开该格子即点开该单元
+    units: Vec<usize>,
+    // 双击某个格子可能点开的3BV单元
+    cover_units: Vec<Vec<usize>>,
+    // 双击后可能点开某个3BV单元的格子
+    unit_chord_cells: Vec<Vec<usize>>,
+    visited: HashMap<Vec<u64>, usize>,
+    best: usize,
+    best_path: Vec<Action>,
+    path: Vec<Action>,
+    nodes: usize,
+    limits: OptimalClicksLimits,
+    start: Instant,
+    stopped: bool,
+}
+
+fn get_bit(bits: &[u64], i: usize) -> bool {
+    bits[i / 64] >> (i % 64) & 1 == 1
+}
+
+fn set_bit(bits: &mut [u64], i: usize) {
+    bits[i / 64] |= 1 << (i % 64);
+}
+
+impl Solver {
+    fn new(board: &[Vec<i32>], limits: OptimalClicksLimits) -> Solver {
+        let row = board.len();
+        let column = board[0].len();
+        let n = row * column;
+        let number: Vec<i32> = board.iter().flatten().copied().collect();
+        let mut neighbors = vec![vec![]; n];
+        for x in 0..row {
+            for y in 0..column {
+                for i in x.max(1) - 1..row.min(x + 2) {
+                    for j in y.max(1) - 1..column.min(y + 2) {
+                        if (i, j) != (x, y) {
+                            neighbors[x * column + y].push(i * column + j);
+                        }
+                    }
+                }
+            }
+        }
+        let mut opening_of = vec![None; n];
+        let mut opening_cells = vec![];
+        let mut units = vec![];
+        for id in 0..n {
+            if number[id] != 0 || opening_of[id].is_some() {
+                continue;
+            }
+            let op = opening_cells.len();
+            let mut cells = vec![id];
+            let mut in_cells = vec![false; n];
+            in_cells[id] = true;
+            let mut buffer = vec![id];
+            opening_of[id] = Some(op);
+            while let Some(k) = buffer.pop() {
+                for &m in &neighbors[k] {
+                    if number[m] < 0 || in_cells[m] {
+                        continue;
+                    }
+                    in_cells[m] = true;
+                    cells.push(m);
+                    if number[m] == 0 {
+                        opening_of[m] = Some(op);
+                        buffer.push(m);
+                    }
+                }
+            }
+            opening_cells.push(cells);
+            units.push(id);
+        }
+        for id in 0..n {
+            if number[id] > 0 && neighbors[id].iter().all(|&m| number[m] != 0) {
+                units.push(id);
+            }
+        }
+        let mut cover_units = vec![vec![]; n];
+        let mut unit_chord_cells = vec![vec![]; units.len()];
+        for c in 0..n {
+            if number[c] <= 0 {
+                continue;
+            }
+            for (u, &cell) in units.iter().enumerate() {
+                let hit = match opening_of[cell] {
+                    Some(op) => neighbors[c].iter().any(|&m| opening_of[m] == Some(op)),
+                    None => neighbors[c].contains(&cell),
+                };
+                if hit {
+                    cover_units[c].push(u);
+                    unit_chord_cells[u].push(c);
+                }
+            }
+        }
+        Solver {
+            row,
+            column,
+            words: n.div_ceil(64),
+            number,
+            neighbors,
+            opening_of,
+            opening_cells,
+            units,
+            cover_units,
+            unit_chord_cells,
+            visited: HashMap::new(),
+            best: usize::MAX,
+            best_path: vec![],
+            path: vec![],
+            nodes: 0,
+            limits,
+            start: Instant::now(),
+            stopped: false,
+        }
+    }
+
+    // 点开一个格子，遇到空则点开整个空。状态的前半部分是已打开的格子，后半部分是已标的雷
+    fn reveal(&self, state: &mut [u64], id: usize) {
+        if get_bit(state, id) || self.number[id] < 0 {
+            return;
+        }
+        match self.opening_of[id] {
+            Some(op) => {
+                for &m in &self.opening_cells[op] {
+                    set_bit(state, m);
+                }
+            }
+            None => set_bit(state, id),
+        }
+    }
+
+    // 执行一步宏操作，返回代价
+    fn apply(&self, state: &mut [u64], action: Action) -> usize {
+        let mut cost = 0;
+        if action.left {
+            self.reveal(state, action.cell);
+            cost += 1;
+        }
+        if action.chord {
+            let (opened, flagged) = state.split_at_mut(self.words);
+            for &m in &self.neighbors[action.cell] {
+                if self.number[m] == -1 && !get_bit(flagged, m) {
+                    set_bit(flagged, m);
+                    cost += 1;
+                }
+            }
+            for &m in &self.neighbors[action.cell] {
+                self.reveal(opened, m);
+            }
+            cost += 1;
+        }
+        cost
+    }
+
+    fn unrevealed_units(&self, state: &[u64]) -> Vec<usize> {
+        (0..self.units.len())
+            .filter(|&u| !get_bit(state, self.units[u]))
+            .collect()
+    }
+
+    // 剩余代价的下界。每个单元记为1除以能同时点开它的单元数的最大值，每步操作的代价至少为1，
+    // 且点开的单元的记数之和不超过1
+    fn lower_bound(&self, state: &[u64], unrevealed: &[usize]) -> usize {
+        let mut sum = 0.0;
+        for &u in unrevealed {
+            let mut max_cover = 1;
+            for &c in &self.unit_chord_cells[u] {
+                let cover = self.cover_units[c]
+                    .iter()
+                    .filter(|&&v| !get_bit(state, self.units[v]))
+                    .count();
+                max_cover = max_cover.max(cover);
+            }
+            sum += 1.0 / max_cover as f64;
+        }
+        (sum - 1e-9).ceil().max(0.0) as usize
+    }
+
+    // 所有有意义的操作，及其代价、点开的单元数、操作后的状态
+    fn actions(
+        &self,
+        state: &[u64],
+        unrevealed: &[usize],
+    ) -> Vec<(Action, usize, usize, Vec<u64>)> {
+        let mut actions = vec![];
+        let mut push = |action: Action| {
+            let mut next = state.to_vec();
+            let cost = self.apply(&mut next, action);
+            let gain = unrevealed
+                .iter()
+                .filter(|&&u| get_bit(&next, self.units[u]))
+                .count();
+            let revealed_cells = (0..self.words)
+                .map(|w| (next[w] & !state[w]).count_ones())
+                .sum::<u32>();
+            // 只点开了自身的左键+双击，不如只用左键
+            if revealed_cells > 1 || (revealed_cells == 1 && !(action.left && action.chord)) {
+                actions.push((action, cost, gain, next));
+            }
+        };
+        for &u in unrevealed {
+            push(Action {
+                cell: self.units[u],
+                left: true,
+                chord: false,
+            });
+        }
+        for c in 0..self.row * self.column {
+            if self.number[c] <= 0 {
+                continue;
+            }
+            if self.neighbors[c]
+                .iter()
+                .all(|&m| self.number[m] < 0 || get_bit(state, m))
+            {
+                continue;
+            }
+            push(Action {
+                cell: c,
+                left: !get_bit(state, c),
+                chord: true,
+            });
+        }
+        // 按每单位代价点开的单元数从大到小排序
+        actions.sort_by(|a, b| (b.2 * a.1).cmp(&(a.2 * b.1)).then(b.2.cmp(&a.2)));
+        actions
+    }
+
+    fn is_limited(&mut self) -> bool {
+        if self.stopped {
+            return true;
+        }
+        self.nodes += 1;
+        if self.nodes > self.limits.max_nodes
+            || (self.nodes.is_multiple_of(1024)
+                && self.start.elapsed().as_millis() > self.limits.max_millis as u128)
+        {
+            self.stopped = true;
+        }
+        self.stopped
+    }
+
+    // 操作在某个状态下的效果：代价和状态的变化。不能执行时为None
+    fn effect(&self, state: &[u64], action: Action) -> Option<(usize, Vec<u64>)> {
+        if action.left == get_bit(state, action.cell) {
+            return None;
+        }
+        let mut next = state.to_vec();
+        let cost = self.apply(&mut next, action);
+        Some((cost, next.iter().zip(state).map(|(a, b)| a ^ b).collect()))
+    }
+
+    // prev为上一步的操作及其之前的状态。与上一步可交换的操作只按key从小到大的顺序搜索
+    fn search(&mut self, state: &[u64], g: usize, prev: Option<(Action, &[u64])>) {
+        if self.is_limited() {
+            return;
+        }
+        let unrevealed = self.unrevealed_units(state);
+        // 剩下的单元全部用左键点开，得到一个可行解
+        if g + unrevealed.len() < self.best {
+            self.best = g + unrevealed.len();
+            self.best_path = self.path.clone();
+            for &u in &unrevealed {
+                self.best_path.push(Action {
+                    cell: self.units[u],
+                    left: true,
+                    chord: false,
+                });
+            }
+        }
+        if unrevealed.is_empty() || g + self.lower_bound(state, &unrevealed) >= self.best {
+            return;
+        }
+        // 可以搜索的操作与上一步有关，因此上一步也是状态的一部分
+        let mut key = state.to_vec();
+        key.push(prev.map_or(u64::MAX, |(a, _)| a.key() as u64));
+        match self.visited.get(&key) {
+            Some(&g0) if g0 <= g => return,
+            _ => {
+                self.visited.insert(key, g);
+            }
+        }
+        for (action, cost, _, next) in self.actions(state, &unrevealed) {
+            if g + cost >= self.best {
+                continue;
+            }
+            if let Some((a, parent)) = prev {
+                if action.key() < a.key() {
+                    let delta: Vec<u64> = next.iter().zip(state).map(|(x, y)| x ^ y).collect();
+                    if self.effect(parent, action) == Some((cost, delta)) {
+                        continue;
+                    }
+                }
+            }
+            self.path.push(action);
+            self.search(&next, g + cost, Some((action, state)));
+            self.path.pop();
+            if self.stopped {
+                return;
+            }
+        }
+    }
+
+    // 把宏操作展开成左键、右键、双击的序列，格式同cal_zini_ops
+    fn to_ops(&self, path: &[Action]) -> Vec<(ZiniOp, (usize, usize))> {
+        let mut state = vec![0; self.words * 2];
+        let mut ops = vec![];
+        let pos = |id: usize| (id / self.column, id % self.column);
+        for &action in path {
+            if action.left {
+                ops.push((ZiniOp::Left, pos(action.cell)));
+            }
+            if action.chord {
+                for &m in &self.neighbors[action.cell] {
+                    if self.number[m] == -1 && !get_bit(&state[self.words..], m) {
+                        ops.push((ZiniOp::Right, pos(m)));
+                    }
+                }
+                ops.push((ZiniOp::Chord, pos(action.cell)));
+            }
+            self.apply(&mut state, action);
+        }
+        ops
+    }
+}
+
+/// 用分支定界法精确求解扫完局面所需的最少点击次数（左键 + 右键 + 双击），以ZiNi为初始的上界。
+/// 搜索量随局面增大而急剧增加，适用于初级、小型的自定义局面。
+/// - 输入：真实局面、搜索限制。
+/// - 返回：点击次数、操作序列（格式同cal_zini_ops，可用zini_ops_to_step_flow回放）、是否已证明为最优。
+///   达到搜索限制时，返回目前找到的最好的结果，且第三项为false。
+/// # Example
+/// ```
+/// use ms_toollib::{cal_optimal_clicks, cal_zini, OptimalClicksLimits, ZiniOp};
+/// let board = vec![
+///     vec![0, 0, 0],
+///     vec![0, 1, 1],
+///     vec![0, 1, -1],
+/// ];
+/// let (clicks, ops, is_optimal) = cal_optimal_clicks(&board, OptimalClicksLimits::default());
+/// assert_eq!((clicks, is_optimal), (1, true));
+/// assert_eq!(ops, vec![(ZiniOp::Left, (0, 0))]);
+/// assert!(clicks <= cal_zini(&board));
+/// ```
+pub fn cal_optimal_clicks(
+    board: &Vec<Vec<i32>>,
+    limits: OptimalClicksLimits,
+) -> (usize, Vec<(ZiniOp, (usize, usize))>, bool) {
+    let mut solver = Solver::new(board, limits);
+    let zini_ops = cal_zini_ops(board);
+    solver.best = zini_ops.len();
+    let state = vec![0; solver.words * 2];
+    solver.search(&state, 0, None);
+    let is_optimal = !solver.stopped;
+    if solver.best < zini_ops.len() {
+        (solver.best, solver.to_ops(&solver.best_path), is_optimal)
+    } else {
+        (zini_ops.len(), zini_ops, is_optimal)
+    }
+}
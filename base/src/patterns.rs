@@ -0,0 +1,252 @@
+// 识别游戏局面上的定式，例如1-2、1-2-1、1-2-2-1等
+
+use crate::topology::Topology;
+
+/// 定式的定义。坐标是相对于第一个数字的（行，列），数字排在第0行，未打开的格子在第1行，
+/// 识别时会尝试所有的8种方向（4种旋转，各自再翻转）。
+struct PatternDef {
+    name: &'static str,
+    /// 数字的位置与值。值是数字减去周围标的雷数
+    numbers: &'static [(i32, i32, i32)],
+    /// 必须未打开的格子
+    unknown: &'static [(i32, i32)],
+    /// 可以未打开、也可以已打开的格子。未打开时一定不是雷
+    optional: &'static [(i32, i32)],
+    /// 必须已打开或在局面以外的格子（除了数字周围以外的）
+    opened: &'static [(i32, i32)],
+    /// 能判出的雷
+    mines: &'static [(i32, i32)],
+    /// 能判出的非雷（不包括optional）
+    not_mines: &'static [(i32, i32)],
+}
+
+const PATTERNS: &[PatternDef] = &[
+    // 靠边的1-1：第一个1只看得到两个格子，第二个1的第三个格子不是雷
+    PatternDef {
+        name: "1-1",
+        numbers: &[(0, 0, 1), (0, 1, 1)],
+        unknown: &[(1, 0), (1, 1), (1, 2)],
+        optional: &[],
+        opened: &[],
+        mines: &[],
+        not_mines: &[(1, 2)],
+    },
+    // 1-2：2的第三个格子是雷，1的第一个格子不是雷
+    PatternDef {
+        name: "1-2",
+        numbers: &[(0, 0, 1), (0, 1, 2)],
+        unknown: &[(1, 0), (1, 1), (1, 2)],
+        optional: &[(1, -1)],
+        opened: &[],
+        mines: &[(1, 2)],
+        not_mines: &[],
+    },
+    PatternDef {
+        name: "1-2-1",
+        numbers: &[(0, 0, 1), (0, 1, 2), (0, 2, 1)],
+        unknown: &[(1, 0), (1, 1), (1, 2)],
+        optional: &[(1, -1), (1, 3)],
+        opened: &[],
+        mines: &[(1, 0), (1, 2)],
+        not_mines: &[(1, 1)],
+    },
+    PatternDef {
+        name: "1-2-2-1",
+        numbers: &[(0, 0, 1), (0, 1, 2), (0, 2, 2), (0, 3, 1)],
+        unknown: &[(1, 0), (1, 1), (1, 2), (1, 3)],
+        optional: &[(1, -1), (1, 4)],
+        opened: &[],
+        mines: &[(1, 1), (1, 2)],
+        not_mines: &[(1, 0), (1, 3)],
+    },
+    // 洞：四周都已打开的一个格子，旁边的1只看得到它
+    PatternDef {
+        name: "hole",
+        numbers: &[(0, 0, 1)],
+        unknown: &[(1, 0)],
+        optional: &[],
+        opened: &[(2, -1), (2, 0), (2, 1)],
+        mines: &[(1, 0)],
+        not_mines: &[],
+    },
+    // 角上的三角形：1与两侧打开的格子围住斜对角的一个格子
+    PatternDef {
+        name: "triangle",
+        numbers: &[(0, 0, 1)],
+        unknown: &[(1, 1)],
+        optional: &[],
+        opened: &[],
+        mines: &[(1, 1)],
+        not_mines: &[],
+    },
+];
+
+// 8种方向的变换矩阵
+const ORIENTATIONS: [[i32; 4]; 8] = [
+    [1, 0, 0, 1],
+    [0, 1, -1, 0],
+    [-1, 0, 0, -1],
+    [0, -1, 1, 0],
+    [1, 0, 0, -1],
+    [0, 1, 1, 0],
+    [-1, 0, 0, 1],
+    [0, -1, -1, 0],
+];
+
+/// 游戏局面上识别出的一个定式。见find_patterns。
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PatternMatch {
+    /// 定式的名字："1-1"、"1-2"、"1-2-1"、"1-2-2-1"、"hole"、"triangle"
+    pub name: &'static str,
+    /// 构成定式的数字的位置（行，列），从小到大排列
+    pub numbers: Vec<(usize, usize)>,
+    /// 定式判出的雷的位置
+    pub mines: Vec<(usize, usize)>,
+    /// 定式判出的非雷的位置
+    pub not_mines: Vec<(usize, usize)>,
+}
+
+// 局面上(x, y)经过偏移后的位置，超出局面时返回None
+fn offset(
+    row: usize,
+    column: usize,
+    (x, y): (usize, usize),
+    (dx, dy): (i32, i32),
+    m: &[i32; 4],
+) -> Option<(usize, usize)> {
+    let i = x as i32 + m[0] * dx + m[1] * dy;
+    let j = y as i32 + m[2] * dx + m[3] * dy;
+    if i < 0 || j < 0 || i >= row as i32 || j >= column as i32 {
+        return None;
+    }
+    Some((i as usize, j as usize))
+}
+
+// 数字(x, y)周围未打开的格子，以及减去周围标的雷以后的值
+fn unknown_around(game_board: &[Vec<i32>], x: usize, y: usize) -> (i32, Vec<(usize, usize)>) {
+    let mut value = game_board[x][y];
+    let mut unknown = vec![];
+    for (i, j) in Topology::Plane.around(game_board.len(), game_board[0].len(), x, y) {
+        match game_board[i][j] {
+            10 => unknown.push((i, j)),
+            11 => value -= 1,
+            _ => {}
+        }
+    }
+    (value, unknown)
+}
+
+// 以(x, y)为第一个数字、按方向m匹配定式
+fn match_pattern(
+    game_board: &[Vec<i32>],
+    pattern: &PatternDef,
+    (x, y): (usize, usize),
+    m: &[i32; 4],
+) -> Option<PatternMatch> {
+    let row = game_board.len();
+    let column = game_board[0].len();
+    let to_board = |cells: &[(i32, i32)]| -> Option<Vec<(usize, usize)>> {
+        cells
+            .iter()
+            .map(|&d| offset(row, column, (x, y), d, m))
+            .collect()
+    };
+    let unknown = to_board(pattern.unknown)?;
+    if unknown.iter().any(|&(i, j)| game_board[i][j] != 10) {
+        return None;
+    }
+    for &d in pattern.opened {
+        if let Some((i, j)) = offset(row, column, (x, y), d, m) {
+            if game_board[i][j] == 10 {
+                return None;
+            }
+        }
+    }
+    let optional: Vec<(usize, usize)> = pattern
+        .optional
+        .iter()
+        .filter_map(|&d| offset(row, column, (x, y), d, m))
+        .filter(|&(i, j)| game_board[i][j] == 10)
+        .collect();
+    let mut numbers = vec![];
+    for &(dx, dy, v) in pattern.numbers {
+        let (i, j) = offset(row, column, (x, y), (dx, dy), m)?;
+        if !(0..=8).contains(&game_board[i][j]) {
+            return None;
+        }
+        let (value, mut around) = unknown_around(game_board, i, j);
+        let mut expected: Vec<(usize, usize)> = unknown
+            .iter()
+            .chain(optional.iter())
+            .filter(|&&c| Topology::Plane.is_around(row, column, (i, j), c))
+            .cloned()
+            .collect();
+        around.sort_unstable();
+        expected.sort_unstable();
+        if value != v || around != expected {
+            return None;
+        }
+        numbers.push((i, j));
+    }
+    let mut mines = to_board(pattern.mines)?;
+    let mut not_mines = to_board(pattern.not_mines)?;
+    not_mines.extend(optional);
+    numbers.sort_unstable();
+    mines.sort_unstable();
+    not_mines.sort_unstable();
+    Some(PatternMatch {
+        name: pattern.name,
+        numbers,
+        mines,
+        not_mines,
+    })
+}
+
+/// 识别游戏局面上所有的定式，包括靠边的1-1、1-2、1-2-1、1-2-2-1、洞（hole）、角上的三角形（triangle），
+/// 会尝试所有的旋转和翻转。
+/// - 输入：游戏局面。标的雷（11）当作雷，数字减去周围标的雷以后再识别，例如旁边有雷的2可以当作1。
+/// - 返回：识别出的定式，按第一个数字的位置排列。被更大的定式包含的定式（例如1-2-1中的1-2）不返回。
+/// - 注意：要求定式中每个数字周围未打开的格子与定式完全一致，因此不会误判，但可能漏掉周围更复杂的情况。
+/// # Example
+/// ```
+/// use ms_toollib::find_patterns;
+/// let game_board = vec![
+///     vec![0, 0, 0, 0],
+///     vec![1, 2, 2, 1],
+///     vec![10, 10, 10, 10],
+/// ];
+/// let patterns = find_patterns(&game_board);
+/// assert_eq!(patterns.len(), 1);
+/// assert_eq!(patterns[0].name, "1-2-2-1");
+/// assert_eq!(patterns[0].mines, vec![(2, 1), (2, 2)]);
+/// assert_eq!(patterns[0].not_mines, vec![(2, 0), (2, 3)]);
+/// ```
+pub fn find_patterns(game_board: &Vec<Vec<i32>>) -> Vec<PatternMatch> {
+    let mut matches: Vec<PatternMatch> = vec![];
+    for x in 0..game_board.len() {
+        for y in 0..game_board[0].len() {
+            if !(1..=8).contains(&game_board[x][y]) {
+                continue;
+            }
+            for pattern in PATTERNS {
+                for m in &ORIENTATIONS {
+                    if let Some(p) = match_pattern(game_board, pattern, (x, y), m) {
+                        if !matches.contains(&p) {
+                            matches.push(p);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    let is_part = |a: &PatternMatch, b: &PatternMatch| {
+        a.numbers.len() < b.numbers.len() && a.numbers.iter().all(|c| b.numbers.contains(c))
+    };
+    let mut ans: Vec<PatternMatch> = matches
+        .iter()
+        .filter(|a| !matches.iter().any(|b| is_part(a, b)))
+        .cloned()
+        .collect();
+    ans.sort_by(|a, b| a.numbers.cmp(&b.numbers).then(a.name.cmp(b.name)));
+    ans
+}
@@ -8,6 +8,8 @@ use num_traits::ToPrimitive;
 
 use crate::binomial::{Binomial, BinomialCache};
 use crate::tile::Tile;
+use crate::topology::Topology;
+use crate::utils::cal_area;
 
 pub const PLAY_STYLE_FLAGS: usize = 1;
 pub const PLAY_STYLE_NOFLAGS: usize = 2;
@@ -124,8 +126,7 @@ impl BoxWitness {
     }
 
     pub fn overlap(&self, other: &BoxWitness) -> bool {
-        let dx = if other.tile.x > self.tile.x { other.tile.x - self.tile.x } else { self.tile.x - other.tile.x };
-        let dy = if other.tile.y > self.tile.y { other.tile.y - self.tile.y } else { self.tile.y - other.tile.y };
+        let (dx, dy) = self.tile.distance(&other.tile);
         if dx > 2 || dy > 2 {
             return false;
         }
@@ -143,8 +144,7 @@ impl BoxWitness {
         if self.tiles.len() != other.tiles.len() {
             return false;
         }
-        let dx = if other.tile.x > self.tile.x { other.tile.x - self.tile.x } else { self.tile.x - other.tile.x };
-        let dy = if other.tile.y > self.tile.y { other.tile.y - self.tile.y } else { self.tile.y - other.tile.y };
+        let (dx, dy) = self.tile.distance(&other.tile);
         if dx > 2 || dy > 2 {
             return false;
         }
@@ -757,25 +757,18 @@ struct TileBoard {
     height: usize,
     num_bombs: usize,
     tiles: Vec<Rc<Tile>>,
+    topology: Topology,
 }
 
 impl Board for TileBoard {
     fn get_adjacent(&self, tile: &Tile) -> Vec<Rc<Tile>> {
         let col = tile.x;
         let row = tile.y;
-        let r1 = if row > 0 { row - 1 } else { 0 };
-        let r2 = if row + 1 < self.height { row + 1 } else { self.height - 1 };
-        let c1 = if col > 0 { col - 1 } else { 0 };
-        let c2 = if col + 1 < self.width { col + 1 } else { self.width - 1 };
-        let mut res = Vec::new();
-        for r in r1..=r2 {
-            for c in c1..=c2 {
-                if !(r == row && c == col) {
-                    res.push(self.tiles[r * self.width + c].clone());
-                }
-            }
-        }
-        res
+        self.topology
+            .around(self.height, self.width, row, col)
+            .filter(|&(r, c)| !(r == row && c == col))
+            .map(|(r, c)| self.tiles[r * self.width + c].clone())
+            .collect()
     }
 
     fn adjacent_found_mine_count(&self, tile: &Tile) -> usize {
@@ -807,7 +800,7 @@ fn extract_board_state(board: &TileBoard) -> (Vec<Rc<Tile>>, Vec<Rc<Tile>>, usiz
             squares_left += 1;
             continue;
         }
-        if tile.is_safe.get() {
+        if tile.is_safe.get() || tile.is_masked.get() {
             continue;
         }
         let adj = board.get_adjacent(tile);
@@ -2307,19 +2300,8 @@ impl ProbabilityEngine {
     }
 }
 
-/// JSMinesweeper 概率引擎移植版。
-/// - 输入：局面、总雷数。
-/// - 返回：所有边缘格子是雷的概率、内部未知格子是雷的概率、局面中总未知雷数范围（[最小可能的总雷数, 当前总雷数, 最大可能的总雷数]，包含已经标出的雷）、最大独立集格数（或其它直观反映求解复杂度的值）。
-/// - 错误码：0=正常, 1=盘面矛盾, 2=枚举过长, 3=输入参数非法。
-/// 输入局面中，0-8代表数字0-8，10代表未开格，11标记视为已知雷（算法标记出来的此处必定为雷），12标记视为已知安全（算法标记出来的此处必定不为雷）。
-/// 总雷数可能为低于或超出上下限的整数，此时算法将其钳位为最小值或最大值；总雷数还可能为0-1之间的浮点数，此时算法应将其视为局面中雷的密度，然后再钳位为最小值或最大值
-pub fn cal_probability_csp(
-    board_of_game: &Vec<Vec<i32>>,
-    minenum: f64,
-) -> Result<(Vec<((usize, usize), f64)>, f64, [usize; 3], usize), usize> {
-    use std::rc::Rc;
-
-    // Validate input
+/// 校验输入的局面。返回局面的高、宽和已标出的雷数（11）。
+fn check_board_of_game(board_of_game: &[Vec<i32>]) -> Result<(usize, usize, usize), usize> {
     let height = board_of_game.len();
     if height == 0 {
         return Err(3);
@@ -2333,7 +2315,7 @@ pub fn cal_probability_csp(
             return Err(3);
         }
         for &v in row.iter() {
-            if v > 12 || v == 9 {
+            if (v > 12 && v != 19) || v == 9 {
                 return Err(3);
             }
         }
@@ -2348,8 +2330,63 @@ pub fn cal_probability_csp(
             }
         }
     }
+    Ok((height, width, flagged_count))
+}
 
-    let total_cells = width * height;
+fn build_tiles(board_of_game: &[Vec<i32>], topology: Topology) -> Vec<Rc<Tile>> {
+    let height = board_of_game.len();
+    let width = board_of_game[0].len();
+    let torus = match topology {
+        Topology::Plane => None,
+        Topology::Torus => Some((width, height)),
+    };
+    let mut tiles = Vec::with_capacity(width * height);
+    for r in 0..height {
+        for c in 0..width {
+            let mut tile = Tile::new(c, r, r * width + c);
+            tile.torus = torus;
+            tiles.push(Rc::new(tile));
+        }
+    }
+    for r in 0..height {
+        for c in 0..width {
+            let v = board_of_game[r][c];
+            let tile = &tiles[r * width + c];
+            match v {
+                10 => {}
+                11 => {
+                    tile.set_found_bomb();
+                    tile.set_covered(false);
+                }
+                12 => {
+                    tile.set_covered(false);
+                    tile.set_value(0);
+                    tile.is_safe.set(true);
+                }
+                0..=8 => {
+                    tile.set_covered(false);
+                    tile.set_value(v as u8);
+                }
+                19 => {
+                    tile.set_covered(false);
+                    tile.is_masked.set(true);
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+    tiles
+}
+
+/// 计算局面中总雷数的可能范围，并把输入的总雷数钳位到该范围内。
+/// - 返回：钳位后的总雷数、[最小可能的总雷数, 当前总雷数, 最大可能的总雷数]。
+fn clamp_total_mines(
+    board_of_game: &[Vec<i32>],
+    minenum: f64,
+    topology: Topology,
+) -> Result<(usize, [usize; 3]), usize> {
+    let (height, width, flagged_count) = check_board_of_game(board_of_game)?;
+    let total_cells = cal_area(board_of_game);
 
     // If minenum is in (0,1), treat as density
     let raw_total = if minenum > 0.0 && minenum < 1.0 {
@@ -2358,45 +2395,12 @@ pub fn cal_probability_csp(
         minenum as usize
     };
 
-    let build_tiles = || {
-        let mut tiles = Vec::with_capacity(total_cells);
-        for r in 0..height {
-            for c in 0..width {
-                tiles.push(Rc::new(Tile::new(c, r, r * width + c)));
-            }
-        }
-        for r in 0..height {
-            for c in 0..width {
-                let v = board_of_game[r][c];
-                let tile = &tiles[r * width + c];
-                match v {
-                    10 => {}
-                    11 => {
-                        tile.set_found_bomb();
-                        tile.set_covered(false);
-                    }
-                    12 => {
-                        tile.set_covered(false);
-                        tile.set_value(0);
-                        tile.is_safe.set(true);
-                    }
-                    0..=8 => {
-                        tile.set_covered(false);
-                        tile.set_value(v as u8);
-                    }
-                    _ => unreachable!(),
-                }
-            }
-        }
-        tiles
-    };
-
-    let base_tiles = build_tiles();
     let base_board = TileBoard {
         width,
         height,
         num_bombs: flagged_count,
-        tiles: base_tiles,
+        tiles: build_tiles(board_of_game, topology),
+        topology,
     };
 
     let (range_witnesses, range_witnessed, squares_left, _) = extract_board_state(&base_board);
@@ -2416,6 +2420,7 @@ pub fn cal_probability_csp(
             height,
             num_bombs: flagged_count + analysis_remaining_mines,
             tiles: base_board.tiles.clone(),
+            topology,
         });
         let options = ProbabilityOptions::new(PLAY_STYLE_EFFICIENCY, false, false, true);
         let mut range_pe = ProbabilityEngine::new(
@@ -2443,61 +2448,96 @@ pub fn cal_probability_csp(
     let min_possible = flagged_count + min_edge_mines;
     let max_possible = flagged_count + max_edge_mines + tiles_off_edge;
     let total_mines = raw_total.max(min_possible).min(max_possible);
+    Ok((total_mines, [min_possible, total_mines, max_possible]))
+}
 
-    if range_witnesses.is_empty() {
-        let remaining_mines = total_mines.saturating_sub(flagged_count);
-        let mine_prob = if squares_left == 0 {
-            0.0
-        } else {
-            remaining_mines as f64 / squares_left as f64
-        };
-        // No edge/witness cells: for API consistency with cal_probability_enum,
-        // return an empty edge_probs Vec and provide the interior probability
-        // via the second return value (`mine_prob`). Complexity is 0.
-        return Ok((
-            Vec::new(),
-            mine_prob,
-            [min_possible, total_mines, max_possible],
-            0,
-        ));
-    }
-
-    let tiles = build_tiles();
+/// 在确定的总雷数下，运行完一次概率引擎后的局面。
+struct SolvedBoard {
+    tiles: Vec<Rc<Tile>>,
+    witnessed: Vec<Rc<Tile>>,
+    squares_left: usize,
+    mines_left: usize,
+    /// 没有边缘格子或没有剩余的雷时，不需要运行引擎，为None
+    pe: Option<ProbabilityEngine>,
+}
+
+impl SolvedBoard {
+    /// 与局面相容的雷的排布总数
+    fn solutions_count(&self) -> BigUint {
+        match &self.pe {
+            Some(pe) => pe.final_solutions_count.clone(),
+            None if self.mines_left == 0 => BigUint::from(1u32),
+            None => combination(self.mines_left, self.squares_left),
+        }
+    }
+
+    /// 某个未打开的格子不是雷的概率
+    fn safety(&self, tile: &Tile) -> f64 {
+        match &self.pe {
+            Some(pe) => pe.get_probability(tile),
+            None => self.off_edge_safety(),
+        }
+    }
+
+    /// 内部（非边缘）未打开的格子不是雷的概率
+    fn off_edge_safety(&self) -> f64 {
+        match &self.pe {
+            Some(pe) => pe.off_edge_probability,
+            None if self.squares_left == 0 => 1.0,
+            None => 1.0 - self.mines_left as f64 / self.squares_left as f64,
+        }
+    }
+
+    /// 局面上是否存在必然安全的未打开的格子
+    fn has_safe_tile(&self) -> bool {
+        match &self.pe {
+            Some(pe) => {
+                !pe.local_clears.is_empty()
+                    || (pe.tiles_off_edge > 0 && pe.off_edge_mine_tally == BigUint::from(0u32))
+            }
+            None => self.mines_left == 0 && self.squares_left > 0,
+        }
+    }
+}
+
+/// 以确定的总雷数（不再钳位）运行概率引擎。错误码同cal_probability_csp。
+fn solve_board(
+    board_of_game: &[Vec<i32>],
+    total_mines: usize,
+    topology: Topology,
+) -> Result<SolvedBoard, usize> {
+    let height = board_of_game.len();
+    let width = board_of_game[0].len();
+    let tiles = build_tiles(board_of_game, topology);
     let tile_board = TileBoard {
         width,
         height,
         num_bombs: total_mines,
         tiles: tiles.clone(),
+        topology,
     };
 
     let (witnesses, witnessed, squares_left, mines_left) = extract_board_state(&tile_board);
-
-    // No remaining mines — all probabilities are zero
-    if mines_left == 0 {
-        let edge_probs: Vec<((usize, usize), f64)> = witnessed
-            .iter()
-            .map(|t| ((t.y, t.x), 0.0))
-            .collect();
-        return Ok((
-            edge_probs,
-            0.0,
-            [min_possible, total_mines, max_possible],
-            0,
-        ));
+    if squares_left - witnessed.len() > get_binomial_cache().lock().unwrap().get_max_n() {
+        return Err(2);
+    }
+    if witnesses.is_empty() || mines_left == 0 {
+        return Ok(SolvedBoard {
+            tiles,
+            witnessed,
+            squares_left,
+            mines_left,
+            pe: None,
+        });
     }
 
-    let board_box: std::boxed::Box<dyn Board> = std::boxed::Box::new(TileBoard {
-        width,
-        height,
-        num_bombs: total_mines,
-        tiles,
-    });
+    let board_box: std::boxed::Box<dyn Board> = std::boxed::Box::new(tile_board);
 
     // Use full_probability=true to force full enumeration
     let options = ProbabilityOptions::new(PLAY_STYLE_EFFICIENCY, false, false, true);
     let mut pe = ProbabilityEngine::new(
         board_box,
-        witnesses.clone(),
+        witnesses,
         witnessed.clone(),
         squares_left,
         mines_left,
@@ -2506,11 +2546,6 @@ pub fn cal_probability_csp(
 
     // Check for contradiction detected during construction
     if !pe.valid_web {
-        for w in &pe.box_witnesses {
-            if w.mines_to_find < 0 || (w.mines_to_find as usize) > w.tiles.len() {
-                return Err(1);
-            }
-        }
         return Err(1);
     }
 
@@ -2521,9 +2556,61 @@ pub fn cal_probability_csp(
         return Err(1);
     }
 
+    Ok(SolvedBoard {
+        tiles,
+        witnessed,
+        squares_left,
+        mines_left,
+        pe: Some(pe),
+    })
+}
+
+/// JSMinesweeper 概率引擎移植版。
+/// - 输入：局面、总雷数。
+/// - 返回：所有边缘格子是雷的概率、内部未知格子是雷的概率、局面中总未知雷数范围（[最小可能的总雷数, 当前总雷数, 最大可能的总雷数]，包含已经标出的雷）、最大独立集格数（或其它直观反映求解复杂度的值）。
+/// - 错误码：0=正常, 1=盘面矛盾, 2=枚举过长, 3=输入参数非法。
+/// 输入局面中，0-8代表数字0-8，10代表未开格，11标记视为已知雷（算法标记出来的此处必定为雷），12标记视为已知安全（算法标记出来的此处必定不为雷），19代表被遮挡的位置。
+/// 总雷数可能为低于或超出上下限的整数，此时算法将其钳位为最小值或最大值；总雷数还可能为0-1之间的浮点数，此时算法应将其视为局面中雷的密度，然后再钳位为最小值或最大值
+pub fn cal_probability_csp(
+    board_of_game: &Vec<Vec<i32>>,
+    minenum: f64,
+) -> Result<(Vec<((usize, usize), f64)>, f64, [usize; 3], usize), usize> {
+    cal_probability_csp_with_topology(board_of_game, minenum, Topology::Plane)
+}
+
+/// 在指定的拓扑结构上计算概率。输入、返回值和错误码同cal_probability_csp。
+pub fn cal_probability_csp_with_topology(
+    board_of_game: &Vec<Vec<i32>>,
+    minenum: f64,
+    topology: Topology,
+) -> Result<(Vec<((usize, usize), f64)>, f64, [usize; 3], usize), usize> {
+    let (total_mines, mine_range) = clamp_total_mines(board_of_game, minenum, topology)?;
+    let solved = solve_board(board_of_game, total_mines, topology)?;
+
+    let pe = match &solved.pe {
+        Some(pe) => pe,
+        None => {
+            // No edge/witness cells: for API consistency with cal_probability_enum,
+            // return an empty edge_probs Vec and provide the interior probability
+            // via the second return value (`mine_prob`). Complexity is 0.
+            // No remaining mines — all probabilities are zero
+            let edge_probs: Vec<((usize, usize), f64)> = solved
+                .witnessed
+                .iter()
+                .map(|t| ((t.y, t.x), 0.0))
+                .collect();
+            let mine_prob = if solved.squares_left == 0 {
+                0.0
+            } else {
+                solved.mines_left as f64 / solved.squares_left as f64
+            };
+            return Ok((edge_probs, mine_prob, mine_range, 0));
+        }
+    };
+
     // Edge cell mine probabilities (converted from safety)
-    let mut edge_probs: Vec<((usize, usize), f64)> = Vec::with_capacity(witnessed.len());
-    for t in &witnessed {
+    let mut edge_probs: Vec<((usize, usize), f64)> = Vec::with_capacity(solved.witnessed.len());
+    for t in &solved.witnessed {
         let mine_prob = match pe.get_box(t) {
             Some(b) => divide_bigint_exact(&b.mine_tally.borrow(), &pe.final_solutions_count),
             None => {
@@ -2561,12 +2648,289 @@ pub fn cal_probability_csp(
     };
 
     // API compatibility: expose the number of edge cells with explicit probabilities.
-    let complexity = witnessed.len();
+    let complexity = solved.witnessed.len();
 
     Ok((
         edge_probs,
         off_edge_mine_prob,
-        [min_possible, total_mines, max_possible],
+        mine_range,
         complexity,
     ))
 }
+
+/// 候选格子的最低安全率与最佳安全率之比，低于此比例的格子不作为候选
+const SUGGEST_MOVES_THRESHOLD: f64 = 0.9;
+
+/// 走法建议中的一个候选格子。
+#[derive(Clone, Debug, PartialEq)]
+pub struct MoveSuggestion {
+    /// 位置，（行，列）
+    pub pos: (usize, usize),
+    /// 不是雷的概率
+    pub safety: f64,
+    /// 点开后能推理出新的必然安全的格子的概率。必然安全的格子记为1.0，死格记为0.0
+    pub progress: f64,
+    /// 是否是死格，即点开后显示的数字不能提供任何新的信息
+    pub dead: bool,
+}
+
+/// 局面上某位置周围的（已标雷数, 未打开的格子数）
+fn count_adjacent(board_of_game: &[Vec<i32>], r: usize, c: usize) -> (usize, usize) {
+    let mut flags = 0;
+    let mut covered = 0;
+    for (m, row) in board_of_game.iter().enumerate().take(r + 2).skip(r.max(1) - 1) {
+        for (n, &v) in row.iter().enumerate().take(c + 2).skip(c.max(1) - 1) {
+            if m == r && n == c {
+                continue;
+            }
+            match v {
+                10 => covered += 1,
+                11 => flags += 1,
+                _ => {}
+            }
+        }
+    }
+    (flags, covered)
+}
+
+/// 计算点开某个格子后取得进展的概率。对于格子可能显示的每一个数字，假设其为该数字并重新求解局面，
+/// 若新局面上存在必然安全的格子，则计入该数字对应的雷的排布数。
+fn cal_progress(
+    board_of_game: &[Vec<i32>],
+    total_mines: usize,
+    pos: (usize, usize),
+    solutions: &BigUint,
+) -> f64 {
+    let (flags, covered) = count_adjacent(board_of_game, pos.0, pos.1);
+    if covered == 0 {
+        // 周围已经没有未知的格子，点开后不可能带来新的信息
+        return 0.0;
+    }
+    let mut board = board_of_game.to_vec();
+    let mut progress_solutions = BigUint::from(0u32);
+    for value in flags..=flags + covered {
+        board[pos.0][pos.1] = value as i32;
+        if let Ok(solved) = solve_board(&board, total_mines, Topology::Plane) {
+            if solved.has_safe_tile() {
+                progress_solutions += solved.solutions_count();
+            }
+        }
+    }
+    divide_bigint_exact(&progress_solutions, solutions)
+}
+
+/// 基于概率引擎的走法建议。
+/// 存在必然安全的格子时，只返回所有必然安全的格子；否则返回概率引擎的最佳候选（安全率不低于最佳安全率的90%的
+/// 非死格边缘格子）、同样安全的死格，以及与边缘格子相邻或位于四角的内部格子。只对非死格计算进展概率。
+/// 排序规则：非死格优先，再按安全率、进展概率从高到低。
+/// - 输入：局面、总雷数（规则同cal_probability_csp）。
+/// - 返回：按推荐程度从高到低排序的候选格子。
+/// - 错误码：同cal_probability_csp。
+pub fn suggest_moves(board_of_game: &Vec<Vec<i32>>, minenum: f64) -> Result<Vec<MoveSuggestion>, usize> {
+    let (total_mines, _) = clamp_total_mines(board_of_game, minenum, Topology::Plane)?;
+    let solved = solve_board(board_of_game, total_mines, Topology::Plane)?;
+    let height = board_of_game.len();
+    let width = board_of_game[0].len();
+    let is_dead = |tile: &Tile| solved.pe.as_ref().is_some_and(|pe| pe.is_dead(tile));
+
+    // 必然安全的格子
+    let mut safe_moves = vec![];
+    for tile in &solved.tiles {
+        let covered = tile.is_covered() && !tile.is_solver_found_bomb();
+        if tile.is_safe.get() || (covered && solved.safety(tile) >= 1.0) {
+            safe_moves.push(MoveSuggestion {
+                pos: (tile.y, tile.x),
+                safety: 1.0,
+                progress: 1.0,
+                dead: is_dead(tile),
+            });
+        }
+    }
+    if !safe_moves.is_empty() {
+        safe_moves.sort_by_key(|m| m.dead);
+        return Ok(safe_moves);
+    }
+
+    let off_edge_safety = solved.off_edge_safety();
+    let best_safety = match &solved.pe {
+        Some(pe) => pe.best_probability,
+        None => off_edge_safety,
+    };
+    let test = best_safety * SUGGEST_MOVES_THRESHOLD;
+    let solutions = solved.solutions_count();
+
+    let mut moves = vec![];
+    if let Some(pe) = &solved.pe {
+        // 概率引擎给出的最佳候选都不是死格，只对它们计算进展概率
+        for action in pe.get_best_candidates(SUGGEST_MOVES_THRESHOLD) {
+            moves.push(MoveSuggestion {
+                pos: (action.y, action.x),
+                safety: action.prob,
+                progress: cal_progress(board_of_game, total_mines, (action.y, action.x), &solutions),
+                dead: false,
+            });
+        }
+        // 死格点开后不能提供新的信息，进展概率为0
+        for tile in pe.get_dead_tiles() {
+            if solved.witnessed.iter().any(|w| w.is_equal(tile)) && solved.safety(tile) >= test {
+                moves.push(MoveSuggestion {
+                    pos: (tile.y, tile.x),
+                    safety: solved.safety(tile),
+                    progress: 0.0,
+                    dead: true,
+                });
+            }
+        }
+    }
+    if solved.squares_left > solved.witnessed.len() && off_edge_safety >= test {
+        let off_edge: Vec<Rc<Tile>> = solved
+            .tiles
+            .iter()
+            .filter(|t| {
+                t.is_covered()
+                    && !t.is_solver_found_bomb()
+                    && !solved.witnessed.iter().any(|w| w.is_equal(t))
+            })
+            .cloned()
+            .collect();
+        let mut near: Vec<Rc<Tile>> = off_edge
+            .iter()
+            .filter(|t| {
+                (t.x == 0 || t.x + 1 == width) && (t.y == 0 || t.y + 1 == height)
+                    || solved.witnessed.iter().any(|w| w.is_adjacent(t))
+            })
+            .cloned()
+            .collect();
+        if near.is_empty() {
+            near = off_edge;
+        }
+        for tile in near {
+            let dead = is_dead(&tile);
+            let progress = if dead {
+                0.0
+            } else {
+                cal_progress(board_of_game, total_mines, (tile.y, tile.x), &solutions)
+            };
+            moves.push(MoveSuggestion {
+                pos: (tile.y, tile.x),
+                safety: off_edge_safety,
+                progress,
+                dead,
+            });
+        }
+    }
+    moves.sort_by(|a, b| {
+        a.dead
+            .cmp(&b.dead)
+            .then(b.safety.partial_cmp(&a.safety).unwrap_or(Ordering::Equal))
+            .then(b.progress.partial_cmp(&a.progress).unwrap_or(Ordering::Equal))
+            .then(a.pos.cmp(&b.pos))
+    });
+    Ok(moves)
+}
+
+/// 运行概率引擎，并用给定的方法检查局面中无法避免的猜测。
+fn check_unavoidable<F>(board_of_game: &Vec<Vec<i32>>, minenum: f64, check: F) -> Result<Option<Vec<(usize, usize)>>, usize>
+where
+    F: Fn(&ProbabilityEngine) -> Option<Vec<Rc<Tile>>>,
+{
+    let (total_mines, _) = clamp_total_mines(board_of_game, minenum, Topology::Plane)?;
+    let solved = solve_board(board_of_game, total_mines, Topology::Plane)?;
+    Ok(solved
+        .pe
+        .as_ref()
+        .and_then(check)
+        .map(|tiles| tiles.iter().map(|t| (t.y, t.x)).collect()))
+}
+
+/// 检查局面中是否有无法避免的猜测，即某个数字周围的未知格子，无论如何都不能通过打开其他格子获得更多信息。
+/// - 返回：需要猜的格子（非死格优先），没有则返回None。
+/// - 错误码：同cal_probability_csp。
+pub fn check_for_unavoidable_guess(board_of_game: &Vec<Vec<i32>>, minenum: f64) -> Result<Option<Vec<(usize, usize)>>, usize> {
+    check_unavoidable(board_of_game, minenum, |pe| pe.check_for_unavoidable_guess())
+}
+
+/// 检查局面中是否有无法避免的50/50，包括由若干个二选一首尾相连组成的链。
+/// - 返回：50/50涉及的格子（非死格优先），没有则返回None。
+/// - 错误码：同cal_probability_csp。
+pub fn check_for_unavoidable_5050(board_of_game: &Vec<Vec<i32>>, minenum: f64) -> Result<Option<Vec<(usize, usize)>>, usize> {
+    check_unavoidable(board_of_game, minenum, |pe| pe.check_for_unavoidable_5050())
+}
+
+/// 检查局面中是否有无法避免的50/50或伪50/50（在一个数字周围的多个格子中，有两个格子构成的二选一）。
+/// - 返回：涉及的格子（非死格优先），没有则返回None。
+/// - 错误码：同cal_probability_csp。
+pub fn check_for_unavoidable_5050_or_pseudo(board_of_game: &Vec<Vec<i32>>, minenum: f64) -> Result<Option<Vec<(usize, usize)>>, usize> {
+    check_unavoidable(board_of_game, minenum, |pe| pe.check_for_unavoidable_5050_or_pseudo())
+}
+
+/// 精确计算与局面相容的雷的排布总数，以及每个格子是雷的排布数。格子是雷的概率即为二者之比。
+/// - 返回：排布总数、与局面位置对应的是雷的排布数（已打开或标为安全的格子为0，标雷的格子等于排布总数）。
+/// - 错误码：同cal_probability_csp。
+pub fn cal_solutions_count_exact(
+    board_of_game: &Vec<Vec<i32>>,
+    minenum: f64,
+) -> Result<(BigUint, Vec<Vec<BigUint>>), usize> {
+    let (total_mines, _) = clamp_total_mines(board_of_game, minenum, Topology::Plane)?;
+    let solved = solve_board(board_of_game, total_mines, Topology::Plane)?;
+    let solutions = solved.solutions_count();
+    let off_edge_mine_count = match &solved.pe {
+        Some(pe) => pe.off_edge_mine_tally.clone(),
+        None if solved.mines_left == 0 => BigUint::from(0u32),
+        None => combination(solved.mines_left - 1, solved.squares_left - 1),
+    };
+    let width = board_of_game[0].len();
+    let mut mine_counts = vec![vec![BigUint::from(0u32); width]; board_of_game.len()];
+    for tile in &solved.tiles {
+        let count = if tile.is_solver_found_bomb() {
+            solutions.clone()
+        } else if !tile.is_covered() {
+            continue;
+        } else {
+            match solved.pe.as_ref().and_then(|pe| pe.get_box(tile)) {
+                Some(b) => b.mine_tally.borrow().clone(),
+                None => off_edge_mine_count.clone(),
+            }
+        };
+        mine_counts[tile.y][tile.x] = count;
+    }
+    Ok((solutions, mine_counts))
+}
+
+/// 计算边缘（与数字相邻的未打开的格子）上的雷数、内部未打开的格子中的雷数的概率分布。不包括已经标出的雷。
+/// - 返回：边缘雷数的分布、内部雷数的分布，均为按雷数从小到大排列的（雷数, 概率），只包含概率不为0的雷数。
+/// - 错误码：同cal_probability_csp。
+pub fn cal_mine_count_distribution(
+    board_of_game: &Vec<Vec<i32>>,
+    minenum: f64,
+) -> Result<(Vec<(usize, f64)>, Vec<(usize, f64)>), usize> {
+    let (total_mines, _) = clamp_total_mines(board_of_game, minenum, Topology::Plane)?;
+    let solved = solve_board(board_of_game, total_mines, Topology::Plane)?;
+    let pe = match &solved.pe {
+        Some(pe) => pe,
+        None => {
+            // 没有边缘格子或没有剩余的雷，剩余的雷都在内部
+            return Ok((vec![(0, 1.0)], vec![(solved.mines_left, 1.0)]));
+        }
+    };
+
+    // 按边缘雷数统计排布数，同calculate_box_probabilities
+    let mut tally: Vec<BigUint> = vec![BigUint::from(0u32); pe.mines_left + 1];
+    for pl in &pe.held_probs {
+        if pl.mine_count >= pe.min_total_mines && pl.mine_count <= pe.mines_left {
+            let mult = combination(pe.mines_left - pl.mine_count, pe.tiles_off_edge);
+            tally[pl.mine_count] += &mult * &pl.solution_count;
+        }
+    }
+    let mut edge_dist = vec![];
+    let mut off_edge_dist = vec![];
+    for (mines, count) in tally.iter().enumerate() {
+        if *count != BigUint::from(0u32) {
+            let p = divide_bigint_exact(count, &pe.final_solutions_count);
+            edge_dist.push((mines, p));
+            off_edge_dist.push((pe.mines_left - mines, p));
+        }
+    }
+    off_edge_dist.reverse();
+    Ok((edge_dist, off_edge_dist))
+}
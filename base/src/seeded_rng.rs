@@ -0,0 +1,93 @@
+// 可复现的伪随机数发生器，用于带种子的埋雷
+use getrandom::getrandom;
+
+const GOLDEN_GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// SplitMix64，只用于把种子展开成xoshiro256**的状态
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(GOLDEN_GAMMA);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// 可复现的伪随机数发生器。所有带种子的埋雷函数（如laymine_seeded）都使用它，
+/// 在Rust、Python、Javascript等各个平台上，相同的种子生成完全相同的局面。
+///
+/// 算法是完全确定的，便于用其他语言复现：
+/// - 发生器：xoshiro256**（<https://prng.di.unimi.it/>）。
+/// - 初始化：以`seed + 4 * stream * 0x9E3779B97F4A7C15`（模2^64）为SplitMix64的初始状态，
+///   依次取4个输出作为xoshiro256**的状态。`stream`用于从同一个种子派生出互不相同的序列，默认为0。
+/// - 整数：`gen_below(n)`产生[0, n)内均匀分布的整数。取64位输出x，若`x < (2^64 - n) % n`则丢弃重取，否则返回`x % n`。
+/// - 洗牌：Fisher-Yates洗牌，i从n-1递减到1，把第i个元素与第`gen_below(i + 1)`个元素交换。
+///
+/// 埋雷时，先构造前面是非雷（0）、后面是雷（-1）的一维序列，洗牌后再按列优先（laymine）
+/// 或行优先（laymine_op）的顺序铺到局面上。
+/// # Example
+/// ```
+/// use ms_toollib::SeededRng;
+/// let mut rng = SeededRng::new(0);
+/// let mut v = vec![0, 1, 2, 3, 4];
+/// rng.shuffle(&mut v);
+/// let mut rng_2 = SeededRng::new(0);
+/// let mut v_2 = vec![0, 1, 2, 3, 4];
+/// rng_2.shuffle(&mut v_2);
+/// assert_eq!(v, v_2);
+/// ```
+#[derive(Clone, Debug)]
+pub struct SeededRng {
+    s: [u64; 4],
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> SeededRng {
+        SeededRng::new_stream(seed, 0)
+    }
+    /// 从同一个种子派生出的第stream个序列。
+    pub fn new_stream(seed: u64, stream: u64) -> SeededRng {
+        let mut state = seed.wrapping_add(stream.wrapping_mul(4).wrapping_mul(GOLDEN_GAMMA));
+        let s = [
+            splitmix64(&mut state),
+            splitmix64(&mut state),
+            splitmix64(&mut state),
+            splitmix64(&mut state),
+        ];
+        SeededRng { s }
+    }
+    /// 用系统的随机源产生一个种子。
+    pub fn random_seed() -> u64 {
+        let mut buf = [0u8; 8];
+        getrandom(&mut buf).unwrap();
+        u64::from_le_bytes(buf)
+    }
+    pub fn next_u64(&mut self) -> u64 {
+        let result = self.s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = self.s[1] << 17;
+        self.s[2] ^= self.s[0];
+        self.s[3] ^= self.s[1];
+        self.s[1] ^= self.s[2];
+        self.s[0] ^= self.s[3];
+        self.s[2] ^= t;
+        self.s[3] = self.s[3].rotate_left(45);
+        result
+    }
+    /// 产生[0, limit)内均匀分布的整数。limit不能为0。
+    pub fn gen_below(&mut self, limit: usize) -> usize {
+        let limit = limit as u64;
+        let threshold = limit.wrapping_neg() % limit;
+        loop {
+            let x = self.next_u64();
+            if x >= threshold {
+                return (x % limit) as usize;
+            }
+        }
+    }
+    /// Fisher-Yates洗牌。
+    pub fn shuffle<T>(&mut self, v: &mut [T]) {
+        for i in (1..v.len()).rev() {
+            let j = self.gen_below(i + 1);
+            v.swap(i, j);
+        }
+    }
+}
@@ -0,0 +1,414 @@
+// 增量求解的会话，用于连续变化的局面（例如录像中逐个事件的局面）的概率计算
+use crate::algorithms::cal_probability_onboard_with_topology;
+use crate::probability_engine::{combination, divide_bigint};
+use crate::topology::Topology;
+use crate::utils::{cal_area, cal_table_minenum_recursion, combine, refresh_blocks};
+use crate::ENUM_LIMIT;
+use num_bigint::BigUint;
+use std::cmp::{max, min};
+use std::collections::{HashMap, HashSet};
+
+/// 区块的矩阵（系数矩阵、变量、常数向量），唯一地决定了区块的枚举结果
+type BlockKey = (Vec<Vec<i32>>, Vec<(usize, usize)>, Vec<i32>);
+
+/// 一个区块的枚举结果，只与该区块的矩阵有关
+#[derive(Clone, Debug)]
+struct BlockTable {
+    /// 方格的组合关系
+    comb_relp: Vec<Vec<usize>>,
+    /// 雷数分布表：区块中每种雷数下的情况数
+    table_minenum: [Vec<usize>; 2],
+    /// 每格雷数表：区块中每种雷数下，每格（或者地位等同的复合格中的一格）是雷的情况数
+    table_cell_minenum: Vec<Vec<usize>>,
+}
+
+impl BlockTable {
+    fn new(
+        matrix_a: &Vec<Vec<i32>>,
+        matrix_x: &Vec<(usize, usize)>,
+        matrix_b: &Vec<i32>,
+    ) -> Result<BlockTable, usize> {
+        let (matrix_a_squeeze, matrixx_squeeze, comb_relp) = combine(matrix_a, matrix_x);
+        let (table_minenum, table_cell_minenum) =
+            cal_table_minenum_recursion(&matrix_a_squeeze, &matrixx_squeeze, matrix_b, &comb_relp)?;
+        Ok(BlockTable {
+            comb_relp,
+            table_minenum,
+            table_cell_minenum,
+        })
+    }
+    /// 区块中雷数的情况数分布，下标为雷数
+    fn distribution(&self) -> Vec<BigUint> {
+        let mut dist = vec![BigUint::from(0u32); self.table_minenum[0].last().unwrap() + 1];
+        for (&mines, &count) in self.table_minenum[0]
+            .iter()
+            .zip(self.table_minenum[1].iter())
+        {
+            dist[mines] = BigUint::from(count);
+        }
+        dist
+    }
+}
+
+/// 两个雷数分布的卷积，只保留雷数不超过max_mines的部分
+fn convolve(a: &[BigUint], b: &[BigUint], max_mines: usize) -> Vec<BigUint> {
+    let len = min(a.len() + b.len() - 1, max_mines + 1);
+    let mut ans = vec![BigUint::from(0u32); len];
+    for (i, x) in a.iter().enumerate().take(len) {
+        for (j, y) in b.iter().enumerate().take(len - i) {
+            ans[i + j] += x * y;
+        }
+    }
+    ans
+}
+
+/// 局面上的一个区块：矩阵、每一行对应的数字的位置，以及枚举结果（还没有枚举时为None）
+#[derive(Clone, Debug)]
+struct Block {
+    key: BlockKey,
+    rows: Vec<(usize, usize)>,
+    table: Option<BlockTable>,
+}
+
+/// 增量求解会话。
+/// 维护一个游戏局面，接受打开格子、标雷等增量的修改，并记录修改过的格子。
+/// 重新计算概率时，只重新生成修改过的格子附近的区块的矩阵，并且只枚举这些区块中矩阵发生了变化的区块，
+/// 其余区块直接复用上一次的结果，最后再汇总所有区块的雷数分布，计算整个局面的概率。
+/// 适用于录像分析中，逐个事件计算局面概率的场景。
+/// - 注意：局面中的标雷（11）必须是正确的，否则结果没有意义。
+/// # Example
+/// ```
+/// use ms_toollib::SolverSession;
+/// let mut session = SolverSession::new(3, 3, 2.0);
+/// session.reveal((0, 0), 1);
+/// session.reveal((2, 2), 1);
+/// let poss = session.get_poss().unwrap();
+/// assert!((poss[0][1] - poss[1][0]).abs() < 1e-9);
+/// ```
+#[derive(Clone, Debug)]
+pub struct SolverSession {
+    game_board: Vec<Vec<i32>>,
+    mine_num: f64,
+    topology: Topology,
+    blocks: Vec<Block>,
+    // 上次更新区块以后修改过的格子
+    dirty: Vec<(usize, usize)>,
+    // 每个格子是否是内部的未知格（自己和周围都未打开）
+    inside: Vec<Vec<bool>>,
+    inside_cell: usize,
+    is_minenum: usize,
+    poss: Vec<Vec<f64>>,
+    mine_range: [usize; 3],
+    has_poss: bool, // 当前局面是否已经计算过概率
+    block_solve_count: usize,
+}
+
+impl SolverSession {
+    /// 以全部未打开的局面创建会话。
+    /// - 输入：行数、列数、总雷数（>=1时为总雷数，<1时为雷的比例）。
+    pub fn new(row: usize, column: usize, mine_num: f64) -> SolverSession {
+        SolverSession::new_with_topology(row, column, mine_num, Topology::Plane)
+    }
+    /// 以全部未打开的、指定拓扑结构的局面创建会话。输入同new。
+    pub fn new_with_topology(
+        row: usize,
+        column: usize,
+        mine_num: f64,
+        topology: Topology,
+    ) -> SolverSession {
+        SolverSession {
+            game_board: vec![vec![10; column]; row],
+            mine_num,
+            topology,
+            blocks: vec![],
+            dirty: vec![],
+            inside: vec![vec![true; column]; row],
+            inside_cell: row * column,
+            is_minenum: 0,
+            poss: vec![],
+            mine_range: [0; 3],
+            has_poss: false,
+            block_solve_count: 0,
+        }
+    }
+    /// 当前的游戏局面
+    pub fn get_game_board(&self) -> &Vec<Vec<i32>> {
+        &self.game_board
+    }
+    /// 打开一个格子，显示为数字number（0-8）。
+    pub fn reveal(&mut self, pos: (usize, usize), number: i32) {
+        self.set_cell(pos, number);
+    }
+    /// 标雷。
+    pub fn flag(&mut self, pos: (usize, usize)) {
+        self.set_cell(pos, 11);
+    }
+    /// 取消标雷。
+    pub fn unflag(&mut self, pos: (usize, usize)) {
+        if self.game_board[pos.0][pos.1] == 11 {
+            self.set_cell(pos, 10);
+        }
+    }
+    /// 把会话的局面设置为给定的游戏局面。只有发生变化的格子会作为增量修改。
+    pub fn set_game_board(&mut self, board: &Vec<Vec<i32>>) {
+        if board.len() != self.game_board.len() || board[0].len() != self.game_board[0].len() {
+            let block_solve_count = self.block_solve_count;
+            *self = SolverSession::new_with_topology(
+                board.len(),
+                board[0].len(),
+                self.mine_num,
+                self.topology,
+            );
+            self.block_solve_count = block_solve_count;
+        }
+        for (r, row) in board.iter().enumerate() {
+            for (c, &v) in row.iter().enumerate() {
+                self.set_cell((r, c), v);
+            }
+        }
+    }
+    fn set_cell(&mut self, pos: (usize, usize), value: i32) {
+        let old = self.game_board[pos.0][pos.1];
+        if old == value {
+            return;
+        }
+        self.game_board[pos.0][pos.1] = value;
+        if old == 11 {
+            self.is_minenum -= 1;
+        }
+        if value == 11 {
+            self.is_minenum += 1;
+        }
+        // 只有这个格子周围的格子是不是内部的未知格可能改变
+        let row = self.game_board.len();
+        let column = self.game_board[0].len();
+        for (m, n) in self.topology.around(row, column, pos.0, pos.1) {
+            let inside = self.game_board[m][n] == 10
+                && self
+                    .topology
+                    .around(row, column, m, n)
+                    .all(|(i, j)| self.game_board[i][j] >= 10);
+            if inside != self.inside[m][n] {
+                self.inside[m][n] = inside;
+                if inside {
+                    self.inside_cell += 1;
+                } else {
+                    self.inside_cell -= 1;
+                }
+            }
+        }
+        self.dirty.push(pos);
+        self.has_poss = false;
+    }
+    /// 计算局面中各位置是雷的概率，按照所在的位置返回。格式同cal_probability_onboard。
+    /// - 错误码：同cal_probability_enum。
+    pub fn get_poss(&mut self) -> Result<&Vec<Vec<f64>>, usize> {
+        if !self.has_poss {
+            self.solve()?;
+        }
+        Ok(&self.poss)
+    }
+    /// 局面中总未知雷数的范围（最小值、当前值、最大值），包含已经标出的雷。
+    pub fn get_mine_range(&mut self) -> Result<[usize; 3], usize> {
+        if !self.has_poss {
+            self.solve()?;
+        }
+        Ok(self.mine_range)
+    }
+    /// 累计实际进行过枚举的区块数，可用于评估缓存的效果。
+    pub fn get_block_solve_count(&self) -> usize {
+        self.block_solve_count
+    }
+    /// 重新生成修改过的格子附近的区块。某个区块的数字或未知格在修改过的格子周围时，
+    /// 它的矩阵可能改变，也可能与其他区块合并或者分裂，因此把这些区块中的数字，
+    /// 连同修改过的格子周围的数字重新分块；其余区块的矩阵不会改变，原样保留。
+    /// 重新分块后矩阵没有变化的区块，保留原来的枚举结果。
+    fn update_blocks(&mut self) {
+        if self.dirty.is_empty() {
+            return;
+        }
+        let row = self.game_board.len();
+        let column = self.game_board[0].len();
+        let topology = self.topology;
+        let near: HashSet<(usize, usize)> = self
+            .dirty
+            .drain(..)
+            .flat_map(|(r, c)| topology.around(row, column, r, c))
+            .collect();
+        let mut seeds: Vec<(usize, usize)> = near.iter().copied().collect();
+        let mut old_tables = HashMap::new();
+        let mut blocks = Vec::with_capacity(self.blocks.len());
+        for block in self.blocks.drain(..) {
+            if block
+                .rows
+                .iter()
+                .chain(block.key.1.iter())
+                .any(|cell| near.contains(cell))
+            {
+                seeds.extend(block.rows.iter().copied());
+                if let Some(table) = block.table {
+                    old_tables.insert(block.key, table);
+                }
+            } else {
+                blocks.push(block);
+            }
+        }
+        // 与refresh_matrixs_with_topology相同，按行扫描的顺序分块
+        seeds.sort_unstable();
+        seeds.dedup();
+        let board = &self.game_board;
+        seeds.retain(|&(i, j)| {
+            board[i][j] >= 0
+                && board[i][j] < 10
+                && topology
+                    .around(row, column, i, j)
+                    .any(|(m, n)| board[m][n] == 10)
+        });
+        let (matrix_as, matrix_xs, matrix_bs, matrix_rows) = refresh_blocks(board, topology, seeds);
+        for (((a, x), b), rows) in matrix_as
+            .into_iter()
+            .zip(matrix_xs)
+            .zip(matrix_bs)
+            .zip(matrix_rows)
+        {
+            let key = (a, x, b);
+            let table = old_tables.remove(&key);
+            blocks.push(Block { key, rows, table });
+        }
+        self.blocks = blocks;
+    }
+    /// 与概率引擎（cal_probability_csp）的计算方式相同：先统计每个格子是雷的精确的情况数，
+    /// 最后再除以总情况数，因此两者的结果完全一致。
+    /// - 注意：只重新生成、重新枚举受修改影响的区块，但每次都要重新汇总所有区块的雷数分布；
+    ///   区块过长时退回到cal_probability_onboard_with_topology。
+    fn solve(&mut self) -> Result<(), usize> {
+        self.update_blocks();
+        if self.blocks.iter().any(|b| b.key.1.len() > ENUM_LIMIT) {
+            // 区块过长，退回到不按区块缓存的概率计算引擎
+            (self.poss, self.mine_range) = cal_probability_onboard_with_topology(
+                &self.game_board,
+                self.mine_num,
+                self.topology,
+            )?;
+            self.has_poss = true;
+            return Ok(());
+        }
+        for block in self.blocks.iter_mut().filter(|b| b.table.is_none()) {
+            self.block_solve_count += 1;
+            block.table = Some(BlockTable::new(&block.key.0, &block.key.1, &block.key.2)?);
+        }
+        let block_tables: Vec<&BlockTable> = self
+            .blocks
+            .iter()
+            .map(|b| b.table.as_ref().unwrap())
+            .collect();
+        let inside_cell = self.inside_cell;
+        let is_minenum = self.is_minenum;
+
+        // 钳位总雷数，规则同cal_probability_csp
+        let row = self.game_board.len();
+        let column = self.game_board[0].len();
+        let raw_total = if self.mine_num > 0.0 && self.mine_num < 1.0 {
+            (self.mine_num * cal_area(&self.game_board) as f64).floor() as usize
+        } else {
+            self.mine_num as usize
+        };
+        let min_edge: usize = block_tables.iter().map(|t| t.table_minenum[0][0]).sum();
+        let max_edge: usize = block_tables
+            .iter()
+            .map(|t| *t.table_minenum[0].last().unwrap())
+            .sum();
+        let min_possible = is_minenum + min_edge;
+        let max_possible = is_minenum + max_edge + inside_cell;
+        let total_mines = min(max(raw_total, min_possible), max_possible);
+        let mines_left = total_mines - is_minenum;
+
+        let mut poss = vec![vec![-1.0; column]; row];
+        let p_unknow = if block_tables.is_empty() {
+            if inside_cell == 0 {
+                0.0
+            } else {
+                mines_left as f64 / inside_cell as f64
+            }
+        } else {
+            // 各区块以及内部未知区域的雷数分布
+            let mut dists: Vec<Vec<BigUint>> =
+                block_tables.iter().map(|t| t.distribution()).collect();
+            dists.push(
+                (0..=min(inside_cell, mines_left))
+                    .map(|m| combination(m, inside_cell))
+                    .collect(),
+            );
+            // 前缀卷积prefix[i]为前i个分布的卷积，后缀卷积suffix[i]为第i个及以后的分布的卷积
+            let n = dists.len();
+            let mut prefix = vec![vec![BigUint::from(1u32)]];
+            for d in &dists {
+                let next = convolve(prefix.last().unwrap(), d, mines_left);
+                prefix.push(next);
+            }
+            let mut suffix = vec![vec![BigUint::from(1u32)]; n + 1];
+            for i in (0..n).rev() {
+                suffix[i] = convolve(&dists[i], &suffix[i + 1], mines_left);
+            }
+            let total = prefix[n].get(mines_left).cloned().unwrap_or_default();
+            if total == BigUint::from(0u32) {
+                return Err(1);
+            }
+            // 除第i个分布以外，其他分布中共有mines_left - k个雷的情况数
+            let others = |i: usize, k: usize| -> BigUint {
+                if k > mines_left {
+                    return BigUint::from(0u32);
+                }
+                let t = mines_left - k;
+                let mut ans = BigUint::from(0u32);
+                for (a, x) in prefix[i].iter().enumerate().take(t + 1) {
+                    if let Some(y) = suffix[i + 1].get(t - a) {
+                        ans += x * y;
+                    }
+                }
+                ans
+            };
+            for (i, table) in block_tables.iter().enumerate() {
+                let others_s: Vec<BigUint> = table.table_minenum[0]
+                    .iter()
+                    .map(|&k| others(i, k))
+                    .collect();
+                for (cells_id, cells) in table.comb_relp.iter().enumerate() {
+                    let mut mine_count = BigUint::from(0u32);
+                    for (s, o) in others_s.iter().enumerate() {
+                        mine_count += o * BigUint::from(table.table_cell_minenum[s][cells_id]);
+                    }
+                    let pc = divide_bigint(&mine_count, &total, 8);
+                    for &id in cells {
+                        let (r, c) = self.blocks[i].key.1[id];
+                        poss[r][c] = pc;
+                    }
+                }
+            }
+            if inside_cell == 0 {
+                0.0
+            } else {
+                let mut outside = BigUint::from(0u32);
+                for (m, count) in dists[n - 1].iter().enumerate() {
+                    outside += others(n - 1, m) * count * BigUint::from(m);
+                }
+                divide_bigint(&outside, &(total * BigUint::from(inside_cell)), 8)
+            }
+        };
+        for (r, poss_row) in poss.iter_mut().enumerate() {
+            for (c, pc) in poss_row.iter_mut().enumerate() {
+                match self.game_board[r][c] {
+                    11 => *pc = 1.0,
+                    10 if *pc < -0.5 => *pc = p_unknow,
+                    12 => *pc = 0.0,
+                    _ if *pc < -0.5 => *pc = 0.0,
+                    _ => {}
+                }
+            }
+        }
+        self.poss = poss;
+        self.mine_range = [min_possible, total_mines, max_possible];
+        self.has_poss = true;
+        Ok(())
+    }
+}
@@ -0,0 +1,81 @@
+// 局面在旋转、翻转下的对称性，用于把相同的局面归为一类
+
+/// 局面的二面体群变换：（是否转置, 是否上下翻转, 是否左右翻转）。
+/// 前4个不改变局面的尺寸，适用于所有局面；后4个带转置，仅适用于方形局面。
+const TRANSFORMS: [(bool, bool, bool); 8] = [
+    (false, false, false),
+    (false, true, false),
+    (false, false, true),
+    (false, true, true),
+    (true, false, false),
+    (true, true, false),
+    (true, false, true),
+    (true, true, true),
+];
+
+fn transform_board(
+    board: &[Vec<i32>],
+    (transpose, flip_x, flip_y): (bool, bool, bool),
+) -> Vec<Vec<i32>> {
+    let row = board.len();
+    let column = board[0].len();
+    let (new_row, new_column) = if transpose {
+        (column, row)
+    } else {
+        (row, column)
+    };
+    let mut new_board = vec![vec![0; new_column]; new_row];
+    for (i, line) in new_board.iter_mut().enumerate() {
+        for (j, cell) in line.iter_mut().enumerate() {
+            let (x, y) = if transpose { (j, i) } else { (i, j) };
+            let x = if flip_x { row - 1 - x } else { x };
+            let y = if flip_y { column - 1 - y } else { y };
+            *cell = board[x][y];
+        }
+    }
+    new_board
+}
+
+/// 局面在旋转、翻转下的所有像，包括自身。方形局面有8个，其他局面有4个，可能有重复。
+pub fn board_symmetries(board: &Vec<Vec<i32>>) -> Vec<Vec<Vec<i32>>> {
+    let n = if board.len() == board[0].len() { 8 } else { 4 };
+    TRANSFORMS[..n]
+        .iter()
+        .map(|&t| transform_board(board, t))
+        .collect()
+}
+
+/// 局面在旋转、翻转下的标准形式，即所有像中按行优先排列时字典序最小的一个。
+/// 经过旋转、翻转能互相得到的局面，标准形式相同。对真实局面和游戏局面都适用。
+/// - 注意：非方形局面不考虑转置，因此16×30与30×16的局面不会被归为一类。
+/// # Example
+/// ```
+/// use ms_toollib::{canonical_board, cal_board_numbers};
+/// let mut a = vec![vec![-1, 0, 0], vec![0, 0, 0], vec![0, 0, 0]];
+/// let mut b = vec![vec![0, 0, 0], vec![0, 0, 0], vec![0, 0, -1]];
+/// cal_board_numbers(&mut a);
+/// cal_board_numbers(&mut b);
+/// assert_eq!(canonical_board(&a), canonical_board(&b));
+/// ```
+pub fn canonical_board(board: &Vec<Vec<i32>>) -> Vec<Vec<i32>> {
+    board_symmetries(board).into_iter().min().unwrap()
+}
+
+/// 局面的标准形式的64位哈希值（FNV-1a），用于大量局面的去重。
+/// 与标准库的哈希不同，在各个平台、各个版本上都相同。
+pub fn canonical_board_hash(board: &Vec<Vec<i32>>) -> u64 {
+    let canonical = canonical_board(board);
+    let mut hash = 0xcbf2_9ce4_8422_2325u64;
+    let mut feed = |v: u32| {
+        for b in v.to_le_bytes() {
+            hash ^= b as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    };
+    feed(canonical.len() as u32);
+    feed(canonical[0].len() as u32);
+    for &c in canonical.iter().flatten() {
+        feed(c as u32);
+    }
+    hash
+}
@@ -13,6 +13,8 @@ pub struct Tile {
     pub is_bomb: Cell<Option<bool>>,
     pub exploded: Cell<bool>,
     pub is_safe: Cell<bool>,
+    // 被遮挡的位置，不存在的格子
+    pub is_masked: Cell<bool>,
     pub is_next_move: Cell<bool>,
     pub is_start: Cell<bool>,
     pub on_edge: Cell<bool>,
@@ -20,6 +22,8 @@ pub struct Tile {
     pub probability: Cell<f64>,
     pub hint_text: Cell<String>,
     pub has_hint: Cell<bool>,
+    // 环面局面上的（宽, 高），平面局面上为None
+    pub torus: Option<(usize, usize)>,
 }
 
 impl Clone for Tile {
@@ -35,6 +39,7 @@ impl Clone for Tile {
             is_bomb: Cell::new(self.is_bomb.get()),
             exploded: Cell::new(self.exploded.get()),
             is_safe: Cell::new(self.is_safe.get()),
+            is_masked: Cell::new(self.is_masked.get()),
             is_next_move: Cell::new(self.is_next_move.get()),
             is_start: Cell::new(self.is_start.get()),
             on_edge: Cell::new(self.on_edge.get()),
@@ -42,6 +47,7 @@ impl Clone for Tile {
             probability: Cell::new(self.probability.get()),
             hint_text: Cell::new(self.hint_text.take()),
             has_hint: Cell::new(self.has_hint.get()),
+            torus: self.torus,
         }
     }
 }
@@ -59,6 +65,7 @@ impl Tile {
             is_bomb: Cell::new(None),
             exploded: Cell::new(false),
             is_safe: Cell::new(false),
+            is_masked: Cell::new(false),
             is_next_move: Cell::new(false),
             is_start: Cell::new(false),
             on_edge: Cell::new(false),
@@ -66,12 +73,22 @@ impl Tile {
             probability: Cell::new(-1.0),
             hint_text: Cell::new(String::new()),
             has_hint: Cell::new(false),
+            torus: None,
+        }
+    }
+
+    /// 与另一个格子在x、y方向上的距离，环面局面上跨过边界计算
+    pub fn distance(&self, other: &Tile) -> (usize, usize) {
+        let dx = self.x.abs_diff(other.x);
+        let dy = self.y.abs_diff(other.y);
+        match self.torus {
+            Some((width, height)) => (dx.min(width - dx), dy.min(height - dy)),
+            None => (dx, dy),
         }
     }
 
     pub fn is_adjacent(&self, other: &Tile) -> bool {
-        let dx = if self.x > other.x { self.x - other.x } else { other.x - self.x };
-        let dy = if self.y > other.y { self.y - other.y } else { other.y - self.y };
+        let (dx, dy) = self.distance(other);
         dx < 2 && dy < 2 && !(dx == 0 && dy == 0)
     }
 
@@ -0,0 +1,97 @@
+// 局面的拓扑结构，决定格子之间的相邻关系
+
+/// 局面的拓扑结构。默认是普通的平面局面。
+/// 做ffi传递时作如下约定：
+/// Plane => 0
+/// Torus => 1
+/// # Example
+/// ```
+/// use ms_toollib::Topology;
+/// // 环面上，左上角与右下角相邻
+/// assert!(Topology::Torus.is_around(8, 8, (0, 0), (7, 7)));
+/// assert!(!Topology::Plane.is_around(8, 8, (0, 0), (7, 7)));
+/// assert_eq!(Topology::Torus.around(8, 8, 0, 0).count(), 9);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Topology {
+    /// 普通的平面局面，边界上的格子周围不足8个格子
+    #[default]
+    Plane,
+    /// 环面局面，上下边界相连、左右边界相连，每个格子周围都有8个格子（局面很小时可能重复，只计一次）
+    Torus,
+}
+
+impl Topology {
+    /// 某一维上，坐标x周围（包括x）的坐标及其个数，从小到大排列、不重复
+    fn around_1d(&self, len: usize, x: usize) -> ([usize; 3], usize) {
+        match self {
+            Topology::Plane => {
+                let low = x.max(1) - 1;
+                ([low, low + 1, low + 2], len.min(x + 2) - low)
+            }
+            Topology::Torus => {
+                let mut v = [(x + len - 1) % len, x, (x + 1) % len];
+                v.sort_unstable();
+                if len >= 3 {
+                    (v, 3)
+                } else {
+                    // 局面的宽度不足3时，周围的坐标有重复
+                    let mut n = 1;
+                    for k in 1..3 {
+                        if v[k] != v[n - 1] {
+                            v[n] = v[k];
+                            n += 1;
+                        }
+                    }
+                    (v, n)
+                }
+            }
+        }
+    }
+    /// 某一维上，两个坐标之间的距离
+    fn distance_1d(&self, len: usize, a: usize, b: usize) -> usize {
+        let d = a.abs_diff(b);
+        match self {
+            Topology::Plane => d,
+            Topology::Torus => d.min(len - d),
+        }
+    }
+    /// (x, y)位置周围3×3范围内的所有位置，包括自身，按行、列从小到大排列，不重复。
+    /// 平面局面上的顺序与`for i in max(1, x) - 1..min(row, x + 2)`的二重循环相同。
+    pub fn around(
+        &self,
+        row: usize,
+        column: usize,
+        x: usize,
+        y: usize,
+    ) -> impl Iterator<Item = (usize, usize)> + Clone {
+        let (xs, x_num) = self.around_1d(row, x);
+        let (ys, y_num) = self.around_1d(column, y);
+        xs.into_iter()
+            .take(x_num)
+            .flat_map(move |i| ys.into_iter().take(y_num).map(move |j| (i, j)))
+    }
+    /// 两个位置是否在彼此周围3×3的范围内，相同的位置也返回true。
+    pub fn is_around(
+        &self,
+        row: usize,
+        column: usize,
+        a: (usize, usize),
+        b: (usize, usize),
+    ) -> bool {
+        self.distance_1d(row, a.0, b.0) <= 1 && self.distance_1d(column, a.1, b.1) <= 1
+    }
+    /// 两个位置在行、列方向上的距离
+    pub fn distance(
+        &self,
+        row: usize,
+        column: usize,
+        a: (usize, usize),
+        b: (usize, usize),
+    ) -> (usize, usize) {
+        (
+            self.distance_1d(row, a.0, b.0),
+            self.distance_1d(column, a.1, b.1),
+        )
+    }
+}
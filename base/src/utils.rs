@@ -1,8 +1,4 @@
 use itertools::Itertools;
-#[cfg(any(feature = "py", feature = "rs"))]
-use rand::seq::SliceRandom;
-#[cfg(any(feature = "py", feature = "rs"))]
-use rand::thread_rng;
 use std::cmp::{max, min};
 use std::collections::VecDeque;
 use std::vec;
@@ -13,48 +9,19 @@ use getrandom::getrandom;
 use crate::big_number::BigNumber;
 use crate::safe_board;
 use crate::safe_board::BoardSize;
+use crate::seeded_rng::SeededRng;
+use crate::topology::Topology;
 use crate::ENUM_LIMIT;
 
 // 整个模块是最底层的一些小工具，如埋雷、局面分块、计算3BV等
 
 /// 输入局面，计算空，即0的8连通域数
-pub fn cal_op<T>(board_raw: &T) -> usize
+pub fn cal_op<T>(board: &T) -> usize
 where
     T: std::ops::Index<usize> + safe_board::BoardSize,
     T::Output: std::ops::Index<usize, Output = i32>,
 {
-    let row = board_raw.get_row();
-    let column = board_raw.get_column();
-    let mut board = vec![vec![0; column]; row];
-    for i in 0..row {
-        for j in 0..column {
-            board[i][j] = board_raw[i][j];
-        }
-    }
-    let mut op = 0;
-    for i in 0..row {
-        for j in 0..column {
-            if board[i][j] == 0 {
-                infect_board(&mut board, i, j);
-                op += 1;
-            }
-        }
-    }
-    op
-}
-
-// Board(x, y)位置的整个空都用数字1填满，仅计算Op用
-fn infect_board(board: &mut Vec<Vec<i32>>, x: usize, y: usize) {
-    let row = board.len();
-    let column = board[0].len();
-    board[x][y] = 1;
-    for i in max(1, x) - 1..min(row, x + 2) {
-        for j in max(1, y) - 1..min(column, y + 2) {
-            if board[i][j] == 0 {
-                infect_board(board, i, j);
-            }
-        }
-    }
+    cal_op_with_topology(board, Topology::Plane)
 }
 
 /// 输入局面，计算岛  
@@ -63,29 +30,7 @@ where
     T: std::ops::Index<usize> + safe_board::BoardSize,
     T::Output: std::ops::Index<usize, Output = i32>,
 {
-    let row = raw_board.get_row();
-    let column = raw_board.get_column();
-    let mut board = vec![vec![1; column]; row];
-    for i in 0..row {
-        for j in 0..column {
-            if raw_board[i][j] <= 0 {
-                continue;
-            }
-            let mut flag = true;
-            'outer: for m in max(1, i) - 1..min(row, i + 2) {
-                for n in max(1, j) - 1..min(column, j + 2) {
-                    if raw_board[m][n] == 0 {
-                        flag = false;
-                        break 'outer;
-                    }
-                }
-            }
-            if flag {
-                board[i][j] = 0;
-            }
-        }
-    }
-    cal_op(&board)
+    cal_isl_with_topology(raw_board, Topology::Plane)
 }
 
 /// 计算每个数字出现的次数  
@@ -177,6 +122,37 @@ pub fn refresh_matrixs(
     Vec<Vec<i32>>,
     usize,
     usize,
+) {
+    refresh_matrixs_with_topology(board_of_game, Topology::Plane)
+}
+
+/// 在指定的拓扑结构上根据游戏局面生成矩阵，分段。返回值同refresh_matrixs。
+pub fn refresh_matrixs_with_topology(
+    board_of_game: &Vec<Vec<i32>>,
+    topology: Topology,
+) -> (
+    Vec<Vec<Vec<i32>>>,
+    Vec<Vec<(usize, usize)>>,
+    Vec<Vec<i32>>,
+    usize,
+    usize,
+) {
+    let (matrix_as, matrix_xs, matrix_bs, _, inside_cell, is_minenum) =
+        refresh_matrixs_with_rows(board_of_game, topology);
+    (matrix_as, matrix_xs, matrix_bs, inside_cell, is_minenum)
+}
+
+// 同refresh_matrixs_with_topology，另外返回每一行对应的数字的位置，与常数向量的形状相同
+pub(crate) fn refresh_matrixs_with_rows(
+    board_of_game: &Vec<Vec<i32>>,
+    topology: Topology,
+) -> (
+    Vec<Vec<Vec<i32>>>,
+    Vec<Vec<(usize, usize)>>,
+    Vec<Vec<i32>>,
+    Vec<Vec<(usize, usize)>>,
+    usize,
+    usize,
 ) {
     // 根据游戏局面分块生成矩阵。分段的数据结构是最外面再套一层Vec
     // board_of_game必须且肯定是正确标雷的游戏局面，但不需要标全，不能标非雷
@@ -186,32 +162,22 @@ pub fn refresh_matrixs(
     let column = board_of_game[0].len();
     let mut inside_cell = 0;
     let mut is_minenum = 0;
-    let mut matrix_as = vec![];
-    let mut matrix_xs = vec![];
-    let mut matrix_bs = vec![];
     let mut all_cell: Vec<(usize, usize)> = vec![]; // 记录所有周围有未打开格子的数字的位置
     for i in 0..row {
         for j in 0..column {
             if board_of_game[i][j] >= 0 && board_of_game[i][j] < 10 {
-                'outer: for m in max(1, i) - 1..min(row, i + 2) {
-                    for n in max(1, j) - 1..min(column, j + 2) {
-                        if board_of_game[m][n] == 10 {
-                            all_cell.push((i, j));
-                            break 'outer;
-                        }
-                    }
+                if topology
+                    .around(row, column, i, j)
+                    .any(|(m, n)| board_of_game[m][n] == 10)
+                {
+                    all_cell.push((i, j));
                 }
             } else if board_of_game[i][j] == 10 {
                 // 数内部有几个格子
-                let mut flag = true;
-                for m in max(1, i) - 1..min(row, i + 2) {
-                    for n in max(1, j) - 1..min(column, j + 2) {
-                        if board_of_game[m][n] < 10 {
-                            flag = false;
-                        }
-                    }
-                }
-                if flag {
+                if topology
+                    .around(row, column, i, j)
+                    .all(|(m, n)| board_of_game[m][n] >= 10)
+                {
                     inside_cell += 1;
                 }
             } else if board_of_game[i][j] == 11 {
@@ -219,6 +185,29 @@ pub fn refresh_matrixs(
             }
         }
     }
+    let (matrix_as, matrix_xs, matrix_bs, matrix_rows) =
+        refresh_blocks(board_of_game, topology, all_cell);
+    (matrix_as, matrix_xs, matrix_bs, matrix_rows, inside_cell, is_minenum)
+}
+
+// 把给定的、周围有未打开格子的数字分成互不相关的区块，生成每块的矩阵。
+// 返回：系数矩阵、变量矩阵、常数向量、每一行对应的数字的位置
+pub(crate) fn refresh_blocks(
+    board_of_game: &Vec<Vec<i32>>,
+    topology: Topology,
+    mut all_cell: Vec<(usize, usize)>,
+) -> (
+    Vec<Vec<Vec<i32>>>,
+    Vec<Vec<(usize, usize)>>,
+    Vec<Vec<i32>>,
+    Vec<Vec<(usize, usize)>>,
+) {
+    let row = board_of_game.len();
+    let column = board_of_game[0].len();
+    let mut matrix_as = vec![];
+    let mut matrix_xs = vec![];
+    let mut matrix_bs = vec![];
+    let mut matrix_rows = vec![];
     let mut p = 0; //指针，代表第几块
     while !all_cell.is_empty() {
         matrix_xs.push(vec![]);
@@ -228,14 +217,12 @@ pub fn refresh_matrixs(
         let mut num_cells = vec![]; // 记录了当前段的数字格的坐标
         let mut temp_cells: VecDeque<(usize, usize)> = VecDeque::new(); // 记录了待查找的数字格的坐标
         let mut flag_num = 0;
-        for m in max(1, x_0) - 1..min(row, x_0 + 2) {
-            for n in max(1, y_0) - 1..min(column, y_0 + 2) {
-                if board_of_game[m][n] == 10 {
-                    matrix_xs[p].push((m, n));
-                }
-                if board_of_game[m][n] == 11 {
-                    flag_num += 1;
-                }
+        for (m, n) in topology.around(row, column, x_0, y_0) {
+            if board_of_game[m][n] == 10 {
+                matrix_xs[p].push((m, n));
+            }
+            if board_of_game[m][n] == 11 {
+                flag_num += 1;
             }
         }
         matrix_bs[p].push(board_of_game[x_0][y_0] - flag_num);
@@ -243,32 +230,23 @@ pub fn refresh_matrixs(
         temp_cells.push_back((x_0, y_0));
         while let Some((x_e, y_e)) = temp_cells.pop_front() {
             for t in (1..all_cell.len()).rev() {
-                let x_t = all_cell[t].0;
-                let y_t = all_cell[t].1;
-                if x_t >= x_e + 3 || x_e >= x_t + 3 || y_t >= y_e + 3 || y_e >= y_t + 3 {
+                let (x_t, y_t) = all_cell[t];
+                let (dx, dy) = topology.distance(row, column, (x_t, y_t), (x_e, y_e));
+                if dx >= 3 || dy >= 3 {
                     continue;
                 }
-                let mut flag_be_neighbor = false;
-                for m in max(1, max(x_t, x_e)) - 1..min(row, min(x_t + 2, x_e + 2)) {
-                    for n in max(1, max(y_t, y_e)) - 1..min(column, min(y_t + 2, y_e + 2)) {
-                        if board_of_game[m][n] == 10 {
-                            flag_be_neighbor = true;
-                            break;
-                        }
-                    }
-                }
+                // 两个数字周围有共同的未打开的格子
+                let flag_be_neighbor = topology.around(row, column, x_t, y_t).any(|(m, n)| {
+                    board_of_game[m][n] == 10 && topology.is_around(row, column, (x_e, y_e), (m, n))
+                });
                 if flag_be_neighbor {
                     let mut flag_num = 0;
-                    for m in max(1, x_t) - 1..min(row, x_t + 2) {
-                        for n in max(1, y_t) - 1..min(column, y_t + 2) {
-                            if board_of_game[m][n] == 10 {
-                                if !matrix_xs[p].contains(&(m, n)) {
-                                    matrix_xs[p].push((m, n));
-                                }
-                            }
-                            if board_of_game[m][n] == 11 {
-                                flag_num += 1;
-                            }
+                    for (m, n) in topology.around(row, column, x_t, y_t) {
+                        if board_of_game[m][n] == 10 && !matrix_xs[p].contains(&(m, n)) {
+                            matrix_xs[p].push((m, n));
+                        }
+                        if board_of_game[m][n] == 11 {
+                            flag_num += 1;
                         }
                     }
                     matrix_bs[p].push(board_of_game[x_t][y_t] - flag_num);
@@ -281,19 +259,16 @@ pub fn refresh_matrixs(
         matrix_as.push(vec![vec![0; matrix_xs[p].len()]; matrix_bs[p].len()]);
         for i in 0..num_cells.len() {
             for j in 0..matrix_xs[p].len() {
-                if num_cells[i].0 <= matrix_xs[p][j].0 + 1
-                    && matrix_xs[p][j].0 <= num_cells[i].0 + 1
-                    && num_cells[i].1 <= matrix_xs[p][j].1 + 1
-                    && matrix_xs[p][j].1 <= num_cells[i].1 + 1
-                {
+                if topology.is_around(row, column, num_cells[i], matrix_xs[p][j]) {
                     matrix_as[p][i][j] = 1;
                 }
             }
         }
         all_cell.swap_remove(0);
+        matrix_rows.push(num_cells);
         p += 1;
     }
-    (matrix_as, matrix_xs, matrix_bs, inside_cell, is_minenum)
+    (matrix_as, matrix_xs, matrix_bs, matrix_rows)
 }
 
 /// 根据游戏局面生成矩阵，分段、且分块。输入的必须保证是合法的游戏局面。  
@@ -431,41 +406,13 @@ pub fn get_random_int(limit: usize) -> usize {
     t % limit
 }
 
-#[cfg(feature = "js")]
-pub trait JsShuffle {
-    fn shuffle_(&mut self);
-}
-
-#[cfg(feature = "js")]
-impl JsShuffle for Vec<i32> {
-    fn shuffle_(&mut self) {
-        // 存疑！！！！！
-        let l = self.len();
-        for i in 1..l {
-            let id = get_random_int(i + 1);
-            let t = self[i];
-            self[i] = self[id];
-            self[id] = t;
-        }
-    }
-}
-
 /// 一维埋雷，给局部埋雷，完全随机。
-/// - 需要埋雷的区域的面积，雷数。
+/// - 需要埋雷的区域的面积，雷数，随机数发生器。
 /// - 例如，高级标准埋雷时，area = 16*30-1
-pub fn get_board_1d(area: usize, minenum: usize) -> Vec<i32> {
-    let mut board_1d: Vec<i32> = vec![];
-    board_1d.reserve(area);
-    board_1d = vec![0; area - minenum];
+pub(crate) fn get_board_1d_rng(area: usize, minenum: usize, rng: &mut SeededRng) -> Vec<i32> {
+    let mut board_1d: Vec<i32> = vec![0; area - minenum];
     board_1d.append(&mut vec![-1; minenum]);
-    #[cfg(any(feature = "py", feature = "rs"))]
-    let mut rng = thread_rng();
-
-    #[cfg(any(feature = "py", feature = "rs"))]
-    board_1d.shuffle(&mut rng);
-
-    #[cfg(feature = "js")]
-    board_1d.shuffle_();
+    rng.shuffle(&mut board_1d);
     board_1d
 }
 
@@ -504,7 +451,31 @@ pub fn trans_board_1d_2d_op(
 /// - 标准埋雷规则：起手位置非雷，其余位置的雷服从均匀分布。
 /// - 输出：二维的局面，其中0代表空，1~8代表1~8，-1代表雷。
 pub fn laymine(row: usize, column: usize, minenum: usize, x0: usize, y0: usize) -> Vec<Vec<i32>> {
-    let board1_dim = get_board_1d(row * column - 1, minenum);
+    laymine_rng(row, column, minenum, x0, y0, &mut SeededRng::new(SeededRng::random_seed()))
+}
+
+/// 带种子的标准埋雷。规则同laymine，相同的种子在各个平台上生成相同的局面。
+/// - 随机数发生器及埋雷的过程见[SeededRng]。
+pub fn laymine_seeded(
+    row: usize,
+    column: usize,
+    minenum: usize,
+    x0: usize,
+    y0: usize,
+    seed: u64,
+) -> Vec<Vec<i32>> {
+    laymine_rng(row, column, minenum, x0, y0, &mut SeededRng::new(seed))
+}
+
+pub(crate) fn laymine_rng(
+    row: usize,
+    column: usize,
+    minenum: usize,
+    x0: usize,
+    y0: usize,
+    rng: &mut SeededRng,
+) -> Vec<Vec<i32>> {
+    let board1_dim = get_board_1d_rng(row * column - 1, minenum, rng);
 
     let mut board1_dim_2: Vec<i32> = vec![];
     board1_dim_2.reserve(row * column);
@@ -534,6 +505,170 @@ pub fn laymine(row: usize, column: usize, minenum: usize, x0: usize, y0: usize)
     board
 }
 
+/// 带遮挡的标准埋雷引擎，用于非矩形的局面（例如环形、十字形或中间有洞的局面）。
+/// - 输入：遮挡（true代表该位置没有格子），雷数，起手位置。局面的尺寸与遮挡相同。
+/// - 标准埋雷规则：起手位置非雷，其余没有被遮挡的位置的雷服从均匀分布。
+/// - 输出：二维的局面，其中0代表空，1~8代表1~8，-1代表雷，-2代表被遮挡的位置。
+pub fn laymine_masked(mask: &Vec<Vec<bool>>, minenum: usize, x0: usize, y0: usize) -> Vec<Vec<i32>> {
+    laymine_masked_rng(mask, minenum, x0, y0, &mut SeededRng::new(SeededRng::random_seed()))
+}
+
+/// 带种子的、带遮挡的标准埋雷。规则同laymine_masked，相同的种子在各个平台上生成相同的局面。
+pub fn laymine_masked_seeded(
+    mask: &Vec<Vec<bool>>,
+    minenum: usize,
+    x0: usize,
+    y0: usize,
+    seed: u64,
+) -> Vec<Vec<i32>> {
+    laymine_masked_rng(mask, minenum, x0, y0, &mut SeededRng::new(seed))
+}
+
+pub(crate) fn laymine_masked_rng(
+    mask: &Vec<Vec<bool>>,
+    minenum: usize,
+    x0: usize,
+    y0: usize,
+    rng: &mut SeededRng,
+) -> Vec<Vec<i32>> {
+    let row = mask.len();
+    let column = mask[0].len();
+    let mut board: Vec<Vec<i32>> = vec![vec![0; column]; row];
+    // 可以埋雷的位置，与laymine相同，按列的顺序排列
+    let mut cells = vec![];
+    for y in 0..column {
+        for x in 0..row {
+            if mask[x][y] {
+                board[x][y] = -2;
+            } else if x != x0 || y != y0 {
+                cells.push((x, y));
+            }
+        }
+    }
+    let board1_dim = get_board_1d_rng(cells.len(), minenum, rng);
+    for (&(x, y), &c) in cells.iter().zip(board1_dim.iter()) {
+        if c < 0 {
+            board[x][y] = -1;
+        }
+    }
+    cal_board_numbers(&mut board);
+    board
+}
+
+/// 根据局面生成开局时的游戏局面：没有被遮挡的位置都是10，被遮挡的位置是19。
+pub fn get_covered_game_board<T>(board: &T) -> Vec<Vec<i32>>
+where
+    T: std::ops::Index<usize> + safe_board::BoardSize,
+    T::Output: std::ops::Index<usize, Output = i32>,
+{
+    let row = board.get_row();
+    let column = board.get_column();
+    let mut game_board = vec![vec![10; column]; row];
+    for i in 0..row {
+        for j in 0..column {
+            if board[i][j] == -2 {
+                game_board[i][j] = 19;
+            }
+        }
+    }
+    game_board
+}
+
+/// 计算局面中存在的格子数，即不计被遮挡的格子。局面或游戏局面均可。
+pub fn cal_area(board: &[Vec<i32>]) -> usize {
+    board
+        .iter()
+        .map(|row| row.iter().filter(|&&x| x != -2 && x != 19).count())
+        .sum()
+}
+
+/// 在指定的拓扑结构上埋雷。
+/// - 规则：op_start为false时，同laymine，起手位置非雷；op_start为true时，同laymine_op，起手位置开空。
+///   在环面上，起手位置周围的3×3范围会跨过边界。
+/// - 输出：二维的局面，数字按照拓扑结构计算，其中0代表空，1~8代表1~8，-1代表雷。
+pub fn laymine_with_topology(
+    row: usize,
+    column: usize,
+    minenum: usize,
+    x0: usize,
+    y0: usize,
+    op_start: bool,
+    topology: Topology,
+) -> Vec<Vec<i32>> {
+    laymine_with_topology_rng(
+        row,
+        column,
+        minenum,
+        x0,
+        y0,
+        op_start,
+        topology,
+        &mut SeededRng::new(SeededRng::random_seed()),
+    )
+}
+
+/// 带种子的、在指定的拓扑结构上埋雷。规则同laymine_with_topology。
+/// - 在平面上，结果与相同种子的laymine_seeded（op_start为false）或laymine_op_seeded（op_start为true）相同。
+pub fn laymine_with_topology_seeded(
+    row: usize,
+    column: usize,
+    minenum: usize,
+    x0: usize,
+    y0: usize,
+    op_start: bool,
+    topology: Topology,
+    seed: u64,
+) -> Vec<Vec<i32>> {
+    laymine_with_topology_rng(
+        row,
+        column,
+        minenum,
+        x0,
+        y0,
+        op_start,
+        topology,
+        &mut SeededRng::new(seed),
+    )
+}
+
+fn laymine_with_topology_rng(
+    row: usize,
+    column: usize,
+    minenum: usize,
+    x0: usize,
+    y0: usize,
+    op_start: bool,
+    topology: Topology,
+    rng: &mut SeededRng,
+) -> Vec<Vec<i32>> {
+    let safe_area = if op_start {
+        topology.around(row, column, x0, y0).collect()
+    } else {
+        vec![(x0, y0)]
+    };
+    // 可以埋雷的位置。与laymine、laymine_op一致，分别按列、按行的顺序排列
+    let cells: Vec<(usize, usize)> = if op_start {
+        (0..row)
+            .flat_map(|x| (0..column).map(move |y| (x, y)))
+            .filter(|c| !safe_area.contains(c))
+            .collect()
+    } else {
+        (0..column)
+            .flat_map(|y| (0..row).map(move |x| (x, y)))
+            .filter(|c| !safe_area.contains(c))
+            .collect()
+    };
+    let board_1d = get_board_1d_rng(cells.len(), minenum, rng);
+    let mut board = vec![vec![0; column]; row];
+    for (&(x, y), &c) in cells.iter().zip(board_1d.iter()) {
+        if c < 0 {
+            board[x][y] = -1;
+        }
+    }
+    cal_board_numbers_with_topology(&mut board, topology);
+    board
+}
+
 /// 通用win7规则埋雷引擎。
 /// - win7规则：起手位置开空，其余位置的雷服从均匀分布。
 /// - 输出：二维的局面，其中0代表空，1~8代表1~8，-1代表雷。
@@ -543,6 +678,30 @@ pub fn laymine_op(
     minenum: usize,
     x0: usize,
     y0: usize,
+) -> Vec<Vec<i32>> {
+    laymine_op_rng(row, column, minenum, x0, y0, &mut SeededRng::new(SeededRng::random_seed()))
+}
+
+/// 带种子的win7规则埋雷。规则同laymine_op，相同的种子在各个平台上生成相同的局面。
+/// - 随机数发生器及埋雷的过程见[SeededRng]。
+pub fn laymine_op_seeded(
+    row: usize,
+    column: usize,
+    minenum: usize,
+    x0: usize,
+    y0: usize,
+    seed: u64,
+) -> Vec<Vec<i32>> {
+    laymine_op_rng(row, column, minenum, x0, y0, &mut SeededRng::new(seed))
+}
+
+pub(crate) fn laymine_op_rng(
+    row: usize,
+    column: usize,
+    minenum: usize,
+    x0: usize,
+    y0: usize,
+    rng: &mut SeededRng,
 ) -> Vec<Vec<i32>> {
     let mut area_op = 9;
     if x0 == 0 || y0 == 0 || x0 == row - 1 || y0 == column - 1 {
@@ -557,37 +716,176 @@ pub fn laymine_op(
         }
     }
     let area = row * column - area_op;
-    let board_1d = get_board_1d(area, minenum);
+    let board_1d = get_board_1d_rng(area, minenum, rng);
     trans_board_1d_2d_op(&board_1d, row, column, x0, y0)
 }
 
-pub fn cal_bbbv_on_island<T>(board: &T) -> usize
+/// 在指定的拓扑结构上计算除空以外的3BV，即岛上的数字的个数
+pub fn cal_bbbv_on_island_with_topology<T>(board: &T, topology: Topology) -> usize
 where
     T: std::ops::Index<usize> + safe_board::BoardSize,
     T::Output: std::ops::Index<usize, Output = i32>,
 {
-    // 计算除空以外的3BV
     let row = board.get_row();
     let column = board.get_column();
     let mut num_bbbv_on_island = 0;
     for i in 0..row {
         for j in 0..column {
-            if board[i][j] > 0 {
-                let mut flag: bool = true;
-                for x in max(1, i) - 1..min(row, i + 2) {
-                    for y in max(1, j) - 1..min(column, j + 2) {
-                        if board[x][y] == 0 {
-                            flag = false;
-                        }
+            if board[i][j] > 0
+                && topology
+                    .around(row, column, i, j)
+                    .all(|(m, n)| board[m][n] != 0)
+            {
+                num_bbbv_on_island += 1;
+            }
+        }
+    }
+    num_bbbv_on_island
+}
+
+/// 局面上的每一片空，即0的8连通域，按左上角的0行优先排列，规则同cal_op。
+/// - 返回：每一片空的格子，包括其中的0和边缘上的数字，即点开这片空时打开的所有格子。空的大小即格子数。
+/// - 注意：与两片空相邻的数字，在两片空中都会出现。
+pub fn cal_openings<T>(board: &T) -> Vec<Vec<(usize, usize)>>
+where
+    T: std::ops::Index<usize> + safe_board::BoardSize,
+    T::Output: std::ops::Index<usize, Output = i32>,
+{
+    cal_openings_with_topology(board, Topology::Plane)
+}
+
+/// 在指定的拓扑结构上计算局面上的每一片空，规则同cal_openings。
+pub fn cal_openings_with_topology<T>(board: &T, topology: Topology) -> Vec<Vec<(usize, usize)>>
+where
+    T: std::ops::Index<usize> + safe_board::BoardSize,
+    T::Output: std::ops::Index<usize, Output = i32>,
+{
+    let row = board.get_row();
+    let column = board.get_column();
+    // 格子最后一次被加入的空的编号+1，用于去重
+    let mut stamp = vec![vec![0; column]; row];
+    let mut openings = vec![];
+    for i in 0..row {
+        for j in 0..column {
+            if board[i][j] != 0 || stamp[i][j] != 0 {
+                continue;
+            }
+            let id = openings.len() + 1;
+            stamp[i][j] = id;
+            let mut cells = vec![(i, j)];
+            let mut pointer = 0;
+            while pointer < cells.len() {
+                let (x, y) = cells[pointer];
+                pointer += 1;
+                if board[x][y] != 0 {
+                    continue;
+                }
+                for (m, n) in topology.around(row, column, x, y) {
+                    if stamp[m][n] != id && board[m][n] >= 0 {
+                        stamp[m][n] = id;
+                        cells.push((m, n));
                     }
                 }
-                if flag {
-                    num_bbbv_on_island += 1;
+            }
+            cells.sort_unstable();
+            openings.push(cells);
+        }
+    }
+    openings
+}
+
+/// 局面上的每一个岛，即不与空相邻的数字的8连通域，按左上角的格子行优先排列，规则同cal_isl。
+/// - 返回：每一个岛的格子。岛上的每个数字都计1个3BV，因此岛的3BV即格子数。
+pub fn cal_islands<T>(board: &T) -> Vec<Vec<(usize, usize)>>
+where
+    T: std::ops::Index<usize> + safe_board::BoardSize,
+    T::Output: std::ops::Index<usize, Output = i32>,
+{
+    cal_islands_with_topology(board, Topology::Plane)
+}
+
+/// 在指定的拓扑结构上计算局面上的每一个岛，规则同cal_islands。
+pub fn cal_islands_with_topology<T>(board: &T, topology: Topology) -> Vec<Vec<(usize, usize)>>
+where
+    T: std::ops::Index<usize> + safe_board::BoardSize,
+    T::Output: std::ops::Index<usize, Output = i32>,
+{
+    let row = board.get_row();
+    let column = board.get_column();
+    let labels = cal_bbbv_labels_with_topology(board, topology);
+    let mut visited = vec![vec![false; column]; row];
+    let mut islands = vec![];
+    for i in 0..row {
+        for j in 0..column {
+            if labels[i][j] != 2 || visited[i][j] {
+                continue;
+            }
+            visited[i][j] = true;
+            let mut cells = vec![(i, j)];
+            let mut pointer = 0;
+            while pointer < cells.len() {
+                let (x, y) = cells[pointer];
+                pointer += 1;
+                for (m, n) in topology.around(row, column, x, y) {
+                    if labels[m][n] == 2 && !visited[m][n] {
+                        visited[m][n] = true;
+                        cells.push((m, n));
+                    }
                 }
             }
+            cells.sort_unstable();
+            islands.push(cells);
         }
     }
-    num_bbbv_on_island
+    islands
+}
+
+/// 局面上每个格子在3BV中的分类，规则同cal_bbbv。
+/// - 返回：与局面同尺寸的矩阵。-1为雷（以及其他负数，如被遮挡的格子），0为空中的0，
+///   1为空边缘上的数字（随空一起打开，不单独计3BV），2为岛上的数字（每个计1个3BV）。
+/// # Example
+/// ```
+/// use ms_toollib::cal_bbbv_labels;
+/// let board = vec![vec![0, 1, -1], vec![0, 2, 2], vec![0, 1, -1]];
+/// assert_eq!(
+///     cal_bbbv_labels(&board),
+///     vec![vec![0, 1, -1], vec![0, 1, 2], vec![0, 1, -1]]
+/// );
+/// ```
+pub fn cal_bbbv_labels<T>(board: &T) -> Vec<Vec<i32>>
+where
+    T: std::ops::Index<usize> + safe_board::BoardSize,
+    T::Output: std::ops::Index<usize, Output = i32>,
+{
+    cal_bbbv_labels_with_topology(board, Topology::Plane)
+}
+
+/// 在指定的拓扑结构上计算局面上每个格子在3BV中的分类，规则同cal_bbbv_labels。
+pub fn cal_bbbv_labels_with_topology<T>(board: &T, topology: Topology) -> Vec<Vec<i32>>
+where
+    T: std::ops::Index<usize> + safe_board::BoardSize,
+    T::Output: std::ops::Index<usize, Output = i32>,
+{
+    let row = board.get_row();
+    let column = board.get_column();
+    let mut labels = vec![vec![0; column]; row];
+    for i in 0..row {
+        for j in 0..column {
+            labels[i][j] = if board[i][j] < 0 {
+                -1
+            } else if board[i][j] == 0 {
+                0
+            } else if topology
+                .around(row, column, i, j)
+                .any(|(x, y)| board[x][y] == 0)
+            {
+                1
+            } else {
+                2
+            };
+        }
+    }
+    labels
 }
 
 /// 计算局面的3BV
@@ -596,15 +894,78 @@ where
     T: std::ops::Index<usize> + safe_board::BoardSize,
     T::Output: std::ops::Index<usize, Output = i32>,
 {
-    cal_bbbv_on_island(board) + cal_op(board)
+    cal_bbbv_with_topology(board, Topology::Plane)
 }
 
-/// 依据左击位置刷新局面。如踩雷，标上或14、15标记
-/// - 注意：兼容12标记符
-pub fn refresh_board<T>(
+/// 在指定的拓扑结构上计算局面的Op，即空的8连通域数
+pub fn cal_op_with_topology<T>(board: &T, topology: Topology) -> usize
+where
+    T: std::ops::Index<usize> + safe_board::BoardSize,
+    T::Output: std::ops::Index<usize, Output = i32>,
+{
+    let row = board.get_row();
+    let column = board.get_column();
+    let mut visited = vec![vec![false; column]; row];
+    let mut op = 0;
+    for i in 0..row {
+        for j in 0..column {
+            if board[i][j] != 0 || visited[i][j] {
+                continue;
+            }
+            op += 1;
+            visited[i][j] = true;
+            let mut buffer = vec![(i, j)];
+            while let Some((x, y)) = buffer.pop() {
+                for (m, n) in topology.around(row, column, x, y) {
+                    if board[m][n] == 0 && !visited[m][n] {
+                        visited[m][n] = true;
+                        buffer.push((m, n));
+                    }
+                }
+            }
+        }
+    }
+    op
+}
+
+/// 在指定的拓扑结构上计算局面的岛，即不与空相邻的数字的8连通域数
+pub fn cal_isl_with_topology<T>(raw_board: &T, topology: Topology) -> usize
+where
+    T: std::ops::Index<usize> + safe_board::BoardSize,
+    T::Output: std::ops::Index<usize, Output = i32>,
+{
+    let row = raw_board.get_row();
+    let column = raw_board.get_column();
+    let mut board = vec![vec![1; column]; row];
+    for i in 0..row {
+        for j in 0..column {
+            if raw_board[i][j] > 0
+                && topology
+                    .around(row, column, i, j)
+                    .all(|(m, n)| raw_board[m][n] != 0)
+            {
+                board[i][j] = 0;
+            }
+        }
+    }
+    cal_op_with_topology(&board, topology)
+}
+
+/// 在指定的拓扑结构上计算局面的3BV
+pub fn cal_bbbv_with_topology<T>(board: &T, topology: Topology) -> usize
+where
+    T: std::ops::Index<usize> + safe_board::BoardSize,
+    T::Output: std::ops::Index<usize, Output = i32>,
+{
+    cal_bbbv_on_island_with_topology(board, topology) + cal_op_with_topology(board, topology)
+}
+
+/// 在指定的拓扑结构上依据左击位置刷新局面，规则同refresh_board
+pub fn refresh_board_with_topology<T>(
     board: &T,
     board_of_game: &mut Vec<Vec<i32>>,
     mut clicked_poses: Vec<(usize, usize)>,
+    topology: Topology,
 ) where
     T: std::ops::Index<usize> + safe_board::BoardSize,
     T::Output: std::ops::Index<usize, Output = i32>,
@@ -613,19 +974,17 @@ pub fn refresh_board<T>(
     let column = board.get_column();
     // 是否踩雷
     let mut loss_flag = false;
-    while let Some(top) = clicked_poses.pop() {
-        let (i, j) = top;
+    while let Some((i, j)) = clicked_poses.pop() {
         if board[i][j] > 0 {
             board_of_game[i][j] = board[i][j];
+        } else if board[i][j] == -2 {
+            // 被遮挡的位置不能打开
+            continue;
         } else if board[i][j] == 0 {
             board_of_game[i][j] = 0;
-            for m in max(1, i) - 1..min(row, i + 2) {
-                for n in max(1, j) - 1..min(column, j + 2) {
-                    if (i != m || j != n)
-                        && (board_of_game[m][n] == 10 || board_of_game[m][n] == 12)
-                    {
-                        clicked_poses.push((m, n));
-                    }
+            for (m, n) in topology.around(row, column, i, j) {
+                if board_of_game[m][n] == 10 || board_of_game[m][n] == 12 {
+                    clicked_poses.push((m, n));
                 }
             }
         } else {
@@ -645,6 +1004,19 @@ pub fn refresh_board<T>(
     }
 }
 
+/// 依据左击位置刷新局面。如踩雷，标上或14、15标记
+/// - 注意：兼容12标记符
+pub fn refresh_board<T>(
+    board: &T,
+    board_of_game: &mut Vec<Vec<i32>>,
+    clicked_poses: Vec<(usize, usize)>,
+) where
+    T: std::ops::Index<usize> + safe_board::BoardSize,
+    T::Output: std::ops::Index<usize, Output = i32>,
+{
+    refresh_board_with_topology(board, board_of_game, clicked_poses, Topology::Plane)
+}
+
 // 计算组合数 C(n, k)
 pub fn c(n: usize, k: usize) -> BigNumber {
     if k > n {
@@ -1307,7 +1679,7 @@ pub fn legalize_board(board: &mut Vec<Vec<i32>>) {
     let column = board[0].len();
     for x in 0..row {
         for y in 0..column {
-            if board[x][y] <= -1 || board[x][y] >= 13 || board[x][y] == 9 {
+            if board[x][y] <= -1 || (board[x][y] >= 13 && board[x][y] != 19) || board[x][y] == 9 {
                 // 把局面中明显未定义的数字改成未打开
                 board[x][y] = 10;
             } else if board[x][y] >= 1 && board[x][y] <= 8 {
@@ -1328,20 +1700,31 @@ pub fn legalize_board(board: &mut Vec<Vec<i32>>) {
 
 // 重新分块矩阵
 // 这些矩阵必须非空、没有空的块、没有b=0的情况
-pub fn chunk_matrixes(
+// 传入matrix_rows时（与常数向量的形状相同，如每一行对应的数字的位置），按相同的方式重排
+pub fn chunk_matrixes<T: Clone>(
     matrix_as: &mut Vec<Vec<Vec<i32>>>,
     matrix_xs: &mut Vec<Vec<(usize, usize)>>,
     matrix_bs: &mut Vec<Vec<i32>>,
+    mut matrix_rows: Option<&mut Vec<Vec<T>>>,
 ) {
     let block_num = matrix_bs.len();
     let mut aas = vec![];
     let mut xxs = vec![];
     let mut bbs = vec![];
+    let mut rrs = vec![];
     for _ in 0..block_num {
         let aa = matrix_as.pop().unwrap();
         let xx = matrix_xs.pop().unwrap();
         let bb = matrix_bs.pop().unwrap();
-        let (mut a_, mut x_, mut b_) = chunk_matrix(aa, xx, bb);
+        let (mut a_, mut x_, mut b_, row_ids) = chunk_matrix(aa, xx, bb);
+        if let Some(rows) = matrix_rows.as_deref_mut() {
+            let rr = rows.pop().unwrap();
+            rrs.extend(
+                row_ids
+                    .iter()
+                    .map(|ids| ids.iter().map(|&r| rr[r].clone()).collect::<Vec<_>>()),
+            );
+        }
         aas.append(&mut a_);
         xxs.append(&mut x_);
         bbs.append(&mut b_);
@@ -1349,6 +1732,9 @@ pub fn chunk_matrixes(
     *matrix_as = aas;
     *matrix_xs = xxs;
     *matrix_bs = bbs;
+    if let Some(rows) = matrix_rows {
+        *rows = rrs;
+    }
 }
 
 /// 按列（未知格）连通性将系数矩阵拆分为独立子块。
@@ -1366,7 +1752,7 @@ pub fn chunk_matrixes(
 /// - `matrix_b`：方程右端项，`b[i]` 是方程 i 约束的雷数
 ///
 /// # 返回
-/// `(matrix_as, matrix_xs, matrix_bs)` — 各子块的系数矩阵、坐标映射、右端项
+/// `(matrix_as, matrix_xs, matrix_bs, row_ids)` — 各子块的系数矩阵、坐标映射、右端项、每一行在原矩阵中的行号
 ///
 /// # 测试例
 /// ```
@@ -1379,7 +1765,7 @@ pub fn chunk_matrixes(
 /// ];
 /// let x = vec![(1, 2), (3, 4), (5, 6), (7, 8)];
 /// let b = vec![1, 2, 3, 4];
-/// let (aa, xx, bb) = chunk_matrix(a, x, b);
+/// let (aa, xx, bb, row_ids) = chunk_matrix(a, x, b);
 /// assert_eq!(aa.len(), 2);
 /// assert_eq!(aa[0].len(), 2);      // 子块 0：2 行
 /// assert_eq!(aa[0][0].len(), 2);   // 子块 0：2 列
@@ -1390,14 +1776,20 @@ pub fn chunk_matrixes(
 /// assert_eq!(xx[1], vec![(7, 8), (5, 6)]);
 /// assert_eq!(bb[0], vec![3, 1]);
 /// assert_eq!(bb[1], vec![4, 2]);
+/// assert_eq!(row_ids, vec![vec![2, 0], vec![3, 1]]);
 /// ```
 pub fn chunk_matrix(
     matrix_a: Vec<Vec<i32>>,
     matrix_x: Vec<(usize, usize)>,
     matrix_b: Vec<i32>,
-) -> (Vec<Vec<Vec<i32>>>, Vec<Vec<(usize, usize)>>, Vec<Vec<i32>>) {
+) -> (
+    Vec<Vec<Vec<i32>>>,
+    Vec<Vec<(usize, usize)>>,
+    Vec<Vec<i32>>,
+    Vec<Vec<usize>>,
+) {
     if matrix_a.is_empty() || matrix_x.is_empty() {
-        return (vec![], vec![], vec![]);
+        return (vec![], vec![], vec![], vec![]);
     }
 
     let cols = matrix_x.len();
@@ -1418,6 +1810,7 @@ pub fn chunk_matrix(
     let mut matrix_as = vec![];
     let mut matrix_xs = vec![];
     let mut matrix_bs = vec![];
+    let mut row_ids = vec![];
 
     loop {
         let start = match col_done.iter().position(|&d| !d) {
@@ -1470,6 +1863,7 @@ pub fn chunk_matrix(
         for (ic, &c) in comp_cols.iter().enumerate() {
             matrix_xs[bid][ic] = matrix_x[c];
         }
+        row_ids.push(comp_rows);
     }
 
     // 孤立行：不与任何列相连的全零行，各自独立成块
@@ -1478,10 +1872,11 @@ pub fn chunk_matrix(
             matrix_as.push(vec![vec![]]);
             matrix_bs.push(vec![matrix_b[r]]);
             matrix_xs.push(vec![]);
+            row_ids.push(vec![r]);
         }
     }
 
-    (matrix_as, matrix_xs, matrix_bs)
+    (matrix_as, matrix_xs, matrix_bs, row_ids)
 }
 
 #[test]
@@ -1494,7 +1889,7 @@ fn chunk_matrix_works() {
     ];
     let x = vec![(1, 2), (3, 4), (5, 6), (7, 8)];
     let b = vec![1, 2, 3, 4];
-    let (aa, xx, bb) = chunk_matrix(a, x, b);
+    let (aa, xx, bb, row_ids) = chunk_matrix(a, x, b);
     assert_eq!(aa.len(), 2);
     assert_eq!(aa[0].len(), 2);
     assert_eq!(aa[0][0].len(), 2);
@@ -1504,6 +1899,7 @@ fn chunk_matrix_works() {
     assert_eq!(xx[1], vec![(7, 8), (5, 6)]);
     assert_eq!(bb[0], vec![3, 1]);
     assert_eq!(bb[1], vec![4, 2]);
+    assert_eq!(row_ids, vec![vec![2, 0], vec![3, 1]]);
 }
 
 // 找局面中间的格子的所在块的任意一个边界的格子。(可能不严格)
@@ -1603,18 +1999,21 @@ fn surround_cell_num(board_of_game: &Vec<Vec<i32>>, pos: (usize, usize)) -> i8 {
     }
 }
 
-/// 算数字。局面上只有0和-1时，计算其他的数字。不具备幂等性！！！
+/// 算数字。局面上只有0和-1（以及被遮挡的-2）时，计算其他的数字。不具备幂等性！！！
 pub fn cal_board_numbers(board: &mut Vec<Vec<i32>>) {
+    cal_board_numbers_with_topology(board, Topology::Plane)
+}
+
+/// 在指定的拓扑结构上算数字，规则同cal_board_numbers。不具备幂等性！！！
+pub fn cal_board_numbers_with_topology(board: &mut Vec<Vec<i32>>, topology: Topology) {
     let height = board.len();
     let width = board[0].len();
     for x in 0..height {
         for y in 0..width {
             if board[x][y] == -1 {
-                for j in max(1, x) - 1..min(height, x + 2) {
-                    for k in max(1, y) - 1..min(width, y + 2) {
-                        if board[j][k] >= 0 {
-                            board[j][k] += 1;
-                        }
+                for (j, k) in topology.around(height, width, x, y) {
+                    if board[j][k] >= 0 {
+                        board[j][k] += 1;
                     }
                 }
             }
@@ -1,9 +1,9 @@
-use crate::algorithms::{cal_probability_cells_not_mine, mark_board};
+use crate::algorithms::{cal_probability_cells_not_mine_with_topology, mark_board_with_topology};
+use crate::patterns::{find_patterns, PatternMatch};
 use crate::utils::is_good_chording;
 use crate::videos::base_video::BaseVideo;
 use crate::videos::types::Event;
 use crate::MouseState;
-use std::cmp::{max, min};
 
 // 录像的事件分析。参与分析的录像必须已经计算出对应的数据。
 // error: 高风险的猜雷（猜对概率0.05）√
@@ -102,6 +102,7 @@ pub fn analyse_jump_judge(video: &mut BaseVideo<Vec<Vec<i32>>>) {
 pub fn analyse_needless_guess(video: &mut BaseVideo<Vec<Vec<i32>>>) {
     let mut r;
     let mut c;
+    let topology = video.minesweeper_board.topology;
     'outer: for ide in 2..video.video_action_state_recorder.len() {
         let vas = &mut video.video_action_state_recorder[ide];
         if let Some(Event::Mouse(mouse_event)) = &vas.event {
@@ -117,8 +118,13 @@ pub fn analyse_needless_guess(video: &mut BaseVideo<Vec<Vec<i32>>>) {
                     .get_poss()[r][c]
                     > 0.0
                 {
-                    for m in max(2, r) - 2..min(video.height, r + 3) {
-                        for n in max(2, c) - 2..min(video.width, c + 3) {
+                    for m in 0..video.height {
+                        for n in 0..video.width {
+                            let (dm, dn) =
+                                topology.distance(video.height, video.width, (r, c), (m, n));
+                            if dm > 2 || dn > 2 {
+                                continue;
+                            }
                             if vas
                                 .prior_game_board
                                 .as_ref()
@@ -279,6 +285,52 @@ pub fn analyse_vision_transfer(video: &mut BaseVideo<Vec<Vec<i32>>>) {
     }
 }
 
+/// 检查用定式（1-2-1、1-2-2-1等，见find_patterns）判雷时是否犹豫太久。
+/// 定式出现在局面上以后，玩家第一次打开它判出的非雷、或标出它判出的雷时，认为解决了该定式；
+/// 若从定式第一次出现到解决超过1秒，记为warning:slow_pattern:定式的名字:从出现到解决的秒数。
+/// 玩家标的雷可能是错的，识别定式时不使用。
+pub fn analyse_slow_pattern(video: &mut BaseVideo<Vec<Vec<i32>>>) {
+    const SLOW_PATTERN_TIME: f64 = 1.0;
+    // 还没有解决的定式，以及它第一次出现的时间
+    let mut pending: Vec<(PatternMatch, f64)> = vec![];
+    let mut seen: Vec<PatternMatch> = vec![];
+    for vas in video.video_action_state_recorder.iter_mut() {
+        if vas.useful_level == 0 || !matches!(vas.event, Some(Event::Mouse(_))) {
+            continue;
+        }
+        let mut game_board = vas
+            .next_game_board
+            .as_ref()
+            .unwrap()
+            .borrow()
+            .game_board
+            .clone();
+        pending.retain(|(p, appear_time)| {
+            let solved = p.not_mines.iter().any(|&(i, j)| game_board[i][j] < 10)
+                || p.mines.iter().any(|&(i, j)| game_board[i][j] == 11);
+            let used_time = vas.time - appear_time;
+            if solved && used_time > SLOW_PATTERN_TIME {
+                vas.comments
+                    .push_str(&format!("warning:slow_pattern:{}:{:.3};", p.name, used_time));
+            }
+            !solved
+        });
+        for row in game_board.iter_mut() {
+            for cell in row.iter_mut() {
+                if *cell > 10 && *cell != 19 {
+                    *cell = 10;
+                }
+            }
+        }
+        for p in find_patterns(&game_board) {
+            if !seen.contains(&p) {
+                seen.push(p.clone());
+                pending.push((p, vas.time));
+            }
+        }
+    }
+}
+
 /// 计算回放的录像的各个时刻的pluck参数
 pub fn analyse_pluck(video: &mut BaseVideo<Vec<Vec<i32>>>) {
     let mut pluck = 0.0;
@@ -318,21 +370,20 @@ pub fn analyse_pluck(video: &mut BaseVideo<Vec<Vec<i32>>>) {
                     .borrow_mut()
                     .game_board
                     .clone();
-                let mut chording_cells = vec![];
-                for m in max(1, r) - 1..min(video.height, r + 2) {
-                    for n in max(1, c) - 1..min(video.width, c + 2) {
-                        if game_board_clone_clean[m][n] == 10 {
-                            chording_cells.push((m, n));
-                        }
-                    }
-                }
+                let topology = video.minesweeper_board.topology;
+                let chording_cells: Vec<(usize, usize)> = topology
+                    .around(video.height, video.width, r, c)
+                    .filter(|&(m, n)| game_board_clone_clean[m][n] == 10)
+                    .collect();
 
-                let _ = mark_board(&mut game_board_clone_clean, true).unwrap();
+                let _ =
+                    mark_board_with_topology(&mut game_board_clone_clean, true, topology).unwrap();
                 // 安全的概率
-                let p = cal_probability_cells_not_mine(
+                let p = cal_probability_cells_not_mine_with_topology(
                     &game_board_clone_clean,
                     video.mine_num as f64,
                     &chording_cells,
+                    topology,
                 );
 
                 if p <= 0.0 || pluck == f64::MAX {
@@ -1,30 +1,36 @@
 // 录像相关的类，局面在board
 use crate::board::GameBoard;
+use crate::solver_session::SolverSession;
 use crate::cal_cell_nums;
 use crate::miscellaneous::time_ms_between;
-use crate::utils::cal_bbbv;
-use crate::utils::{cal_isl, cal_op};
+use crate::topology::Topology;
+use crate::utils::{cal_bbbv_with_topology, get_covered_game_board};
+use crate::utils::{cal_isl_with_topology, cal_op_with_topology};
 use crate::videos::analyse_methods::{
     analyse_high_risk_guess, analyse_jump_judge, analyse_mouse_trace, analyse_needless_guess,
-    analyse_pluck, analyse_super_fl_local, analyse_vision_transfer,
+    analyse_pluck, analyse_slow_pattern, analyse_super_fl_local, analyse_vision_transfer,
 };
-use crate::{cal_hzini, cal_zini};
+use crate::zini::ZiniRoute;
+use crate::{cal_hzini_with_topology, cal_nf_zini_with_topology, cal_zini_with_topology};
 use core::panic;
 use std::cell::RefCell;
 #[cfg(any(feature = "py", feature = "rs"))]
 use std::fs;
 use std::rc::Rc;
+use std::sync::{Arc, Mutex};
 use web_time::{Instant, SystemTime, UNIX_EPOCH};
 
 use crate::safe_board::{BoardSize, EmptyBoard};
 #[cfg(any(feature = "py", feature = "rs"))]
 use crate::safe_board::SafeBoard;
 
-use crate::{GameBoardState, MinesweeperBoard};
+use crate::{FirstClickPolicy, GameBoardState, MinesweeperBoard};
 // use tract_onnx::prelude::Op;
-use crate::algorithms::cal_probability_cells_not_mine;
-use crate::mark_board;
-use std::cmp::{max, min};
+use crate::algorithms::{
+    cal_probability_cells_not_mine_with_topology, cal_solution_complexity_with_topology,
+    SolutionComplexity,
+};
+use crate::mark_board_with_topology;
 
 use crate::videos::byte_reader::ByteReader;
 use crate::videos::types::{
@@ -348,10 +354,27 @@ impl BaseVideo<Vec<Vec<i32>>> {
         assert!(self.can_analyse, "调用parse或扫完前，不能调用analyse方法");
         // self.minesweeper_board
         let mut b = MinesweeperBoard::<Vec<Vec<i32>>>::new(self.board.clone());
+        let topology = self.minesweeper_board.topology;
+        b.set_topology(topology);
+        // 所有局面共用一个增量求解会话，计算概率时只需重新枚举发生变化的区块
+        let solver_session = Arc::new(Mutex::new(SolverSession::new_with_topology(
+            self.height,
+            self.width,
+            self.mine_num as f64,
+            topology,
+        )));
         let mut first_game_board = GameBoard::new(self.mine_num);
-        first_game_board.set_game_board(&vec![vec![10; self.width]; self.height]);
+        first_game_board.set_topology(topology);
+        first_game_board.set_game_board(&b.game_board);
+        first_game_board.set_solver_session(Arc::clone(&solver_session));
         self.game_board_stream
             .push(Rc::new(RefCell::new(first_game_board)));
+        // 是否nf要推衍完才知道，因此两种局部的ZiNi都要计算
+        let zini_route = ZiniRoute::new(&self.board, topology);
+        let nf_zini_route = ZiniRoute::new_nf(&self.board, topology);
+        let mut zini_solved = 0;
+        let mut nf_zini_solved = 0;
+        let mut nf_zini_solved_list = vec![];
         for ide in 0..self.video_action_state_recorder.len() {
             // 控制svi的生命周期
             let svi = &mut self.video_action_state_recorder[ide];
@@ -383,11 +406,17 @@ impl BaseVideo<Vec<Vec<i32>>> {
                     svi.useful_level = u_level;
                     if u_level >= 1 {
                         let mut g_b = GameBoard::new(self.mine_num);
+                        g_b.set_topology(topology);
                         g_b.set_game_board(&b.game_board);
+                        g_b.set_solver_session(Arc::clone(&solver_session));
                         self.game_board_stream.push(Rc::new(RefCell::new(g_b)));
                         if old_state != GameBoardState::Playing {
                             self.delta_time = svi.time;
                         }
+                        if u_level >= 2 {
+                            zini_solved = zini_route.solved(&b.game_board);
+                            nf_zini_solved = nf_zini_route.solved(&b.game_board);
+                        }
                         // println!("{:?}, {:?}", self.game_board_stream.len(), svi.mouse);
                     }
                 }
@@ -401,6 +430,8 @@ impl BaseVideo<Vec<Vec<i32>>> {
                 svi.key_dynamic_params.rce = b.rce;
                 svi.key_dynamic_params.dce = b.dce;
                 svi.key_dynamic_params.flag = b.flag;
+                svi.key_dynamic_params.zini_solved = zini_solved;
+                nf_zini_solved_list.push((ide, nf_zini_solved));
                 // 这两个很难搞
                 svi.key_dynamic_params.op_solved = 0;
                 svi.key_dynamic_params.isl_solved = 0;
@@ -450,6 +481,17 @@ impl BaseVideo<Vec<Vec<i32>>> {
         self.is_fair = self.is_completed;
 
         self.nf = b.rce == 0;
+        // 盲扫的录像，ZiNi效率按NF ZiNi计算
+        let zini_solved = if self.nf {
+            for (ide, solved) in nf_zini_solved_list {
+                self.video_action_state_recorder[ide]
+                    .key_dynamic_params
+                    .zini_solved = solved;
+            }
+            nf_zini_solved
+        } else {
+            zini_solved
+        };
         self.game_dynamic_params.left = b.left;
         self.game_dynamic_params.left_s = b.left as f64 / rtime;
         self.game_dynamic_params.right = b.right;
@@ -481,12 +523,14 @@ impl BaseVideo<Vec<Vec<i32>>> {
         self.video_dynamic_params.corr =
             (b.lce + b.rce + b.dce) as f64 / self.game_dynamic_params.cl as f64;
         self.video_dynamic_params.thrp = b.bbbv_solved as f64 / (b.lce + b.rce + b.dce) as f64;
+        self.video_dynamic_params.zini_solved = zini_solved;
+        self.video_dynamic_params.zioe = zini_solved as f64 / self.game_dynamic_params.cl as f64;
         // 最后，计算静态指标
         self.cal_static_params();
     }
 
     /// 传入要检查的事件，会把结果记在comments字段里。
-    /// 可以传入high_risk_guess、jump_judge、needless_guess、mouse_trace、vision_transfer、survive_poss等。顺序不讲究。
+    /// 可以传入high_risk_guess、jump_judge、needless_guess、mouse_trace、vision_transfer、survive_poss、slow_pattern等。顺序不讲究。
     /// #### 检查录像中所有的教科书式的fl局部（python）
     /// ```python
     /// import ms_toollib as ms
@@ -537,6 +581,7 @@ impl BaseVideo<Vec<Vec<i32>>> {
                 &"vision_transfer" => analyse_vision_transfer(self),
                 &"pluck" => analyse_pluck(self),
                 &"super_fl_local" => analyse_super_fl_local(self),
+                &"slow_pattern" => analyse_slow_pattern(self),
                 _ => panic!("not supported analysis feature!"),
             };
         }
@@ -739,6 +784,7 @@ impl<T> BaseVideo<T> {
         });
         self.height = board.len();
         self.width = board[0].len();
+        self.static_params.solution_complexity = None;
         if self.height == 8 && self.width == 8 && self.mine_num == 10 {
             self.level = 3;
         } else if self.height == 16 && self.width == 16 && self.mine_num == 40 {
@@ -753,9 +799,44 @@ impl<T> BaseVideo<T> {
             self.minesweeper_board.replace_board(self.board.clone());
         } else {
             self.minesweeper_board.board = self.board.clone();
+            // 更新被遮挡的位置，预标的雷不变
+            for (i, row) in board.iter().enumerate() {
+                for (j, &cell) in row.iter().enumerate() {
+                    let c = &mut self.minesweeper_board.game_board[i][j];
+                    if cell == -2 {
+                        *c = 19;
+                    } else if *c == 19 {
+                        *c = 10;
+                    }
+                }
+            }
         }
         Ok(0)
     }
+    /// 设置第一下点击的规则，只能在游戏开始前设置。第一下按规则移动雷后，录像中记录移动后的局面。
+    pub fn set_first_click_policy(&mut self, policy: FirstClickPolicy, seed: u64) -> Result<u8, ()>
+    where
+        T: From<Vec<Vec<i32>>>,
+    {
+        match self.game_board_state {
+            GameBoardState::Ready | GameBoardState::PreFlaging => {
+                self.minesweeper_board.set_first_click_policy(policy, seed);
+                Ok(0)
+            }
+            _ => Err(()),
+        }
+    }
+    /// 设置局面的拓扑结构，只能在游戏开始前设置。双击、打开空、3BV、Op、Isl、ZiNi，
+    /// 以及录像分析中的判雷、概率和解题复杂度都按此拓扑结构计算。
+    pub fn set_topology(&mut self, topology: Topology) -> Result<u8, ()> {
+        match self.game_board_state {
+            GameBoardState::Ready | GameBoardState::PreFlaging => {
+                self.minesweeper_board.set_topology(topology);
+                Ok(0)
+            }
+            _ => Err(()),
+        }
+    }
     // 播放阶段计算pluck。Display阶段返回缓存值，Win/Loss阶段实时计算。
     pub fn get_pluck(&mut self) -> Result<f64, ()> {
         match self.game_board_state {
@@ -798,19 +879,22 @@ impl<T> BaseVideo<T> {
                                     .borrow_mut()
                                     .game_board
                                     .clone();
-                                let mut chording_cells = vec![];
-                                for m in max(1, x) - 1..min(self.height, x + 2) {
-                                    for n in max(1, y) - 1..min(self.width, y + 2) {
-                                        if game_board_clone[m][n] == 10 {
-                                            chording_cells.push((m, n));
-                                        }
-                                    }
-                                }
-                                let _ = mark_board(&mut game_board_clone, true).unwrap();
-                                let p = cal_probability_cells_not_mine(
+                                let topology = self.minesweeper_board.topology;
+                                let chording_cells: Vec<(usize, usize)> = topology
+                                    .around(self.height, self.width, x, y)
+                                    .filter(|&(m, n)| game_board_clone[m][n] == 10)
+                                    .collect();
+                                let _ = mark_board_with_topology(
+                                    &mut game_board_clone,
+                                    true,
+                                    topology,
+                                )
+                                .unwrap();
+                                let p = cal_probability_cells_not_mine_with_topology(
                                     &game_board_clone,
                                     self.mine_num as f64,
                                     &chording_cells,
+                                    topology,
                                 );
                                 if p <= 0.0 {
                                     return Ok(f64::INFINITY);
@@ -853,6 +937,20 @@ impl<T> BaseVideo<T> {
         let x = pos.0 / self.cell_pixel_size as usize;
         let y = pos.1 / self.cell_pixel_size as usize;
         let a = self.minesweeper_board.step(e, (x, y))?;
+        if let Some(rebuild_board) = self.minesweeper_board.rebuild_board {
+            if (old_state == GameBoardState::Ready || old_state == GameBoardState::PreFlaging)
+                && self.minesweeper_board.game_board_state != old_state
+                && self.minesweeper_board.first_click_policy != FirstClickPolicy::None
+            {
+                // 第一下可能按照规则移动了雷，录像中记录移动后的局面
+                let board = &self.minesweeper_board.board;
+                self.board = rebuild_board(
+                    (0..self.height)
+                        .map(|i| (0..self.width).map(|j| board[i][j]).collect())
+                        .collect(),
+                );
+            }
+        }
         // 获取新的状态
         self.game_board_state = self.minesweeper_board.game_board_state;
         match self.game_board_state {
@@ -956,8 +1054,16 @@ impl<T> BaseVideo<T> {
                 || self.game_board_state == GameBoardState::Loss)
         {
             // 维护第一个先验局面（和path无关）
+            let topology = self.minesweeper_board.topology;
             let mut g_b = GameBoard::new(self.mine_num);
-            g_b.set_game_board(&vec![vec![10; self.width]; self.height]);
+            g_b.set_topology(topology);
+            g_b.set_game_board(&get_covered_game_board(&self.board));
+            g_b.set_solver_session(Arc::new(Mutex::new(SolverSession::new_with_topology(
+                self.height,
+                self.width,
+                self.mine_num as f64,
+                topology,
+            ))));
             self.game_board_stream.push(Rc::new(RefCell::new(g_b)));
             path = 0.0;
         }
@@ -967,7 +1073,12 @@ impl<T> BaseVideo<T> {
         if a >= 1 {
             prior_game_board = Some(Rc::clone(self.game_board_stream.last().unwrap()));
             let mut g_b = GameBoard::new(self.mine_num);
+            g_b.set_topology(self.minesweeper_board.topology);
             g_b.set_game_board(&self.minesweeper_board.game_board);
+            let solver_session = prior_game_board.as_ref().unwrap().borrow().get_solver_session();
+            if let Some(solver_session) = solver_session {
+                g_b.set_solver_session(solver_session);
+            }
             self.game_board_stream.push(Rc::new(RefCell::new(g_b)));
             next_game_board = Some(Rc::clone(self.game_board_stream.last().unwrap()));
         } else {
@@ -999,6 +1110,8 @@ impl<T> BaseVideo<T> {
                     op_solved: 0,
                     isl_solved: 0,
                     pluck: f64::NAN,
+                    // 游戏过程中为0，结束时为最终的结果
+                    zini_solved: self.video_dynamic_params.zini_solved,
                 },
                 path,
             });
@@ -1095,7 +1208,8 @@ impl<T> BaseVideo<T> {
         let t_ms = time_ms - self.game_start_ms;
         // 这是和录像时间成绩有关
         let t = t_ms as f64 / 1000.0;
-        self.static_params.bbbv = cal_bbbv(&self.board);
+        self.static_params.bbbv =
+            cal_bbbv_with_topology(&self.board, self.minesweeper_board.topology);
         self.game_dynamic_params.rtime = t;
         self.game_dynamic_params.rtime_ms = t_ms;
         if self.game_board_state == GameBoardState::Loss {
@@ -1117,6 +1231,19 @@ impl<T> BaseVideo<T> {
         self.game_dynamic_params.double_s = self.minesweeper_board.double as f64 / t;
         self.game_dynamic_params.flag_s = self.minesweeper_board.flag as f64 / t;
         self.game_dynamic_params.cl_s = self.game_dynamic_params.cl as f64 / t;
+        // 游戏过程中不计算局部的ZiNi，结束后只计算最终的结果。没有标雷时按NF ZiNi计算
+        let board: Vec<Vec<i32>> = (0..self.height)
+            .map(|i| (0..self.width).map(|j| self.board[i][j]).collect())
+            .collect();
+        let topology = self.minesweeper_board.topology;
+        let zini_route = if self.minesweeper_board.rce == 0 {
+            ZiniRoute::new_nf(&board, topology)
+        } else {
+            ZiniRoute::new(&board, topology)
+        };
+        let zini_solved = zini_route.solved(&self.minesweeper_board.game_board);
+        self.video_dynamic_params.zini_solved = zini_solved;
+        self.video_dynamic_params.zioe = zini_solved as f64 / self.game_dynamic_params.cl as f64;
         self.cal_static_params();
     }
     // 计算除fps、bbbv以外的静态指标。因为bbbv是计算动态指标的基础
@@ -1135,10 +1262,45 @@ impl<T> BaseVideo<T> {
         self.static_params.cell6 = cell_nums[6];
         self.static_params.cell7 = cell_nums[7];
         self.static_params.cell8 = cell_nums[8];
-        self.static_params.op = cal_op(&self.board);
-        self.static_params.isl = cal_isl(&self.board);
-        self.static_params.zini = cal_zini(&self.board);
-        self.static_params.hzini = cal_hzini(&self.board);
+        let topology = self.minesweeper_board.topology;
+        self.static_params.op = cal_op_with_topology(&self.board, topology);
+        self.static_params.isl = cal_isl_with_topology(&self.board, topology);
+        self.static_params.zini = cal_zini_with_topology(&self.board, topology);
+        self.static_params.hzini = cal_hzini_with_topology(&self.board, topology);
+        self.static_params.nf_zini = cal_nf_zini_with_topology(&self.board, topology);
+    }
+    /// 从录像的起手位置开始的解题复杂度，见cal_solution_complexity。计算量较大，第一次调用时才计算并缓存。
+    /// 还没有打开过格子时返回Err。
+    pub fn get_solution_complexity(&mut self) -> Result<SolutionComplexity, ()>
+    where
+        T: std::ops::Index<usize> + BoardSize,
+        T::Output: std::ops::Index<usize, Output = i32>,
+    {
+        if let Some(complexity) = self.static_params.solution_complexity {
+            return Ok(complexity);
+        }
+        let (x0, y0) = self.first_click_pos().ok_or(())?;
+        let board: Vec<Vec<i32>> = (0..self.height)
+            .map(|i| (0..self.width).map(|j| self.board[i][j]).collect())
+            .collect();
+        let topology = self.minesweeper_board.topology;
+        let complexity = cal_solution_complexity_with_topology(&board, x0, y0, topology);
+        self.static_params.solution_complexity = Some(complexity);
+        Ok(complexity)
+    }
+    // 第一次打开格子（或踩雷）的位置，即起手位置
+    fn first_click_pos(&self) -> Option<(usize, usize)> {
+        self.video_action_state_recorder
+            .iter()
+            .find(|e| e.useful_level >= 2)
+            .and_then(|e| match &e.event {
+                Some(Event::Mouse(mouse_event)) => Some((
+                    mouse_event.y as usize / self.cell_pixel_size as usize,
+                    mouse_event.x as usize / self.cell_pixel_size as usize,
+                )),
+                _ => None,
+            })
+            .filter(|&(x, y)| x < self.height && y < self.width)
     }
 
     pub fn print_event(&self, flag_print_game_board: bool) {
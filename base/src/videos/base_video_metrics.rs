@@ -698,6 +698,30 @@ impl<T> BaseVideo<T> {
         }
         Ok(bbbv_solved as f64 / cl as f64)
     }
+    /// 局部的ZiNi，即按Greedy ZiNi的路线，打开当前已打开的格子至少需要的点击数。扫完时等于ZiNi
+    pub fn get_zini_solved(&self) -> Result<usize, ()> {
+        match self.game_board_state {
+            GameBoardState::Display => Ok(self.video_action_state_recorder[self.current_event_id]
+                .key_dynamic_params
+                .zini_solved),
+            GameBoardState::Win | GameBoardState::Loss => Ok(self
+                .video_action_state_recorder
+                .last()
+                .unwrap()
+                .key_dynamic_params
+                .zini_solved),
+            _ => Err(()),
+        }
+    }
+    /// ZiNi效率，局部的ZiNi除以点击数。未扫完的录像之间也可以比较
+    pub fn get_zioe(&self) -> Result<f64, ()> {
+        let zini_solved = self.get_zini_solved()?;
+        let cl = self.get_cl();
+        if cl == 0 {
+            return Ok(0.0);
+        }
+        Ok(zini_solved as f64 / cl as f64)
+    }
     // 未实现
     pub fn get_op_solved(&self) -> Result<usize, ()> {
         if self.game_board_state != GameBoardState::Display
@@ -1,11 +1,14 @@
-use crate::utils::refresh_board;
+use crate::seeded_rng::SeededRng;
+use crate::topology::Topology;
+use crate::utils::{
+    cal_board_numbers_with_topology, get_covered_game_board, refresh_board_with_topology,
+};
 
 #[cfg(any(feature = "py", feature = "rs"))]
 use crate::safe_board::SafeBoard;
 
 use crate::safe_board::{BoardSize, EmptyBoard};
 
-use std::cmp::{max, min};
 
 /// 没有时间、像素观念的局面状态机，侧重分析操作与局面的交互、推衍局面。在线地统计左右双击次数、ce次数、左键、右键、双击、当前解决的3BV。  
 /// - 局限：不关注具体的线路（没有像素观念），因此不能计算path等。  
@@ -68,6 +71,14 @@ pub struct MinesweeperBoard<T> {
     middle_hold: bool,
     // 是否曾经修改过局面，若修改过，结算时要从头算，因为ce没法增量算
     pub board_changed: bool,
+    // 第一下点击的规则，默认不移动雷。只能用set_first_click_policy设置
+    pub(crate) first_click_policy: FirstClickPolicy,
+    // 随机移动雷时所用的种子
+    first_click_seed: u64,
+    // 移动雷以后，用新的局面构造T。由set_first_click_policy设置，这样只有设置规则时才要求T能从Vec<Vec<i32>>构造
+    pub(crate) rebuild_board: Option<fn(Vec<Vec<i32>>) -> T>,
+    /// 局面的拓扑结构，决定双击、打开空时涉及的格子，默认是平面
+    pub topology: Topology,
 }
 
 impl Default for MinesweeperBoard<Vec<Vec<i32>>> {
@@ -93,6 +104,10 @@ impl Default for MinesweeperBoard<Vec<Vec<i32>>> {
             pre_flag_num: 0,
             middle_hold: false,
             board_changed: false,
+            first_click_policy: FirstClickPolicy::None,
+            first_click_seed: 0,
+            rebuild_board: None,
+            topology: Topology::Plane,
         }
     }
 }
@@ -121,6 +136,10 @@ impl Default for MinesweeperBoard<SafeBoard> {
             pre_flag_num: 0,
             middle_hold: false,
             board_changed: false,
+            first_click_policy: FirstClickPolicy::None,
+            first_click_seed: 0,
+            rebuild_board: None,
+            topology: Topology::Plane,
         }
     }
 }
@@ -129,11 +148,12 @@ impl MinesweeperBoard<Vec<Vec<i32>>> {
     pub fn new(board: Vec<Vec<i32>>) -> MinesweeperBoard<Vec<Vec<i32>>> {
         let row = board.get_row();
         let column = board.get_column();
+        let game_board = get_covered_game_board(&board);
         MinesweeperBoard {
             board,
             row,
             column,
-            game_board: vec![vec![10; column]; row],
+            game_board,
             flaged_list: vec![],
             mouse_state: MouseState::UpUp,
             ..MinesweeperBoard::<Vec<Vec<i32>>>::default()
@@ -149,11 +169,12 @@ impl MinesweeperBoard<SafeBoard> {
     pub fn new(board: SafeBoard) -> MinesweeperBoard<SafeBoard> {
         let row = board.get_row();
         let column = board.get_column();
+        let game_board = get_covered_game_board(&board);
         MinesweeperBoard {
             board,
             row,
             column,
-            game_board: vec![vec![10; column]; row],
+            game_board,
             flaged_list: vec![],
             mouse_state: MouseState::UpUp,
             ..MinesweeperBoard::<SafeBoard>::default()
@@ -184,6 +205,10 @@ impl<T: EmptyBoard> MinesweeperBoard<T> {
             pre_flag_num: 0,
             middle_hold: false,
             board_changed: false,
+            first_click_policy: FirstClickPolicy::None,
+            first_click_seed: 0,
+            rebuild_board: None,
+            topology: Topology::Plane,
         }
     }
 }
@@ -191,7 +216,12 @@ impl<T: EmptyBoard> MinesweeperBoard<T> {
 impl<T> MinesweeperBoard<T> {
     // 初始化。对应强化学习领域gym的api中的reset。
     pub fn reset(&mut self) {
-        self.game_board = vec![vec![10; self.column]; self.row];
+        // 被遮挡的位置保持不变
+        self.game_board
+            .iter_mut()
+            .flatten()
+            .filter(|c| **c != 19)
+            .for_each(|c| *c = 10);
         // self.board = self.board.new(self.row, self.column);
         self.left = 0;
         self.right = 0;
@@ -225,6 +255,85 @@ impl<T> MinesweeperBoard<T> {
         self.pointer_y = 0;
         self.board_changed = true;
     }
+    /// 设置第一下点击的规则。规则见FirstClickPolicy。
+    /// - seed：随机移动雷时所用的种子，相同的局面、相同的种子，移动的结果相同。
+    pub fn set_first_click_policy(&mut self, policy: FirstClickPolicy, seed: u64)
+    where
+        T: From<Vec<Vec<i32>>>,
+    {
+        self.first_click_policy = policy;
+        self.first_click_seed = seed;
+        self.rebuild_board = Some(T::from);
+    }
+    /// 设置局面的拓扑结构。局面上的数字须已按此拓扑结构计算。
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.topology = topology;
+    }
+    /// 第一次左键打开格子前，按照第一下点击的规则移动雷，并重新计算局面上的数字
+    fn apply_first_click_policy(&mut self, x: usize, y: usize)
+    where
+        T: std::ops::Index<usize> + BoardSize,
+        T::Output: std::ops::Index<usize, Output = i32>,
+    {
+        let Some(rebuild_board) = self.rebuild_board else {
+            return;
+        };
+        // 必须不是雷的格子
+        let safe_area: Vec<(usize, usize)> = match self.first_click_policy {
+            FirstClickPolicy::None => return,
+            FirstClickPolicy::SafeCellCorner | FirstClickPolicy::SafeCell => vec![(x, y)],
+            FirstClickPolicy::SafeOpening => {
+                self.topology.around(self.row, self.column, x, y).collect()
+            }
+        };
+        let mut board: Vec<Vec<i32>> = (0..self.row)
+            .map(|i| (0..self.column).map(|j| self.board[i][j]).collect())
+            .collect();
+        // 范围内的雷，以及范围外可以接收雷的格子（按行扫描的顺序）
+        let split = |safe_area: &[(usize, usize)]| {
+            let mines: Vec<(usize, usize)> = safe_area
+                .iter()
+                .filter(|&&(i, j)| board[i][j] == -1)
+                .copied()
+                .collect();
+            let mut targets = vec![];
+            for i in 0..self.row {
+                for j in 0..self.column {
+                    if board[i][j] >= 0 && !safe_area.contains(&(i, j)) {
+                        targets.push((i, j));
+                    }
+                }
+            }
+            (mines, targets)
+        };
+        let (mut mines, mut targets) = split(&safe_area);
+        if mines.len() > targets.len() {
+            // 雷太多，3×3范围内的雷无处可移，退而只保证第一下不是雷
+            (mines, targets) = split(&[(x, y)]);
+        }
+        if mines.is_empty() || targets.is_empty() {
+            return;
+        }
+        let mut rng = SeededRng::new(self.first_click_seed);
+        for (i, j) in mines {
+            let id = match self.first_click_policy {
+                FirstClickPolicy::SafeCellCorner => 0,
+                _ => rng.gen_below(targets.len()),
+            };
+            let (m, n) = targets.remove(id);
+            board[i][j] = 0;
+            board[m][n] = -1;
+        }
+        board.iter_mut().flatten().for_each(|c| {
+            if *c > 0 {
+                *c = 0;
+            }
+        });
+        cal_board_numbers_with_topology(&mut board, self.topology);
+        self.board = rebuild_board(board);
+        self.pointer_x = 0;
+        self.pointer_y = 0;
+    }
     /// Playing状态下的左击，没有按下抬起之分
     fn left_click(&mut self, x: usize, y: usize) -> Result<u8, ()>
     where
@@ -242,19 +351,34 @@ impl<T> MinesweeperBoard<T> {
                     self.bbbv_solved += 1;
                 }
                 self.lce += 1;
-                refresh_board(&self.board, &mut self.game_board, vec![(x, y)]);
+                refresh_board_with_topology(
+                    &self.board,
+                    &mut self.game_board,
+                    vec![(x, y)],
+                    self.topology,
+                );
                 if self.is_win() {
                     self.game_board_state = GameBoardState::Win;
                 }
                 Ok(2)
             }
             -1 => {
-                refresh_board(&self.board, &mut self.game_board, vec![(x, y)]);
+                refresh_board_with_topology(
+                    &self.board,
+                    &mut self.game_board,
+                    vec![(x, y)],
+                    self.topology,
+                );
                 self.game_board_state = GameBoardState::Loss;
                 Ok(4)
             }
             _ => {
-                refresh_board(&self.board, &mut self.game_board, vec![(x, y)]);
+                refresh_board_with_topology(
+                    &self.board,
+                    &mut self.game_board,
+                    vec![(x, y)],
+                    self.topology,
+                );
                 if self.cell_is_bbbv(x, y) {
                     self.bbbv_solved += 1;
                 }
@@ -273,7 +397,7 @@ impl<T> MinesweeperBoard<T> {
         T::Output: std::ops::Index<usize, Output = i32>,
     {
         self.right += 1;
-        if self.game_board[x][y] < 10 {
+        if self.game_board[x][y] < 10 || self.game_board[x][y] == 19 {
             return Ok(0);
         } else {
             // println!("{:?}", self.board);
@@ -316,7 +440,10 @@ impl<T> MinesweeperBoard<T> {
         T::Output: std::ops::Index<usize, Output = i32>,
     {
         self.double += 1;
-        // 在8、10、11、12上双击一定不会发生任何事情，但是在0上双击仍然可能有效！
+        if x >= self.row || y >= self.column {
+            return Ok(0);
+        }
+        // 在8、10、11、12、19上双击一定不会发生任何事情，但是在0上双击仍然可能有效！
         if self.game_board[x][y] >= 8 {
             return Ok(0);
         }
@@ -324,20 +451,18 @@ impl<T> MinesweeperBoard<T> {
         let mut chording_cells = vec![]; // 未打开的格子的集合
         let mut flaged_num = 0; // 双击点周围的标雷数
         let mut surround_bbbv = 0; // 周围的3BV
-        for i in max(1, x) - 1..min(self.row, x + 2) {
-            for j in max(1, y) - 1..min(self.column, y + 2) {
-                if i != x || j != y {
-                    if self.game_board[i][j] == 11 {
-                        flaged_num += 1
-                    }
-                    if self.game_board[i][j] == 10 {
-                        chording_cells.push((i, j));
-                        flag_chording_useful = true;
-                        if self.board[i][j] > 0 {
-                            // 通过双击打开岛上的3BV
-                            if self.cell_is_bbbv(i, j) {
-                                surround_bbbv += 1;
-                            }
+        for (i, j) in self.topology.around(self.row, self.column, x, y) {
+            if i != x || j != y {
+                if self.game_board[i][j] == 11 {
+                    flaged_num += 1
+                }
+                if self.game_board[i][j] == 10 {
+                    chording_cells.push((i, j));
+                    flag_chording_useful = true;
+                    if self.board[i][j] > 0 {
+                        // 通过双击打开岛上的3BV
+                        if self.cell_is_bbbv(i, j) {
+                            surround_bbbv += 1;
                         }
                     }
                 }
@@ -352,7 +477,12 @@ impl<T> MinesweeperBoard<T> {
             self.dce += 1;
             self.bbbv_solved += surround_bbbv;
             self.bbbv_solved += self.op_num_around_cell(x, y);
-            refresh_board(&self.board, &mut self.game_board, chording_cells);
+            refresh_board_with_topology(
+                &self.board,
+                &mut self.game_board,
+                chording_cells,
+                self.topology,
+            );
 
             if self.is_win() {
                 self.game_board_state = GameBoardState::Win;
@@ -375,14 +505,9 @@ impl<T> MinesweeperBoard<T> {
         if self.board[x][y] <= 0 {
             return false;
         }
-        for i in max(1, x) - 1..min(self.row, x + 2) {
-            for j in max(1, y) - 1..min(self.column, y + 2) {
-                if self.board[i][j] == 0 {
-                    return false;
-                }
-            }
-        }
-        true
+        self.topology
+            .around(self.row, self.column, x, y)
+            .all(|(i, j)| self.board[i][j] != 0)
     }
     // 在传入格子上双击以后，将新打开的（完整）op数。（已打开的不算）
     // 这个格子不是雷、双击是合法的
@@ -394,12 +519,10 @@ impl<T> MinesweeperBoard<T> {
     {
         let mut op_num = 0;
         let mut game_board_mark = vec![vec![false; self.column]; self.row];
-        for i in max(1, x) - 1..min(self.row, x + 2) {
-            for j in max(1, y) - 1..min(self.column, y + 2) {
-                if self.game_board[i][j] == 10 && self.board[i][j] == 0 && !game_board_mark[i][j] {
-                    if self.cell_is_op_completed(i, j, &mut game_board_mark) {
-                        op_num += 1;
-                    }
+        for (i, j) in self.topology.around(self.row, self.column, x, y) {
+            if self.game_board[i][j] == 10 && self.board[i][j] == 0 && !game_board_mark[i][j] {
+                if self.cell_is_op_completed(i, j, &mut game_board_mark) {
+                    op_num += 1;
                 }
             }
         }
@@ -424,11 +547,9 @@ impl<T> MinesweeperBoard<T> {
                     return false;
                 }
                 game_board_mark[i][j] = true;
-                for m in max(1, i) - 1..min(self.row, i + 2) {
-                    for n in max(1, j) - 1..min(self.column, j + 2) {
-                        if (i != m || j != n) && (!game_board_mark[m][n]) {
-                            poses.push((m, n));
-                        }
+                for (m, n) in self.topology.around(self.row, self.column, i, j) {
+                    if (i != m || j != n) && (!game_board_mark[m][n]) {
+                        poses.push((m, n));
                     }
                 }
             } else {
@@ -459,6 +580,13 @@ impl<T> MinesweeperBoard<T> {
                 || (pos.0 == self.row && pos.1 == self.column),
             "pos out of range"
         );
+        // 被遮挡的位置不能点击，视同局面外
+        let pos = if pos.0 < self.row && pos.1 < self.column && self.game_board[pos.0][pos.1] == 19
+        {
+            (self.row, self.column)
+        } else {
+            pos
+        };
         match self.game_board_state {
             GameBoardState::Ready => match e {
                 "mv" | "mc" | "mr" => {
@@ -577,6 +705,7 @@ impl<T> MinesweeperBoard<T> {
                         }
                         if self.game_board[pos.0][pos.1] == 10 {
                             // 预标雷阶段，在10上左键弹起
+                            self.apply_first_click_policy(pos.0, pos.1);
                             self.game_board_state = GameBoardState::Playing;
                             self.mouse_state = MouseState::UpUp;
                             return self.left_click(pos.0, pos.1);
@@ -919,7 +1048,8 @@ impl<T> MinesweeperBoard<T> {
                     return false; // 安全性相关（发生作弊）
                 }
             }
-            if self.game_board[self.pointer_x][j] >= 10 && self.board[self.pointer_x][j] != -1 {
+            // 被遮挡的位置（-2）不需要打开
+            if self.game_board[self.pointer_x][j] >= 10 && self.board[self.pointer_x][j] >= 0 {
                 self.pointer_y = j;
                 return false;
             }
@@ -931,7 +1061,7 @@ impl<T> MinesweeperBoard<T> {
                         return false; // 安全性相关（发生作弊）
                     }
                 }
-                if self.game_board[i][j] >= 10 && self.board[i][j] != -1 {
+                if self.game_board[i][j] >= 10 && self.board[i][j] >= 0 {
                     self.pointer_x = i;
                     self.pointer_y = j;
                     return false;
@@ -977,6 +1107,26 @@ pub enum MouseState {
     Undefined,
 }
 
+/// 第一下点击的规则，用于模拟各种扫雷软件在第一下点到雷时的处理。
+/// 局面在第一下之前已经确定，第一下左键打开格子时，按照规则移动雷。
+/// 做ffi传递时作如下约定：
+/// None => 0
+/// SafeCellCorner => 1
+/// SafeCell => 2
+/// SafeOpening => 3
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum FirstClickPolicy {
+    /// 不移动雷，第一下可能踩雷
+    None,
+    /// 第一下是雷时，把这个雷移到左上角，即按行扫描的第一个不是雷的格子（Windows XP自带扫雷的规则）
+    SafeCellCorner,
+    /// 第一下是雷时，把这个雷随机移到其他不是雷的格子，保证第一下不是雷（阿比特等软件）
+    SafeCell,
+    /// 把第一下及其周围3×3范围内的雷随机移到范围外不是雷的格子，保证第一下开空（Win7自带扫雷的规则）。
+    /// 雷太多、范围外放不下时，同SafeCell，只保证第一下不是雷
+    SafeOpening,
+}
+
 /// 游戏局面状态
 /// 做ffi传递时作如下约定：
 /// Ready => 1
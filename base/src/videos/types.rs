@@ -1,4 +1,4 @@
-use crate::{MouseState, GameBoard};
+use crate::{MouseState, GameBoard, SolutionComplexity};
 use std::cell::RefCell;
 use std::rc::Rc;
 
@@ -127,6 +127,10 @@ pub struct StaticParams {
     pub isl: usize,
     pub zini: usize,
     pub hzini: usize,
+    /// 不标雷时的最少点击数，等于3BV
+    pub nf_zini: usize,
+    /// 从录像的起手位置开始的解题复杂度，None代表还没有计算。见BaseVideo::get_solution_complexity
+    pub solution_complexity: Option<SolutionComplexity>,
     pub cell0: usize,
     pub cell1: usize,
     pub cell2: usize,
@@ -148,6 +152,8 @@ impl Default for StaticParams {
             isl: 0,
             zini: 0,
             hzini: 0,
+            nf_zini: 0,
+            solution_complexity: None,
             cell0: 0,
             cell1: 0,
             cell2: 0,
@@ -178,6 +184,9 @@ pub struct KeyDynamicParams {
     pub op_solved: usize,
     pub isl_solved: usize,
     pub pluck: f64,
+    /// 局部的ZiNi，即按Greedy ZiNi的路线，打开当前已打开的格子至少需要的点击数。
+    /// 盲扫（nf）的录像按NF ZiNi的路线计算
+    pub zini_solved: usize,
 }
 
 impl Default for KeyDynamicParams {
@@ -194,6 +203,7 @@ impl Default for KeyDynamicParams {
             op_solved: 0,
             isl_solved: 0,
             pluck: f64::NAN,
+            zini_solved: 0,
         }
     }
 }
@@ -258,6 +268,10 @@ pub struct VideoDynamicParams {
     pub ioe: f64,
     pub corr: f64,
     pub thrp: f64,
+    /// 局部的ZiNi，扫完时等于ZiNi。盲扫（nf）时按NF ZiNi计算，扫完时等于3BV
+    pub zini_solved: usize,
+    /// ZiNi效率，局部的ZiNi除以点击数。盲扫时是NF ZiNi效率
+    pub zioe: f64,
     // 未完成
     pub op_solved: usize,
     // 未完成
@@ -281,6 +295,8 @@ impl Default for VideoDynamicParams {
             ioe: 0.0,
             corr: 0.0,
             thrp: 0.0,
+            zini_solved: 0,
+            zioe: 0.0,
             op_solved: 0,
             isl_solved: 0,
         }
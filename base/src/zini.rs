@@ -1,4 +1,6 @@
 use crate::safe_board;
+use crate::topology::Topology;
+use crate::MinesweeperBoard;
 use getrandom::getrandom;
 
 // 本模块实现了三种 ZiNi 计算方法，均为原版算法。可用于大型局面，例如255*255以下。
@@ -19,8 +21,41 @@ struct Zcell {
     opened: bool,
     flagged: bool,
     premium: i32,
-    rb: usize, re: usize,
-    cb: usize, ce: usize,
+    // 被遮盖的格子（-2）。不参与计算，视为已经打开
+    masked: bool,
+    // 周围3×3范围内（包括自身）未被遮盖的格子的序号
+    around: Vec<usize>,
+}
+
+/// ZiNi路线中的操作类型。
+/// 做ffi传递时用字符串"left"（左键）、"right"（右键标雷）、"chord"（双击）。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ZiniOp {
+    Left,
+    Right,
+    Chord,
+}
+
+impl ZiniOp {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ZiniOp::Left => "left",
+            ZiniOp::Right => "right",
+            ZiniOp::Chord => "chord",
+        }
+    }
+}
+
+impl std::str::FromStr for ZiniOp {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "left" => Ok(ZiniOp::Left),
+            "right" => Ok(ZiniOp::Right),
+            "chord" => Ok(ZiniOp::Chord),
+            _ => Err(()),
+        }
+    }
 }
 
 /// Greedy ZiNi（确定性算法，贪心算法，默认的算法）。  
@@ -31,17 +66,16 @@ where
     T: std::ops::Index<usize> + safe_board::BoardSize,
     T::Output: std::ops::Index<usize, Output = i32>,
 {
-    let height = board.get_row();
-    let width = board.get_column();
-    let mut vboard = vec![vec![0i32; width]; height];
-    for r in 0..height {
-        for c in 0..width {
-            vboard[r][c] = board[r][c];
-        }
-    }
-    let mut cells = build_cells_ref(&vboard, height, width);
-    initboard(&mut cells, width, height);
-    zinialg(&mut cells, width, height, false, false)
+    cal_zini_with_topology(board, Topology::Plane)
+}
+
+/// 在指定的拓扑结构上计算Greedy ZiNi，规则同cal_zini
+pub fn cal_zini_with_topology<T>(board: &T, topology: Topology) -> usize
+where
+    T: std::ops::Index<usize> + safe_board::BoardSize,
+    T::Output: std::ops::Index<usize, Output = i32>,
+{
+    cal_zini_ops_with_topology(board, topology).len()
 }
 
 /// Human ZiNi（模拟人工操作）。  
@@ -51,17 +85,36 @@ where
     T: std::ops::Index<usize> + safe_board::BoardSize,
     T::Output: std::ops::Index<usize, Output = i32>,
 {
-    let height = board.get_row();
-    let width = board.get_column();
-    let mut vboard = vec![vec![0i32; width]; height];
-    for r in 0..height {
-        for c in 0..width {
-            vboard[r][c] = board[r][c];
-        }
-    }
-    let mut cells = build_cells_ref(&vboard, height, width);
-    initboard(&mut cells, width, height);
-    zinialg(&mut cells, width, height, true, true)
+    cal_hzini_with_topology(board, Topology::Plane)
+}
+
+/// 在指定的拓扑结构上计算Human ZiNi，规则同cal_hzini
+pub fn cal_hzini_with_topology<T>(board: &T, topology: Topology) -> usize
+where
+    T: std::ops::Index<usize> + safe_board::BoardSize,
+    T::Output: std::ops::Index<usize, Output = i32>,
+{
+    cal_hzini_ops_with_topology(board, topology).len()
+}
+
+/// NF ZiNi（不标雷）。  
+/// 不标雷就不能双击，只能逐个左键点开：每片空（opening）点一次，每个不与空相邻的数字点一次，
+/// 因此等于同一拓扑结构上的3BV。局面中被遮盖的格子（-2）不需要打开，不计入。
+pub fn cal_nf_zini<T>(board: &T) -> usize
+where
+    T: std::ops::Index<usize> + safe_board::BoardSize,
+    T::Output: std::ops::Index<usize, Output = i32>,
+{
+    cal_nf_zini_with_topology(board, Topology::Plane)
+}
+
+/// 在指定的拓扑结构上计算NF ZiNi，等于cal_bbbv_with_topology
+pub fn cal_nf_zini_with_topology<T>(board: &T, topology: Topology) -> usize
+where
+    T: std::ops::Index<usize> + safe_board::BoardSize,
+    T::Output: std::ops::Index<usize, Output = i32>,
+{
+    cal_nf_zini_ops_with_topology(board, topology).len()
 }
 
 fn rand_range(limit: usize) -> usize {
@@ -82,6 +135,248 @@ fn rand_range(limit: usize) -> usize {
 /// Random ZiNi（随机化算法）。  
 /// 多轮迭代，每轮在 premium 最高值对应的格子中随机选择，返回所有轮次的最小值。
 pub fn cal_rzini<T>(board: &T, n_iter: usize) -> usize
+where
+    T: std::ops::Index<usize> + safe_board::BoardSize,
+    T::Output: std::ops::Index<usize, Output = i32>,
+{
+    cal_rzini_with_topology(board, n_iter, Topology::Plane)
+}
+
+/// 在指定的拓扑结构上计算Random ZiNi，规则同cal_rzini
+pub fn cal_rzini_with_topology<T>(board: &T, n_iter: usize, topology: Topology) -> usize
+where
+    T: std::ops::Index<usize> + safe_board::BoardSize,
+    T::Output: std::ops::Index<usize, Output = i32>,
+{
+    if n_iter == 0 {
+        return usize::MAX;
+    }
+    cal_rzini_ops_with_topology(board, n_iter, topology).len()
+}
+
+/// Greedy ZiNi的路线，即cal_zini对应的操作序列。
+/// - 返回：按顺序排列的操作，每个操作为（操作类型, (行, 列)）。
+///   操作的个数等于cal_zini。
+pub fn cal_zini_ops<T>(board: &T) -> Vec<(ZiniOp, (usize, usize))>
+where
+    T: std::ops::Index<usize> + safe_board::BoardSize,
+    T::Output: std::ops::Index<usize, Output = i32>,
+{
+    cal_zini_ops_with_topology(board, Topology::Plane)
+}
+
+/// 在指定的拓扑结构上计算Greedy ZiNi的路线，返回值的格式同cal_zini_ops
+pub fn cal_zini_ops_with_topology<T>(board: &T, topology: Topology) -> Vec<(ZiniOp, (usize, usize))>
+where
+    T: std::ops::Index<usize> + safe_board::BoardSize,
+    T::Output: std::ops::Index<usize, Output = i32>,
+{
+    let (mut cells, height, width) = init_cells(board, topology);
+    let ops = zinialg(&mut cells, width, height, false, false);
+    ops_to_positions(&ops, height)
+}
+
+/// Human ZiNi的路线，即cal_hzini对应的操作序列。返回值的格式同cal_zini_ops。
+pub fn cal_hzini_ops<T>(board: &T) -> Vec<(ZiniOp, (usize, usize))>
+where
+    T: std::ops::Index<usize> + safe_board::BoardSize,
+    T::Output: std::ops::Index<usize, Output = i32>,
+{
+    cal_hzini_ops_with_topology(board, Topology::Plane)
+}
+
+/// 在指定的拓扑结构上计算Human ZiNi的路线，返回值的格式同cal_zini_ops
+pub fn cal_hzini_ops_with_topology<T>(
+    board: &T,
+    topology: Topology,
+) -> Vec<(ZiniOp, (usize, usize))>
+where
+    T: std::ops::Index<usize> + safe_board::BoardSize,
+    T::Output: std::ops::Index<usize, Output = i32>,
+{
+    let (mut cells, height, width) = init_cells(board, topology);
+    let ops = zinialg(&mut cells, width, height, true, true);
+    ops_to_positions(&ops, height)
+}
+
+/// Random ZiNi的路线，即n_iter轮迭代中操作数最少的一轮的操作序列。返回值的格式同cal_zini_ops。
+pub fn cal_rzini_ops<T>(board: &T, n_iter: usize) -> Vec<(ZiniOp, (usize, usize))>
+where
+    T: std::ops::Index<usize> + safe_board::BoardSize,
+    T::Output: std::ops::Index<usize, Output = i32>,
+{
+    cal_rzini_ops_with_topology(board, n_iter, Topology::Plane)
+}
+
+/// 在指定的拓扑结构上计算Random ZiNi的路线，返回值的格式同cal_zini_ops
+pub fn cal_rzini_ops_with_topology<T>(
+    board: &T,
+    n_iter: usize,
+    topology: Topology,
+) -> Vec<(ZiniOp, (usize, usize))>
+where
+    T: std::ops::Index<usize> + safe_board::BoardSize,
+    T::Output: std::ops::Index<usize, Output = i32>,
+{
+    let mut best: Option<Vec<(ZiniOp, usize)>> = None;
+    let mut height = 0;
+    for _i in 0..n_iter {
+        let (mut cells, h, width) = init_cells(board, topology);
+        height = h;
+        let ops = zinialg_rng(&mut cells, width, height, false, false);
+        if best.as_ref().is_none_or(|b| ops.len() < b.len()) {
+            best = Some(ops);
+        }
+    }
+    ops_to_positions(&best.unwrap_or_default(), height)
+}
+
+/// NF ZiNi的路线，即cal_nf_zini对应的操作序列，只有左键。返回值的格式同cal_zini_ops。
+pub fn cal_nf_zini_ops<T>(board: &T) -> Vec<(ZiniOp, (usize, usize))>
+where
+    T: std::ops::Index<usize> + safe_board::BoardSize,
+    T::Output: std::ops::Index<usize, Output = i32>,
+{
+    cal_nf_zini_ops_with_topology(board, Topology::Plane)
+}
+
+/// 在指定的拓扑结构上计算NF ZiNi的路线，返回值的格式同cal_zini_ops
+pub fn cal_nf_zini_ops_with_topology<T>(
+    board: &T,
+    topology: Topology,
+) -> Vec<(ZiniOp, (usize, usize))>
+where
+    T: std::ops::Index<usize> + safe_board::BoardSize,
+    T::Output: std::ops::Index<usize, Output = i32>,
+{
+    let (cells, height, _width) = init_cells(board, topology);
+    // 编号为i的空是否已经点过，编号从1开始
+    let mut clicked = vec![false; cells.len() + 1];
+    let mut ops = vec![];
+    for (i, cell) in cells.iter().enumerate() {
+        if cell.mine || cell.masked {
+            continue;
+        }
+        if cell.number == 0 {
+            if !clicked[cell.opening] {
+                clicked[cell.opening] = true;
+                ops.push((ZiniOp::Left, i));
+            }
+        } else if cell.opening == 0 {
+            ops.push((ZiniOp::Left, i));
+        }
+    }
+    ops_to_positions(&ops, height)
+}
+
+/// 把ZiNi的操作序列转换成MinesweeperBoard::step_flow可以执行的鼠标事件，用于回放、验证路线。
+/// - 左键转换为lc、lr，右键转换为rc、rr，双击转换为lc、rc、lr、rr。
+pub fn zini_ops_to_step_flow(
+    ops: &Vec<(ZiniOp, (usize, usize))>,
+) -> Vec<(String, (usize, usize))> {
+    let mut flow = vec![];
+    for (op, pos) in ops {
+        let events: &[&str] = match op {
+            ZiniOp::Left => &["lc", "lr"],
+            ZiniOp::Right => &["rc", "rr"],
+            ZiniOp::Chord => &["lc", "rc", "lr", "rr"],
+        };
+        flow.extend(events.iter().map(|e| (e.to_string(), *pos)));
+    }
+    flow
+}
+
+/// 局部的ZiNi：按Greedy ZiNi的路线，打开游戏局面上已打开的所有格子必需的操作数。
+/// 用于未扫完（踩雷）的局面，与已解决的3BV类似，可以和点击数比较效率。
+/// - 注意：只沿用完整局面的路线，不针对局部重新规划，因此局部的ZiNi可能大于玩家实际的点击数。
+/// - 输入：真实局面、游戏局面（0~8为已打开的格子，其余的数字被忽略）。
+/// - 返回：路线中打开这些格子的操作，以及它们依赖的更早的标雷、左键操作的个数。局面全部打开时等于cal_zini。
+pub fn cal_partial_zini(board: &Vec<Vec<i32>>, game_board: &Vec<Vec<i32>>) -> usize {
+    ZiniRoute::new(board, Topology::Plane).solved(game_board)
+}
+
+/// Greedy ZiNi的路线中，每个操作打开的格子及依赖的操作。
+/// 构造一次以后，可以对录像中的每一个局面计算局部的ZiNi。
+pub(crate) struct ZiniRoute {
+    // 每个操作打开的格子
+    reveals: Vec<Vec<(usize, usize)>>,
+    // 每个操作依赖的更早的操作：双击依赖打开该格子、标出周围的雷的操作
+    deps: Vec<Vec<usize>>,
+}
+
+impl ZiniRoute {
+    pub(crate) fn new(board: &Vec<Vec<i32>>, topology: Topology) -> ZiniRoute {
+        ZiniRoute::from_ops(board, topology, &cal_zini_ops_with_topology(board, topology))
+    }
+
+    /// NF ZiNi的路线，每个操作只打开一个3BV
+    pub(crate) fn new_nf(board: &Vec<Vec<i32>>, topology: Topology) -> ZiniRoute {
+        ZiniRoute::from_ops(board, topology, &cal_nf_zini_ops_with_topology(board, topology))
+    }
+
+    fn from_ops(
+        board: &[Vec<i32>],
+        topology: Topology,
+        ops: &[(ZiniOp, (usize, usize))],
+    ) -> ZiniRoute {
+        let row = board.len();
+        let column = board[0].len();
+        let mut b = MinesweeperBoard::<Vec<Vec<i32>>>::new(board.to_vec());
+        b.set_topology(topology);
+        // 最后一次改变每个格子的操作
+        let mut owner = vec![vec![usize::MAX; column]; row];
+        let mut reveals = Vec::with_capacity(ops.len());
+        let mut deps = Vec::with_capacity(ops.len());
+        for (id, op) in ops.iter().enumerate() {
+            let (x, y) = op.1;
+            let mut dep = vec![];
+            if op.0 == ZiniOp::Chord {
+                for (i, j) in topology.around(row, column, x, y) {
+                    if (board[i][j] == -1 || (i, j) == (x, y)) && owner[i][j] != usize::MAX {
+                        dep.push(owner[i][j]);
+                    }
+                }
+            }
+            let before = b.game_board.clone();
+            b.step_flow(&zini_ops_to_step_flow(&vec![op.clone()])).unwrap();
+            let mut reveal = vec![];
+            for i in 0..row {
+                for j in 0..column {
+                    if b.game_board[i][j] != before[i][j] {
+                        owner[i][j] = id;
+                        if board[i][j] >= 0 && b.game_board[i][j] < 10 {
+                            reveal.push((i, j));
+                        }
+                    }
+                }
+            }
+            reveals.push(reveal);
+            deps.push(dep);
+        }
+        ZiniRoute { reveals, deps }
+    }
+
+    /// 打开游戏局面上已打开的格子必需的操作数
+    pub(crate) fn solved(&self, game_board: &[Vec<i32>]) -> usize {
+        let mut needed: Vec<bool> = self
+            .reveals
+            .iter()
+            .map(|r| r.iter().any(|&(i, j)| (0..=8).contains(&game_board[i][j])))
+            .collect();
+        let mut n = 0;
+        for id in (0..needed.len()).rev() {
+            if needed[id] {
+                n += 1;
+                for &d in &self.deps[id] {
+                    needed[d] = true;
+                }
+            }
+        }
+        n
+    }
+}
+
+fn init_cells<T>(board: &T, topology: Topology) -> (Vec<Zcell>, usize, usize)
 where
     T: std::ops::Index<usize> + safe_board::BoardSize,
     T::Output: std::ops::Index<usize, Output = i32>,
@@ -94,75 +389,75 @@ where
             vboard[r][c] = board[r][c];
         }
     }
-    let mut min_zini = usize::MAX;
-    for _i in 0..n_iter {
-        let mut cells = build_cells_ref(&vboard, height, width);
-        initboard(&mut cells, width, height);
-        let z = zinialg_rng(&mut cells, width, height, false, false);
-        if z < min_zini {
-            min_zini = z;
-        }
-    }
-    min_zini
+    let mut cells = build_cells_ref(&vboard, height, width, topology);
+    initboard(&mut cells, width, height);
+    (cells, height, width)
+}
+
+/// 把格子的序号（按列排列）转换成（行, 列）
+fn ops_to_positions(ops: &[(ZiniOp, usize)], height: usize) -> Vec<(ZiniOp, (usize, usize))> {
+    ops.iter()
+        .map(|&(op, index)| (op, (index % height, index / height)))
+        .collect()
 }
 
-fn build_cells_ref(board: &[Vec<i32>], height: usize, width: usize) -> Vec<Zcell> {
+fn build_cells_ref(
+    board: &[Vec<i32>],
+    height: usize,
+    width: usize,
+    topology: Topology,
+) -> Vec<Zcell> {
     let size = width * height;
     let mut cells: Vec<Zcell> = Vec::with_capacity(size);
     for c in 0..width {
         for r in 0..height {
             let mine = board[r][c] == -1;
-            let rb = if r > 0 { r - 1 } else { r };
-            let re = if r < height - 1 { r + 1 } else { r };
-            let cb = if c > 0 { c - 1 } else { c };
-            let ce = if c < width - 1 { c + 1 } else { c };
+            let masked = board[r][c] == -2;
+            let around = if masked {
+                vec![]
+            } else {
+                topology
+                    .around(height, width, r, c)
+                    .filter(|&(i, j)| board[i][j] != -2)
+                    .map(|(i, j)| j * height + i)
+                    .collect()
+            };
             cells.push(Zcell {
                 mine,
                 opening: 0,
                 opening2: 0,
                 number: 0,
-                opened: false,
+                opened: masked,
                 flagged: false,
                 premium: 0,
-                rb, re, cb, ce,
+                masked,
+                around,
             });
         }
     }
     cells
 }
 
-fn getnumber(cells: &[Zcell], height: usize, index: usize) -> i32 {
+fn getnumber(cells: &[Zcell], index: usize) -> i32 {
     let mut res = 0i32;
-    let rb = cells[index].rb;
-    let re = cells[index].re;
-    let cb = cells[index].cb;
-    let ce = cells[index].ce;
-    for rr in rb..=re {
-        for cc in cb..=ce {
-            let i = cc * height + rr;
-            if cells[i].mine {
-                res += 1;
-            }
+    for k in 0..cells[index].around.len() {
+        let i = cells[index].around[k];
+        if cells[i].mine {
+            res += 1;
         }
     }
     res
 }
 
-fn getadj3bv(cells: &[Zcell], height: usize, index: usize) -> i32 {
+fn getadj3bv(cells: &[Zcell], index: usize) -> i32 {
     if cells[index].number == 0 {
         return 1;
     }
     let mut res = 0i32;
-    let rb = cells[index].rb;
-    let re = cells[index].re;
-    let cb = cells[index].cb;
-    let ce = cells[index].ce;
-    for rr in rb..=re {
-        for cc in cb..=ce {
-            let i = cc * height + rr;
-            if !cells[i].mine && cells[i].opening == 0 {
-                res += 1;
-            }
+    for k in 0..cells[index].around.len() {
+        let i = cells[index].around[k];
+        if !cells[i].mine && cells[i].opening == 0 {
+            res += 1;
         }
     }
     if cells[index].opening != 0 {
@@ -182,20 +477,14 @@ fn setopeningborder(cells: &mut [Zcell], op_id: usize, index: usize) {
     }
 }
 
-fn process_opening(cells: &mut [Zcell], height: usize, op_id: usize, index: usize) {
+fn process_opening(cells: &mut [Zcell], op_id: usize, index: usize) {
     cells[index].opening = op_id;
-    let rb = cells[index].rb;
-    let re = cells[index].re;
-    let cb = cells[index].cb;
-    let ce = cells[index].ce;
-    for rr in rb..=re {
-        for cc in cb..=ce {
-            let i = cc * height + rr;
-            if cells[i].number != 0 {
-                setopeningborder(cells, op_id, i);
-            } else if cells[i].opening == 0 {
-                process_opening(cells, height, op_id, i);
-            }
+    for k in 0..cells[index].around.len() {
+        let i = cells[index].around[k];
+        if cells[i].number != 0 {
+            setopeningborder(cells, op_id, i);
+        } else if cells[i].opening == 0 {
+            process_opening(cells, op_id, i);
         }
     }
 }
@@ -203,52 +492,46 @@ fn process_opening(cells: &mut [Zcell], height: usize, op_id: usize, index: usiz
 fn initboard(cells: &mut [Zcell], width: usize, height: usize) {
     let size = width * height;
     for i in 0..size {
-        cells[i].number = getnumber(cells, height, i);
+        cells[i].number = getnumber(cells, i);
         cells[i].premium = -cells[i].number - 2;
     }
     let mut openings = 0usize;
     for i in 0..size {
-        if !cells[i].mine && cells[i].number == 0 && cells[i].opening == 0 {
+        if !cells[i].mine && !cells[i].masked && cells[i].number == 0 && cells[i].opening == 0 {
             openings += 1;
-            process_opening(cells, height, openings, i);
+            process_opening(cells, openings, i);
         }
     }
     for i in 0..size {
-        cells[i].premium += getadj3bv(cells, height, i);
+        cells[i].premium += getadj3bv(cells, i);
     }
 }
 
-fn open(cells: &mut [Zcell], height: usize, closed_cells: &mut usize, index: usize) {
+fn open(cells: &mut [Zcell], closed_cells: &mut usize, index: usize) {
     cells[index].opened = true;
     cells[index].premium += 1;
     if cells[index].opening == 0 {
-        let rb = cells[index].rb;
-        let re = cells[index].re;
-        let cb = cells[index].cb;
-        let ce = cells[index].ce;
-        for rr in rb..=re {
-            for cc in cb..=ce {
-                let i = cc * height + rr;
-                cells[i].premium -= 1;
-            }
+        for k in 0..cells[index].around.len() {
+            let i = cells[index].around[k];
+            cells[i].premium -= 1;
         }
     }
     *closed_cells -= 1;
 }
 
-fn reveal(cells: &mut [Zcell], height: usize, closed_cells: &mut usize, index: usize) {
+fn reveal(cells: &mut [Zcell], closed_cells: &mut usize, index: usize) {
     if cells[index].opened || cells[index].flagged {
         return;
     }
     if cells[index].number != 0 {
-        open(cells, height, closed_cells, index);
+        open(cells, closed_cells, index);
     } else {
         let op = cells[index].opening;
         let size = cells.len();
         for i in 0..size {
             if cells[i].opening2 == op || cells[i].opening == op {
                 if !cells[i].opened {
-                    open(cells, height, closed_cells, i);
+                    open(cells, closed_cells, i);
                 }
                 cells[i].premium -= 1;
             }
@@ -256,71 +539,52 @@ fn reveal(cells: &mut [Zcell], height: usize, closed_cells: &mut usize, index: u
     }
 }
 
-fn click(cells: &mut [Zcell], height: usize, zini: &mut usize, closed_cells: &mut usize, index: usize) {
-    reveal(cells, height, closed_cells, index);
-    *zini += 1;
+fn click(cells: &mut [Zcell], ops: &mut Vec<(ZiniOp, usize)>, closed_cells: &mut usize, index: usize) {
+    reveal(cells, closed_cells, index);
+    ops.push((ZiniOp::Left, index));
 }
 
-fn flag(cells: &mut [Zcell], height: usize, zini: &mut usize, index: usize) {
+fn flag(cells: &mut [Zcell], ops: &mut Vec<(ZiniOp, usize)>, index: usize) {
     if cells[index].flagged {
         return;
     }
-    *zini += 1;
+    ops.push((ZiniOp::Right, index));
     cells[index].flagged = true;
-    let rb = cells[index].rb;
-    let re = cells[index].re;
-    let cb = cells[index].cb;
-    let ce = cells[index].ce;
-    for rr in rb..=re {
-        for cc in cb..=ce {
-            let i = cc * height + rr;
-            cells[i].premium += 1;
-        }
-    }
-}
-
-fn flagaround(cells: &mut [Zcell], height: usize, zini: &mut usize, index: usize) {
-    let rb = cells[index].rb;
-    let re = cells[index].re;
-    let cb = cells[index].cb;
-    let ce = cells[index].ce;
-    for rr in rb..=re {
-        for cc in cb..=ce {
-            let i = cc * height + rr;
-            if cells[i].mine {
-                flag(cells, height, zini, i);
-            }
-        }
+    for k in 0..cells[index].around.len() {
+        let i = cells[index].around[k];
+        cells[i].premium += 1;
     }
 }
 
-fn chord(cells: &mut [Zcell], height: usize, zini: &mut usize, closed_cells: &mut usize, index: usize) {
-    *zini += 1;
-    let rb = cells[index].rb;
-    let re = cells[index].re;
-    let cb = cells[index].cb;
-    let ce = cells[index].ce;
-    for rr in rb..=re {
-        for cc in cb..=ce {
-            let i = cc * height + rr;
-            reveal(cells, height, closed_cells, i);
+fn flagaround(cells: &mut [Zcell], ops: &mut Vec<(ZiniOp, usize)>, index: usize) {
+    for k in 0..cells[index].around.len() {
+        let i = cells[index].around[k];
+        if cells[i].mine {
+            flag(cells, ops, i);
         }
     }
 }
 
-fn hitopenings(cells: &mut [Zcell], height: usize, zini: &mut usize, closed_cells: &mut usize) {
+fn chord(cells: &mut [Zcell], ops: &mut Vec<(ZiniOp, usize)>, closed_cells: &mut usize, index: usize) {
+    ops.push((ZiniOp::Chord, index));
+    for k in 0..cells[index].around.len() {
+        let i = cells[index].around[k];
+        reveal(cells, closed_cells, i);
+    }
+}
+
+fn hitopenings(cells: &mut [Zcell], ops: &mut Vec<(ZiniOp, usize)>, closed_cells: &mut usize) {
     let size = cells.len();
     for j in 0..size {
         if !cells[j].mine && cells[j].number == 0 && !cells[j].opened {
-            click(cells, height, zini, closed_cells, j);
+            click(cells, ops, closed_cells, j);
         }
     }
 }
 
 fn apply_zini(
     cells: &mut [Zcell],
-    height: usize,
-    zini: &mut usize,
+    ops: &mut Vec<(ZiniOp, usize)>,
     closed_cells: &mut usize,
     human: bool,
 ) -> Option<usize> {
@@ -329,7 +593,11 @@ fn apply_zini(
     let mut curi = None;
 
     for i in 0..size {
-        if cells[i].premium > maxp && !cells[i].mine && (cells[i].opened || !human) {
+        if cells[i].premium > maxp
+            && !cells[i].mine
+            && !cells[i].masked
+            && (cells[i].opened || !human)
+        {
             maxp = cells[i].premium;
             curi = Some(i);
         }
@@ -337,10 +605,10 @@ fn apply_zini(
 
     if let Some(idx) = curi {
         if !cells[idx].opened {
-            click(cells, height, zini, closed_cells, idx);
+            click(cells, ops, closed_cells, idx);
         }
-        flagaround(cells, height, zini, idx);
-        chord(cells, height, zini, closed_cells, idx);
+        flagaround(cells, ops, idx);
+        chord(cells, ops, closed_cells, idx);
         Some(idx)
     } else {
         let mut fb = None;
@@ -353,7 +621,7 @@ fn apply_zini(
             }
         }
         if let Some(idx) = fb {
-            click(cells, height, zini, closed_cells, idx);
+            click(cells, ops, closed_cells, idx);
             fb
         } else {
             None
@@ -363,8 +631,7 @@ fn apply_zini(
 
 fn apply_zini_rng(
     cells: &mut [Zcell],
-    height: usize,
-    zini: &mut usize,
+    ops: &mut Vec<(ZiniOp, usize)>,
     closed_cells: &mut usize,
     human: bool,
     maxpr: &mut Vec<usize>,
@@ -374,7 +641,7 @@ fn apply_zini_rng(
     let mut curi_len = 0usize;
 
     for i in 0..size {
-        if cells[i].mine { continue; }
+        if cells[i].mine || cells[i].masked { continue; }
         if human && !cells[i].opened { continue; }
         if cells[i].premium > maxp {
             maxp = cells[i].premium;
@@ -389,10 +656,10 @@ fn apply_zini_rng(
     if maxp >= 0 {
         let idx = maxpr[rand_range(curi_len)];
         if !cells[idx].opened {
-            click(cells, height, zini, closed_cells, idx);
+            click(cells, ops, closed_cells, idx);
         }
-        flagaround(cells, height, zini, idx);
-        chord(cells, height, zini, closed_cells, idx);
+        flagaround(cells, ops, idx);
+        chord(cells, ops, closed_cells, idx);
         Some(idx)
     } else {
         let mut fb_count = 0usize;
@@ -406,7 +673,7 @@ fn apply_zini_rng(
         }
         if fb_count > 0 {
             let pick = maxpr[rand_range(fb_count)];
-            click(cells, height, zini, closed_cells, pick);
+            click(cells, ops, closed_cells, pick);
             Some(pick)
         } else {
             None
@@ -420,7 +687,7 @@ fn zinialg(
     height: usize,
     human: bool,
     hitops: bool,
-) -> usize {
+) -> Vec<(ZiniOp, usize)> {
     let size = width * height;
     let mut mines = 0usize;
     for i in 0..size {
@@ -428,19 +695,19 @@ fn zinialg(
             mines += 1;
         }
     }
-    let mut closed_cells = size;
-    let mut zini = 0;
+    let mut closed_cells = cells.iter().filter(|c| !c.masked).count();
+    let mut ops = vec![];
 
     if hitops {
-        hitopenings(cells, height, &mut zini, &mut closed_cells);
+        hitopenings(cells, &mut ops, &mut closed_cells);
     }
 
     while closed_cells > mines {
-        if apply_zini(cells, height, &mut zini, &mut closed_cells, human).is_none() {
+        if apply_zini(cells, &mut ops, &mut closed_cells, human).is_none() {
             break;
         }
     }
-    zini
+    ops
 }
 
 fn zinialg_rng(
@@ -449,7 +716,7 @@ fn zinialg_rng(
     height: usize,
     human: bool,
     hitops: bool,
-) -> usize {
+) -> Vec<(ZiniOp, usize)> {
     let size = width * height;
     let mut mines = 0usize;
     for i in 0..size {
@@ -458,19 +725,19 @@ fn zinialg_rng(
         }
     }
     let mut maxpr = vec![0usize; size];
-    let mut closed_cells = size;
-    let mut zini = 0;
+    let mut closed_cells = cells.iter().filter(|c| !c.masked).count();
+    let mut ops = vec![];
 
     if hitops {
-        hitopenings(cells, height, &mut zini, &mut closed_cells);
+        hitopenings(cells, &mut ops, &mut closed_cells);
     }
 
     while closed_cells > mines {
-        if apply_zini_rng(cells, height, &mut zini, &mut closed_cells, human, &mut maxpr).is_none() {
+        if apply_zini_rng(cells, &mut ops, &mut closed_cells, human, &mut maxpr).is_none() {
             break;
         }
     }
-    zini
+    ops
 }
 
 #[cfg(test)]
@@ -525,6 +792,87 @@ mod tests {
         assert_eq!(z, 1);
     }
 
+    #[test]
+    fn test_nf_zini() {
+        for seed in 0..10u64 {
+            let board = crate::laymine_seeded(16, 30, 99, 0, 0, seed);
+            let ops = cal_nf_zini_ops(&board);
+            assert_eq!(ops.len(), crate::cal_bbbv(&board));
+            assert!(ops.len() >= cal_zini(&board));
+            let mut mb = crate::MinesweeperBoard::<Vec<Vec<i32>>>::new(board.clone());
+            mb.step_flow(&zini_ops_to_step_flow(&ops)).unwrap();
+            assert_eq!(mb.game_board_state, crate::GameBoardState::Win);
+            assert_eq!(mb.rce, 0);
+            let route = ZiniRoute::new_nf(&board, Topology::Plane);
+            assert_eq!(route.solved(&board), cal_nf_zini(&board));
+        }
+    }
+
+    #[test]
+    fn test_nf_zini_masked_and_torus() {
+        // 8×8的环形局面，中间4×4被遮盖
+        let mask: Vec<Vec<bool>> = (0..8)
+            .map(|i| (0..8).map(|j| (2..6).contains(&i) && (2..6).contains(&j)).collect())
+            .collect();
+        for seed in 0..10u64 {
+            let board = crate::laymine_masked_seeded(&mask, 8, 0, 0, seed);
+            let bbbv = crate::cal_bbbv_with_topology(&board, Topology::Plane);
+            assert_eq!(cal_nf_zini(&board), bbbv);
+            assert!(cal_zini(&board) <= bbbv);
+            for ops in [cal_zini_ops(&board), cal_hzini_ops(&board), cal_nf_zini_ops(&board)] {
+                let mut mb = crate::MinesweeperBoard::<Vec<Vec<i32>>>::new(board.clone());
+                mb.step_flow(&zini_ops_to_step_flow(&ops)).unwrap();
+                assert_eq!(mb.game_board_state, crate::GameBoardState::Win);
+            }
+        }
+        for seed in 0..10u64 {
+            let board =
+                crate::laymine_with_topology_seeded(8, 8, 10, 0, 0, false, Topology::Torus, seed);
+            let bbbv = crate::cal_bbbv_with_topology(&board, Topology::Torus);
+            assert_eq!(cal_nf_zini_with_topology(&board, Topology::Torus), bbbv);
+            let ops = cal_zini_ops_with_topology(&board, Topology::Torus);
+            assert!(ops.len() <= bbbv);
+            let mut mb = crate::MinesweeperBoard::<Vec<Vec<i32>>>::new(board.clone());
+            mb.set_topology(Topology::Torus);
+            mb.step_flow(&zini_ops_to_step_flow(&ops)).unwrap();
+            assert_eq!(mb.game_board_state, crate::GameBoardState::Win);
+            assert_eq!(mb.lce + mb.rce + mb.dce, ops.len());
+        }
+    }
+
+    #[test]
+    fn test_partial_zini() {
+        for seed in 0..10u64 {
+            let board = crate::laymine_seeded(16, 30, 99, 0, 0, seed);
+            let route = ZiniRoute::new(&board, Topology::Plane);
+            assert_eq!(route.solved(&board), cal_zini(&board));
+            let covered = vec![vec![10; 30]; 16];
+            assert_eq!(route.solved(&covered), 0);
+            // 按路线走到一半时，局部的ZiNi不超过已走的步数
+            let ops = cal_zini_ops(&board);
+            let half = ops[..ops.len() / 2].to_vec();
+            let mut mb = crate::MinesweeperBoard::<Vec<Vec<i32>>>::new(board.clone());
+            mb.step_flow(&zini_ops_to_step_flow(&half)).unwrap();
+            assert!(route.solved(&mb.game_board) <= half.len());
+            assert!(route.solved(&mb.game_board) > 0);
+        }
+    }
+
+    #[test]
+    fn test_ops_replay() {
+        for seed in 0..10u64 {
+            let board = crate::laymine_seeded(16, 30, 99, 0, 0, seed);
+            for ops in [cal_zini_ops(&board), cal_hzini_ops(&board), cal_rzini_ops(&board, 5)] {
+                let mut mb = crate::MinesweeperBoard::<Vec<Vec<i32>>>::new(board.clone());
+                mb.step_flow(&zini_ops_to_step_flow(&ops)).unwrap();
+                assert_eq!(mb.game_board_state, crate::GameBoardState::Win);
+                assert_eq!(mb.lce + mb.rce + mb.dce, ops.len());
+            }
+            assert_eq!(cal_zini_ops(&board).len(), cal_zini(&board));
+            assert_eq!(cal_hzini_ops(&board).len(), cal_hzini(&board));
+        }
+    }
+
     #[test]
     fn test_big_board() {
         let board = laymine(300, 100, 6000, 0, 0);
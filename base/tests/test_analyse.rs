@@ -2,8 +2,10 @@
 use ms_toollib::videos::base_video::NewBaseVideo2;
 use ms_toollib::videos::NewSomeVideo;
 use ms_toollib::{
-    AvfVideo, BaseVideo, Event, EvfVideo, GameBoardState, MinesweeperBoard, MvfVideo,
-    RmvVideo, SafeBoard,
+    cal_bbbv_with_topology, cal_nf_zini, cal_nf_zini_ops, cal_solution_complexity,
+    cal_zini_ops_with_topology, cal_zini_with_topology, laymine_with_topology_seeded,
+    zini_ops_to_step_flow, AvfVideo, BaseVideo, Event, EvfVideo, FirstClickPolicy, GameBoardState,
+    MinesweeperBoard, MvfVideo, RmvVideo, SafeBoard, Topology,
 };
 use std::thread;
 use std::time::Duration;
@@ -1534,6 +1536,29 @@ fn base_video_works_5_1bv() {
     println!("时间毫秒：{:?}", video.get_bbbv_s());
 }
 
+#[test]
+fn base_video_solution_complexity_works() {
+    let board = vec![
+        vec![0, 0, 0, 0, 0, 0, 0, 0],
+        vec![0, 0, 0, 0, 0, 0, 0, 0],
+        vec![0, 0, 0, 0, 0, 0, 0, 0],
+        vec![0, 0, 0, 0, 0, 0, 0, 0],
+        vec![0, 0, 0, 0, 0, 0, 0, 0],
+        vec![0, 0, 0, 0, 0, 0, 0, 0],
+        vec![0, 0, 0, 0, 0, 0, 1, 1],
+        vec![0, 0, 0, 0, 0, 0, 1, -1],
+    ];
+    let mut video = BaseVideo::<SafeBoard>::new(board.clone(), 16);
+    // 还没有第一下时，不知道起手位置
+    assert!(video.get_solution_complexity().is_err());
+    video.step("lc", (32, 49)).unwrap();
+    video.step("lr", (32, 49)).unwrap();
+    assert_eq!(
+        video.get_solution_complexity().unwrap(),
+        cal_solution_complexity(&board, 2, 3)
+    );
+}
+
 #[test]
 fn base_video_works_6_fail() {
     let board = vec![
@@ -1935,3 +1960,231 @@ fn temp_rmv_video_works() {
 
 
 
+
+#[test]
+fn first_click_policy_safe_cell_corner_works() {
+    let board = vec![vec![0, 0, 0], vec![1, 1, 1], vec![1, -1, 1]];
+    let mut my_board = MinesweeperBoard::<Vec<Vec<i32>>>::new(board);
+    my_board.set_first_click_policy(FirstClickPolicy::SafeCellCorner, 0);
+    my_board
+        .step_flow(&vec![("lc".to_string(), (2, 1)), ("lr".to_string(), (2, 1))])
+        .unwrap();
+    assert_eq!(
+        my_board.board,
+        vec![vec![-1, 1, 0], vec![1, 1, 0], vec![0, 0, 0]]
+    );
+    assert_eq!(my_board.game_board_state, GameBoardState::Win);
+}
+
+#[test]
+fn first_click_policy_safe_opening_works() {
+    let board = vec![
+        vec![-1, 2, 1, 0, 0],
+        vec![2, -1, 1, 0, 0],
+        vec![1, 1, 1, 0, 0],
+        vec![0, 0, 0, 1, 1],
+        vec![0, 0, 0, 1, -1],
+    ];
+    let mut boards = vec![];
+    for _ in 0..2 {
+        let mut my_board = MinesweeperBoard::<Vec<Vec<i32>>>::new(board.clone());
+        my_board.set_first_click_policy(FirstClickPolicy::SafeOpening, 20240101);
+        my_board
+            .step_flow(&vec![("lc".to_string(), (1, 1)), ("lr".to_string(), (1, 1))])
+            .unwrap();
+        assert_eq!(my_board.game_board[1][1], 0);
+        let mines: Vec<(usize, usize)> = (0..5)
+            .flat_map(|i| (0..5).map(move |j| (i, j)))
+            .filter(|&(i, j)| my_board.board[i][j] == -1)
+            .collect();
+        assert_eq!(mines.len(), 3);
+        assert!(mines.iter().all(|&(i, j)| i > 2 || j > 2));
+        boards.push(my_board.board);
+    }
+    assert_eq!(boards[0], boards[1]);
+}
+
+#[test]
+fn first_click_policy_safe_opening_dense_board_works() {
+    // 3×3范围内有8个雷，范围外只有2个空位，退而只保证第一下不是雷
+    let board = vec![
+        vec![-1, -1, -1, -1],
+        vec![-1, -1, -1, 3],
+        vec![-1, -1, 3, 1],
+    ];
+    let mut my_board = MinesweeperBoard::<Vec<Vec<i32>>>::new(board);
+    my_board.set_first_click_policy(FirstClickPolicy::SafeOpening, 7);
+    my_board
+        .step_flow(&vec![("lc".to_string(), (1, 1)), ("lr".to_string(), (1, 1))])
+        .unwrap();
+    assert_ne!(my_board.game_board_state, GameBoardState::Loss);
+    assert!(my_board.board[1][1] >= 0);
+    assert_eq!(my_board.game_board[1][1], my_board.board[1][1]);
+    let mines = my_board.board.iter().flatten().filter(|&&c| c == -1).count();
+    assert_eq!(mines, 9);
+}
+
+#[test]
+fn first_click_policy_base_video_records_final_board() {
+    let board = vec![
+        vec![0, 0, 0, 0],
+        vec![0, 1, 1, 1],
+        vec![0, 1, -1, 1],
+        vec![0, 1, 1, 1],
+    ];
+    let mut video = BaseVideo::<SafeBoard>::new(board, 16);
+    video
+        .set_first_click_policy(FirstClickPolicy::SafeCellCorner, 0)
+        .unwrap();
+    video.step("lc", (40, 40)).unwrap();
+    video.step("lr", (40, 40)).unwrap();
+    assert_eq!(video.game_board_state, GameBoardState::Win);
+    let moved = vec![
+        vec![-1, 1, 0, 0],
+        vec![1, 1, 0, 0],
+        vec![0, 0, 0, 0],
+        vec![0, 0, 0, 0],
+    ];
+    assert_eq!(video.board.into_vec_vec(), moved);
+    assert_eq!(video.minesweeper_board.board.into_vec_vec(), moved);
+    assert!(video
+        .set_first_click_policy(FirstClickPolicy::None, 0)
+        .is_err());
+}
+
+#[test]
+fn zini_efficiency_works() {
+    let mut video =
+        AvfVideo::new("../test_files/HI-SCORE Exp_49.25_3BV=127_3BVs=2.57_Wang Jianing G01825.avf");
+    video.parse().unwrap();
+    video.data.analyse();
+    // 扫完的录像，局部的ZiNi等于ZiNi
+    assert_eq!(video.data.static_params.zini, 96);
+    assert_eq!(video.data.video_dynamic_params.zini_solved, 96);
+    let cl = video.data.game_dynamic_params.cl;
+    assert_eq!(video.data.video_dynamic_params.zioe, 96.0 / cl as f64);
+    // 播放到中途，局部的ZiNi随已打开的格子增加而增加
+    let mut last = 0;
+    for t in [5.0, 10.0, 20.0, 40.0, 60.0] {
+        video.data.set_current_time(t);
+        let zini_solved = video.data.get_zini_solved().unwrap();
+        assert!(zini_solved >= last);
+        last = zini_solved;
+    }
+    assert_eq!(last, 96);
+    assert_eq!(video.data.get_zioe().unwrap(), 96.0 / cl as f64);
+
+    // 踩雷的对局，只计算已打开的部分
+    let board = vec![
+        vec![1, 1, 2, 1, 1, 0, 0, 0],
+        vec![1, -1, 2, -1, 1, 0, 0, 0],
+        vec![1, 1, 2, 1, 1, 0, 0, 0],
+        vec![0, 0, 0, 0, 0, 0, 0, 0],
+        vec![2, 2, 1, 0, 0, 0, 0, 0],
+        vec![-1, -1, 2, 0, 0, 1, 1, 1],
+        vec![-1, -1, 3, 0, 0, 2, -1, 2],
+        vec![-1, -1, 2, 0, 0, 2, -1, 2],
+    ];
+    let mut video = BaseVideo::<SafeBoard>::new(board, 16);
+    _sleep_ms(60);
+    video.step("lc", (56, 56)).unwrap();
+    video.step("lr", (56, 56)).unwrap();
+    video.step("lc", (17, 16)).unwrap();
+    video.step("lr", (17, 16)).unwrap();
+    assert_eq!(video.game_board_state, GameBoardState::Loss);
+    assert_eq!(video.get_zini_solved().unwrap(), 1);
+    assert_eq!(video.get_zioe().unwrap(), 0.5);
+}
+
+#[test]
+fn nf_zini_efficiency_works() {
+    let board = vec![
+        vec![1, 1, 2, 1, 1, 0, 0, 0],
+        vec![1, -1, 2, -1, 1, 0, 0, 0],
+        vec![1, 1, 2, 1, 1, 0, 0, 0],
+        vec![0, 0, 0, 0, 0, 0, 0, 0],
+        vec![2, 2, 1, 0, 0, 0, 0, 0],
+        vec![-1, -1, 2, 0, 0, 1, 1, 1],
+        vec![-1, -1, 3, 0, 0, 2, -1, 2],
+        vec![-1, -1, 2, 0, 0, 2, -1, 2],
+    ];
+    let ops = cal_nf_zini_ops(&board);
+    assert_eq!(ops.len(), cal_nf_zini(&board));
+    let mut video = BaseVideo::<SafeBoard>::new(board, 16);
+    _sleep_ms(60);
+    for (_, (x, y)) in &ops {
+        video.step("lc", (x * 16 + 8, y * 16 + 8)).unwrap();
+        video.step("lr", (x * 16 + 8, y * 16 + 8)).unwrap();
+    }
+    assert_eq!(video.game_board_state, GameBoardState::Win);
+    // 没有标雷，按NF ZiNi计算效率
+    assert_eq!(video.static_params.nf_zini, ops.len());
+    assert_eq!(video.get_zini_solved().unwrap(), ops.len());
+    assert_eq!(video.get_zioe().unwrap(), 1.0);
+}
+
+#[test]
+fn torus_zini_efficiency_works() {
+    let board = laymine_with_topology_seeded(8, 8, 10, 0, 0, false, Topology::Torus, 0);
+    let ops = cal_zini_ops_with_topology(&board, Topology::Torus);
+    let mut video = BaseVideo::<SafeBoard>::new(board.clone(), 16);
+    video.set_topology(Topology::Torus).unwrap();
+    _sleep_ms(60);
+    for (e, (x, y)) in zini_ops_to_step_flow(&ops) {
+        video.step(&e, (x * 16 + 8, y * 16 + 8)).unwrap();
+    }
+    assert_eq!(video.game_board_state, GameBoardState::Win);
+    // ZiNi与3BV都按环面计算
+    assert_eq!(video.static_params.bbbv, cal_bbbv_with_topology(&board, Topology::Torus));
+    assert_eq!(video.static_params.zini, cal_zini_with_topology(&board, Topology::Torus));
+    assert_eq!(video.get_zini_solved().unwrap(), video.static_params.zini);
+    assert_eq!(video.get_zioe().unwrap(), 1.0);
+}
+
+#[test]
+fn slow_pattern_works() {
+    let board = vec![
+        vec![0, 0, 0, 0, 0],
+        vec![1, 1, 2, 1, 1],
+        vec![1, -1, 2, -1, 1],
+    ];
+    let mut video = BaseVideo::<Vec<Vec<i32>>>::new(board, 16);
+    _sleep_ms(60);
+    video.step("lc", (8, 8)).unwrap();
+    video.step("lr", (8, 8)).unwrap();
+    // 1-2-1和两个1-1出现后停顿，再一次解决
+    _sleep_ms(1100);
+    video.step("lc", (40, 40)).unwrap();
+    video.step("lr", (40, 40)).unwrap();
+    video.step("lc", (40, 8)).unwrap();
+    video.step("lr", (40, 8)).unwrap();
+    video.analyse_for_features(&vec!["slow_pattern"]);
+    let comments: Vec<_> = video
+        .video_action_state_recorder
+        .iter()
+        .filter(|v| !v.comments.is_empty())
+        .map(|v| v.comments.clone())
+        .collect();
+    assert_eq!(comments.len(), 1);
+    assert!(comments[0].starts_with("warning:slow_pattern:1-1:"));
+    assert!(comments[0].contains("warning:slow_pattern:1-2-1:"));
+    assert_eq!(comments[0].matches("slow_pattern").count(), 3);
+
+    let mut video =
+        AvfVideo::new("../test_files/HI-SCORE Exp_49.25_3BV=127_3BVs=2.57_Wang Jianing G01825.avf");
+    video.parse().unwrap();
+    video.data.analyse();
+    video.data.analyse_for_features(&vec!["slow_pattern"]);
+    // 每条记录的用时超过1秒，且不超过定式出现以来经过的时间
+    let mut warnings = 0;
+    for v in &video.data.video_action_state_recorder {
+        for comment in v.comments.split(';').filter(|c| !c.is_empty()) {
+            let fields: Vec<_> = comment.split(':').collect();
+            assert_eq!(fields[..2], ["warning", "slow_pattern"]);
+            let used_time: f64 = fields[3].parse().unwrap();
+            assert!(used_time > 1.0 && used_time <= v.time + 1e-3);
+            warnings += 1;
+        }
+    }
+    assert!(warnings > 0);
+}
@@ -1,8 +1,15 @@
 // use ms_toollib::refresh_matrixs;
 #[cfg(any(feature = "py", feature = "rs"))]
-use ms_toollib::laymine_solvable_thread;
 use ms_toollib::{
-    is_solvable, laymine, laymine_op, laymine_solvable, laymine_solvable_adjust, try_solve,
+    cal_board_percentile, get_board_histograms, laymine_filtered, laymine_filtered_seeded,
+    laymine_solvable_thread, laymine_solvable_thread_seeded, laymine_solvable_thread_with_control,
+    sample_bbbvs_exp_seeded, sample_bbbvs_exp_with_control, sample_board_histograms,
+    set_board_histograms, Board, BoardFilter, BoardHistograms, ThreadControl, Topology,
+};
+use ms_toollib::{
+    is_solvable, laymine, laymine_op, laymine_op_seeded, laymine_seeded, laymine_solvable,
+    laymine_solvable_adjust, laymine_solvable_adjust_seeded, laymine_solvable_seeded, try_solve,
+    SeededRng,
 };
 use std::time::Instant; // timer
 
@@ -117,3 +124,223 @@ fn laymine_solvable_adjust_high_density() {
     assert_eq!(start_safe, ITERATIONS, "起手位置不应为雷");
     assert_eq!(total_mines_correct, ITERATIONS, "总雷数必须等于传入值");
 }
+
+#[test]
+fn seeded_rng_works() {
+    // 固定的输出，其他平台的实现应当与之一致
+    let mut rng = SeededRng::new(0);
+    let v: Vec<u64> = (0..3).map(|_| rng.next_u64()).collect();
+    assert_eq!(
+        v,
+        vec![11091344671253066420, 13793997310169335082, 1900383378846508768]
+    );
+    let mut rng = SeededRng::new(20240101);
+    let mut a: Vec<usize> = (0..10).collect();
+    rng.shuffle(&mut a);
+    assert_eq!(a, vec![6, 5, 2, 8, 7, 3, 9, 0, 1, 4]);
+    assert_ne!(SeededRng::new_stream(7, 0).next_u64(), SeededRng::new_stream(7, 1).next_u64());
+}
+
+#[test]
+fn laymine_seeded_works() {
+    // 相同的种子生成相同的局面，不同的种子（几乎总是）生成不同的局面
+    let board = laymine_seeded(16, 30, 99, 3, 20, 42);
+    assert_eq!(board, laymine_seeded(16, 30, 99, 3, 20, 42));
+    assert_ne!(board, laymine_seeded(16, 30, 99, 3, 20, 43));
+    assert_eq!(board.iter().flatten().filter(|&&v| v == -1).count(), 99);
+    assert!(board[3][20] >= 0);
+    let board = laymine_op_seeded(16, 30, 99, 3, 20, 42);
+    assert_eq!(board, laymine_op_seeded(16, 30, 99, 3, 20, 42));
+    assert_eq!(board[3][20], 0);
+    let board = laymine_seeded(3, 3, 2, 0, 0, 5);
+    assert_eq!(board, vec![vec![1, 2, -1], vec![1, -1, 2], vec![1, 1, 1]]);
+}
+
+#[test]
+fn laymine_solvable_seeded_works() {
+    let (board, flag) = laymine_solvable_seeded(8, 8, 10, 0, 0, 10000, 2024);
+    assert!(flag);
+    assert!(is_solvable(&board, 0, 0));
+    assert_eq!((board.clone(), flag), laymine_solvable_seeded(8, 8, 10, 0, 0, 10000, 2024));
+    #[cfg(any(feature = "py", feature = "rs"))]
+    for seed in 0..5 {
+        // 多线程的结果与单线程完全相同
+        assert_eq!(
+            laymine_solvable_thread_seeded(16, 30, 99, 0, 0, 100000, seed),
+            laymine_solvable_seeded(16, 30, 99, 0, 0, 100000, seed)
+        );
+    }
+    let (board, flag) = laymine_solvable_adjust_seeded(16, 30, 150, 3, 20, 7);
+    assert_eq!((board, flag), laymine_solvable_adjust_seeded(16, 30, 150, 3, 20, 7));
+}
+
+#[test]
+#[cfg(any(feature = "py", feature = "rs"))]
+fn laymine_filtered_works() {
+    let filter = BoardFilter {
+        bbbv: Some((20, 30)),
+        op: Some((0, 3)),
+        cells: [None, None, None, Some((0, 0)), None, None, None, None, None],
+        ..BoardFilter::default()
+    };
+    let (board, flag, rejected) = laymine_filtered_seeded(9, 9, 10, 4, 4, false, &filter, 100000, 3);
+    assert!(flag);
+    let mut b = Board::new(board.clone());
+    assert!((20..=30).contains(&b.get_bbbv()));
+    assert!(b.get_op() <= 3);
+    assert_eq!(b.get_cell3(), 0);
+    assert!(board[4][4] >= 0);
+    // 被拒绝的局面数就是成功的尝试的序号，前面的尝试都不满足条件
+    let (_, flag_less, rejected_less) =
+        laymine_filtered_seeded(9, 9, 10, 4, 4, false, &filter, rejected, 3);
+    assert!(!flag_less);
+    assert_eq!(rejected_less, rejected);
+    assert_eq!(
+        (board, flag, rejected),
+        laymine_filtered_seeded(9, 9, 10, 4, 4, false, &filter, 100000, 3)
+    );
+    // 无猜的条件与laymine_solvable_thread_seeded一致
+    let filter = BoardFilter {
+        solvable: Some(true),
+        ..BoardFilter::default()
+    };
+    let (board, flag, _) = laymine_filtered_seeded(16, 30, 99, 0, 0, true, &filter, 100000, 9);
+    assert_eq!(
+        (board, flag),
+        laymine_solvable_thread_seeded(16, 30, 99, 0, 0, 100000, 9)
+    );
+    // 不可能满足的条件
+    let filter = BoardFilter {
+        bbbv: Some((1000, 2000)),
+        ..BoardFilter::default()
+    };
+    let (_, flag, rejected) = laymine_filtered(9, 9, 10, 0, 0, true, &filter, 100);
+    assert!(!flag);
+    assert_eq!(rejected, 100);
+}
+
+#[test]
+#[cfg(any(feature = "py", feature = "rs"))]
+fn thread_control_works() {
+    // 结果与线程数无关
+    let expected = laymine_solvable_seeded(16, 30, 99, 0, 0, 100000, 11);
+    for thread_num in [1, 3, 8] {
+        let control = ThreadControl::new(thread_num);
+        let (board, flag, attempts) =
+            laymine_solvable_thread_with_control(16, 30, 99, 0, 0, 100000, 11, &control);
+        assert_eq!((board, flag), expected);
+        assert_eq!(control.get_progress(), attempts);
+        assert!(attempts >= 1);
+    }
+    // 单线程时，尝试次数就是成功的尝试的序号加一
+    let control = ThreadControl::new(1);
+    let (_, _, attempts) = laymine_solvable_thread_with_control(16, 30, 99, 0, 0, 100000, 11, &control);
+    let (_, flag) = laymine_solvable_seeded(16, 30, 99, 0, 0, attempts - 1, 11);
+    assert!(!flag);
+    // 取消
+    let control = ThreadControl::new(4);
+    control.cancel();
+    let (_, flag, attempts) =
+        laymine_solvable_thread_with_control(16, 30, 150, 0, 0, 100000, 11, &control);
+    assert!(!flag);
+    assert_eq!(attempts, 0);
+
+    let (a, n) = sample_bbbvs_exp_with_control(3, 3, 3000, 5, &ThreadControl::new(1));
+    let (b, m) = sample_bbbvs_exp_with_control(3, 3, 3000, 5, &ThreadControl::new(5));
+    assert_eq!(a, b);
+    assert_eq!((n, m), (3000, 3000));
+    assert_eq!(a.iter().sum::<usize>(), 3000);
+    assert_eq!(a, sample_bbbvs_exp_seeded(3, 3, 3000, 5));
+}
+
+#[test]
+#[cfg(any(feature = "py", feature = "rs"))]
+fn thread_control_reset_works() {
+    let control = ThreadControl::new(2);
+    control.cancel();
+    let (_, n) = sample_bbbvs_exp_with_control(3, 3, 3000, 5, &control);
+    assert_eq!(n, 0);
+    control.reset();
+    assert!(!control.is_cancelled());
+    assert_eq!(control.get_progress(), 0);
+    let (_, n) = sample_bbbvs_exp_with_control(3, 3, 3000, 5, &control);
+    assert_eq!(n, 3000);
+    assert_eq!(control.get_progress(), 3000);
+}
+
+#[test]
+#[cfg(any(feature = "py", feature = "rs"))]
+fn sample_board_histograms_works() {
+    let h = sample_board_histograms(9, 9, 10, 4, 4, false, 3000, 1, &ThreadControl::new(1));
+    assert_eq!(h.samples, 3000);
+    for histogram in [&h.bbbv, &h.op, &h.isl, &h.zini] {
+        assert_eq!(histogram.iter().sum::<usize>(), 3000);
+    }
+    // 结果与线程数无关
+    assert_eq!(
+        h,
+        sample_board_histograms(9, 9, 10, 4, 4, false, 3000, 1, &ThreadControl::new(4))
+    );
+    // ZiNi不超过3BV，且至少为1
+    let max_bbbv = h.bbbv.len() - 1;
+    assert!(h.zini.len() - 1 <= max_bbbv);
+    assert_eq!(h.zini[0], 0);
+    // 起手开空时，op至少为1
+    let h = sample_board_histograms(16, 30, 99, 3, 3, true, 1000, 2, &ThreadControl::new(4));
+    assert_eq!(h.op[0], 0);
+    // 只采样一局时，与相同种子的laymine_seeded的局面相同
+    let h = sample_board_histograms(16, 30, 99, 3, 3, false, 1, 2, &ThreadControl::new(4));
+    let mut board = Board::new(laymine_seeded(16, 30, 99, 3, 3, 2));
+    assert_eq!(h.bbbv.len(), board.get_bbbv() + 1);
+    assert_eq!(h.op.len(), board.get_op() + 1);
+    assert_eq!(h.isl.len(), board.get_isl() + 1);
+    assert_eq!(h.zini.len(), board.get_zini() + 1);
+}
+
+#[test]
+#[cfg(any(feature = "py", feature = "rs"))]
+fn board_percentile_works() {
+    // 预先设置的分布表：3BV为10、20、30的局面各1局，ZiNi为5、10、15的局面各1局
+    let mut h = BoardHistograms {
+        samples: 3,
+        bbbv: vec![0; 31],
+        zini: vec![0; 16],
+        ..BoardHistograms::default()
+    };
+    for v in [10, 20, 30] {
+        h.bbbv[v] += 1;
+    }
+    for v in [5, 10, 15] {
+        h.zini[v] += 1;
+    }
+    set_board_histograms(3, 3, 1, 0, 0, true, h);
+    let board = vec![vec![0, 0, 0], vec![0, 1, 1], vec![0, 1, -1]];
+    let mut b = Board::new(board.clone());
+    assert_eq!((b.get_bbbv(), b.get_zini()), (1, 1));
+    assert_eq!(cal_board_percentile(&board, 0, 0, true), Ok((0.0, 0.0)));
+    assert_eq!(b.get_difficulty(0, 0, true), Ok(0.0));
+    let histograms = get_board_histograms(3, 3, 1, 0, 0, true).unwrap();
+    assert_eq!(histograms.bbbv_percentile(20), 50.0);
+    assert!((histograms.bbbv_percentile(25) - 200.0 / 3.0).abs() < 1e-9);
+    assert_eq!(histograms.zini_percentile(100), 100.0);
+    // 起手位置不同的局面使用另一张分布表，没有设置时不会采样
+    assert!(get_board_histograms(3, 3, 1, 2, 2, true).is_none());
+    assert_eq!(cal_board_percentile(&board, 2, 2, true), Err(1));
+
+    // 先采样，再设置分布表
+    let board = laymine_seeded(5, 5, 5, 2, 2, 1);
+    assert_eq!(cal_board_percentile(&board, 2, 2, false), Err(1));
+    let h = sample_board_histograms(5, 5, 5, 2, 2, false, 2000, 0, &ThreadControl::new(2));
+    set_board_histograms(5, 5, 5, 2, 2, false, h);
+    let (p_bbbv, p_zini) = cal_board_percentile(&board, 2, 2, false).unwrap();
+    assert!((0.0..=100.0).contains(&p_bbbv));
+    assert!((0.0..=100.0).contains(&p_zini));
+    let histograms = get_board_histograms(5, 5, 5, 2, 2, false).unwrap();
+    assert_eq!(histograms.samples, 2000);
+    let mut b = Board::new(board.clone());
+    assert_eq!(p_bbbv, histograms.bbbv_percentile(b.get_bbbv()));
+    assert_eq!(b.get_difficulty(2, 2, false), Ok((p_bbbv + p_zini) / 2.0));
+    // 百分位只适用于平面局面
+    let mut b = Board::new_with_topology(board, Topology::Torus);
+    assert_eq!(b.get_percentiles(2, 2, false), Err(2));
+}
@@ -1,7 +1,15 @@
 use ms_toollib::{
-    cal_all_solution, cal_probability_cells_is_op, cal_probability_cells_not_mine,
+    cal_all_solution, cal_mine_count_distribution, cal_solutions_count_exact, check_for_unavoidable_5050, check_for_unavoidable_5050_or_pseudo,
+    check_for_unavoidable_guess, cal_probability_cells_is_op, cal_probability_cells_not_mine,
     cal_probability_csp, cal_probability_enum, cal_probability_onboard, mark_board,
+    suggest_moves, SolverSession, cal_board_numbers, GameBoard,
+    cal_probability_onboard_with_topology, laymine_with_topology_seeded, Topology,
+    laymine_op_seeded, refresh_board, SeededRng,
 };
+use std::sync::{Arc, Mutex};
+use std::thread;
+use num_bigint::BigUint;
+use num_traits::ToPrimitive;
 
 #[test]
 fn board_1_works() {
@@ -580,3 +588,304 @@ fn csp_prob_gt_1() {
         );
     }
 }
+
+#[test]
+fn suggest_moves_works() {
+    let game_board = vec![
+        vec![10, 10, 10, 10, 10, 10, 10, 10],
+        vec![10, 10, 10, 10, 10, 10, 10, 10],
+        vec![10, 10, 10, 1, 10, 10, 10, 10],
+        vec![10, 10, 10, 2, 10, 10, 10, 10],
+        vec![10, 10, 10, 10, 10, 10, 10, 10],
+        vec![10, 10, 10, 10, 10, 10, 10, 10],
+        vec![10, 10, 10, 10, 10, 10, 10, 10],
+        vec![10, 10, 10, 10, 10, 10, 10, 10],
+    ];
+    let moves = suggest_moves(&game_board, 10.0).unwrap();
+    // 1上方的三格最安全，其中两侧的格子点开后更容易取得进展
+    let poses: Vec<_> = moves.iter().map(|m| m.pos).collect();
+    assert_eq!(poses, vec![(1, 2), (1, 4), (1, 3)]);
+    assert!(moves.iter().all(|m| !m.dead));
+    assert!((moves[0].progress - moves[1].progress).abs() < 1e-9);
+    assert!(moves[1].progress > moves[2].progress);
+    let mut marked_board = game_board.clone();
+    mark_board(&mut marked_board, true).unwrap();
+    let (poss, _) = cal_probability_onboard(&marked_board, 10.0).unwrap();
+    for m in &moves {
+        assert!((1.0 - poss[m.pos.0][m.pos.1] - m.safety).abs() < 1e-9);
+        assert!(m.progress >= 0.0 && m.progress <= m.safety + 1e-9);
+    }
+    // 排名靠前的非死格，安全率不低于排名靠后的非死格
+    let living: Vec<_> = moves.iter().filter(|m| !m.dead).collect();
+    for w in living.windows(2) {
+        assert!(w[0].safety >= w[1].safety);
+    }
+}
+
+#[test]
+fn suggest_moves_safe_cells_works() {
+    // 唯一的雷只能在(1, 2)处，右侧另外两格必然安全
+    let game_board = vec![
+        vec![0, 1, 10],
+        vec![0, 1, 10],
+        vec![0, 1, 10],
+    ];
+    let moves = suggest_moves(&game_board, 1.0).unwrap();
+    assert!(moves.iter().all(|m| m.safety == 1.0 && m.progress == 1.0));
+    let poses: Vec<_> = moves.iter().map(|m| m.pos).collect();
+    assert_eq!(poses, vec![(0, 2), (2, 2)]);
+}
+
+#[test]
+fn suggest_moves_dead_works() {
+    // 底部两格为50/50，无论点开哪一格都不会得到新信息
+    let game_board = vec![vec![0, 0], vec![1, 1], vec![10, 10]];
+    let moves = suggest_moves(&game_board, 1.0).unwrap();
+    let poses: Vec<_> = moves.iter().map(|m| m.pos).collect();
+    assert_eq!(poses, vec![(2, 0), (2, 1)]);
+    assert!(moves
+        .iter()
+        .all(|m| (m.safety - 0.5).abs() < 1e-9 && m.dead && m.progress == 0.0));
+}
+
+#[test]
+fn check_for_unavoidable_5050_works() {
+    // 底部两格构成50/50
+    let game_board = vec![vec![0, 0], vec![1, 1], vec![10, 10]];
+    let tiles = check_for_unavoidable_5050(&game_board, 1.0).unwrap().unwrap();
+    assert_eq!(tiles.len(), 2);
+    assert!(tiles.contains(&(2, 0)) && tiles.contains(&(2, 1)));
+    let tiles = check_for_unavoidable_5050_or_pseudo(&game_board, 1.0).unwrap().unwrap();
+    assert_eq!(tiles.len(), 2);
+    let tiles = check_for_unavoidable_guess(&game_board, 1.0).unwrap().unwrap();
+    assert_eq!(tiles.len(), 2);
+    // 还可以通过点开其他格子获得信息
+    let game_board = vec![
+        vec![10, 10, 10, 10, 10, 10, 10, 10],
+        vec![10, 10, 10, 10, 10, 10, 10, 10],
+        vec![10, 10, 10, 1, 10, 10, 10, 10],
+        vec![10, 10, 10, 2, 10, 10, 10, 10],
+        vec![10, 10, 10, 10, 10, 10, 10, 10],
+        vec![10, 10, 10, 10, 10, 10, 10, 10],
+        vec![10, 10, 10, 10, 10, 10, 10, 10],
+        vec![10, 10, 10, 10, 10, 10, 10, 10],
+    ];
+    assert_eq!(check_for_unavoidable_5050(&game_board, 10.0).unwrap(), None);
+    assert_eq!(check_for_unavoidable_guess(&game_board, 10.0).unwrap(), None);
+}
+
+#[test]
+fn cal_solutions_count_exact_works() {
+    let game_board = vec![
+        vec![10, 10, 1, 1, 10, 1, 0, 0],
+        vec![10, 10, 1, 10, 10, 3, 2, 1],
+        vec![10, 10, 10, 10, 10, 10, 10, 10],
+        vec![10, 10, 10, 10, 10, 10, 10, 10],
+        vec![10, 10, 10, 10, 10, 10, 10, 10],
+        vec![10, 10, 10, 10, 2, 10, 10, 10],
+        vec![10, 10, 10, 10, 10, 10, 10, 10],
+        vec![10, 10, 10, 10, 10, 10, 10, 10],
+    ];
+    let (solutions, mine_counts) = cal_solutions_count_exact(&game_board, 10.0).unwrap();
+    let (probs, _) = cal_probability_onboard(&game_board, 10.0).unwrap();
+    // 每一种排布中恰好有10个雷
+    let total: BigUint = mine_counts.iter().flatten().sum();
+    assert_eq!(total, &solutions * BigUint::from(10u32));
+    for r in 0..8 {
+        for c in 0..8 {
+            let p = mine_counts[r][c].to_f64().unwrap() / solutions.to_f64().unwrap();
+            assert!((p - probs[r][c]).abs() < 1e-9);
+        }
+    }
+    // 没有数字的局面，退化为组合数
+    let game_board = vec![vec![10; 4]; 2];
+    let (solutions, mine_counts) = cal_solutions_count_exact(&game_board, 3.0).unwrap();
+    assert_eq!(solutions, BigUint::from(56u32));
+    assert_eq!(mine_counts[1][3], BigUint::from(21u32));
+    // 标雷的格子在所有排布中都是雷
+    let game_board = vec![vec![11, 1, 0], vec![1, 1, 0]];
+    let (solutions, mine_counts) = cal_solutions_count_exact(&game_board, 1.0).unwrap();
+    assert_eq!(solutions, BigUint::from(1u32));
+    assert_eq!(mine_counts[0][0], BigUint::from(1u32));
+    assert_eq!(mine_counts[0][1], BigUint::from(0u32));
+}
+
+#[test]
+fn cal_mine_count_distribution_works() {
+    let game_board = vec![
+        vec![10, 10, 1, 1, 10, 1, 0, 0],
+        vec![10, 10, 1, 10, 10, 3, 2, 1],
+        vec![10, 10, 10, 10, 10, 10, 10, 10],
+        vec![10, 10, 10, 10, 10, 10, 10, 10],
+        vec![10, 10, 10, 10, 10, 10, 10, 10],
+        vec![10, 10, 10, 10, 2, 10, 10, 10],
+        vec![10, 10, 10, 10, 10, 10, 10, 10],
+        vec![10, 10, 10, 10, 10, 10, 10, 10],
+    ];
+    let (edge, off_edge) = cal_mine_count_distribution(&game_board, 10.0).unwrap();
+    assert!((edge.iter().map(|d| d.1).sum::<f64>() - 1.0).abs() < 1e-9);
+    assert_eq!(edge.len(), off_edge.len());
+    for (e, o) in edge.iter().zip(off_edge.iter().rev()) {
+        assert_eq!(e.0 + o.0, 10);
+        assert!((e.1 - o.1).abs() < 1e-12);
+    }
+    // 内部雷数的期望与内部格子的概率一致
+    let (_, mine_prob, _, _) = cal_probability_csp(&game_board, 10.0).unwrap();
+    let off_edge_tiles = game_board.iter().flatten().filter(|&&v| v == 10).count() - 20;
+    let expect: f64 = off_edge.iter().map(|d| d.0 as f64 * d.1).sum();
+    assert!((expect / off_edge_tiles as f64 - mine_prob).abs() < 1e-9);
+    // 没有数字的局面
+    let game_board = vec![vec![10; 4]; 2];
+    let (edge, off_edge) = cal_mine_count_distribution(&game_board, 3.0).unwrap();
+    assert_eq!(edge, vec![(0, 1.0)]);
+    assert_eq!(off_edge, vec![(3, 1.0)]);
+}
+
+#[test]
+fn solver_session_works() {
+    let mut board = vec![
+        vec![0, 0, 0, 0, 0, 0, 0, -1],
+        vec![-1, 0, 0, 0, 0, 0, 0, 0],
+        vec![0, 0, 0, 0, -1, 0, 0, 0],
+        vec![0, 0, 0, 0, 0, 0, 0, -1],
+        vec![0, -1, 0, 0, 0, 0, 0, 0],
+        vec![0, 0, 0, 0, 0, -1, 0, 0],
+        vec![-1, 0, 0, 0, 0, 0, 0, -1],
+        vec![0, 0, 0, -1, 0, 0, 0, 0],
+    ];
+    cal_board_numbers(&mut board);
+    let mut session = SolverSession::new(8, 8, 10.0);
+    let mut game_board = vec![vec![10; 8]; 8];
+    let mut step = 0;
+    // 逐个打开非雷的格子，与重新计算的结果完全一致
+    for r in 0..8 {
+        for c in 0..8 {
+            if board[r][c] < 0 {
+                continue;
+            }
+            game_board[r][c] = board[r][c];
+            session.reveal((r, c), board[r][c]);
+            step += 1;
+            if step % 3 != 0 {
+                continue;
+            }
+            let (expected, expected_range) = cal_probability_onboard(&game_board, 10.0).unwrap();
+            assert_eq!(session.get_poss().unwrap(), &expected);
+            assert_eq!(session.get_mine_range().unwrap(), expected_range);
+        }
+    }
+    session.get_poss().unwrap();
+    let solve_count = session.get_block_solve_count();
+    // 局面恢复原状后，不需要重新枚举
+    session.flag((0, 7));
+    session.unflag((0, 7));
+    session.get_poss().unwrap();
+    assert_eq!(session.get_block_solve_count(), solve_count);
+    // 只修改一个区块时，只有该区块需要重新枚举
+    let mut session = SolverSession::new(3, 7, 2.0);
+    session.set_game_board(&vec![
+        vec![10, 1, 0, 0, 0, 1, 10],
+        vec![10, 1, 0, 0, 0, 1, 10],
+        vec![10, 10, 10, 10, 10, 10, 10],
+    ]);
+    session.get_poss().unwrap();
+    let solve_count = session.get_block_solve_count();
+    session.reveal((2, 6), 1);
+    session.get_poss().unwrap();
+    assert_eq!(session.get_block_solve_count(), solve_count + 1);
+}
+
+#[test]
+fn solver_session_matches_onboard_on_expert_states() {
+    // 在高级局面上，沿着已打开的区域随机地打开、标雷、取消标雷，每一步都与重新计算的结果完全一致
+    for seed in 0..4u64 {
+        let board = laymine_op_seeded(16, 30, 99, 8, 15, seed);
+        let mut rng = SeededRng::new(seed);
+        let mut game_board = vec![vec![10; 30]; 16];
+        refresh_board(&board, &mut game_board, vec![(8, 15)]);
+        let mut session = SolverSession::new(16, 30, 99.0);
+        session.set_game_board(&game_board);
+        for _ in 0..100 {
+            // 周围有已打开的格子的未打开的格子
+            let frontier: Vec<(usize, usize)> = (0..16)
+                .flat_map(|i| (0..30).map(move |j| (i, j)))
+                .filter(|&(i, j)| {
+                    game_board[i][j] == 10
+                        && (i.max(1) - 1..(i + 2).min(16))
+                            .any(|m| (j.max(1) - 1..(j + 2).min(30)).any(|n| game_board[m][n] < 10))
+                })
+                .collect();
+            let (mines, safe): (Vec<_>, Vec<_>) =
+                frontier.into_iter().partition(|&(i, j)| board[i][j] == -1);
+            if safe.is_empty() {
+                break;
+            }
+            let flagged: Vec<(usize, usize)> = (0..16)
+                .flat_map(|i| (0..30).map(move |j| (i, j)))
+                .filter(|&(i, j)| game_board[i][j] == 11)
+                .collect();
+            let action = rng.gen_below(10);
+            if action == 0 && !flagged.is_empty() {
+                let (i, j) = flagged[rng.gen_below(flagged.len())];
+                game_board[i][j] = 10;
+                session.unflag((i, j));
+            } else if action < 3 && !mines.is_empty() {
+                let (i, j) = mines[rng.gen_below(mines.len())];
+                game_board[i][j] = 11;
+                session.flag((i, j));
+            } else {
+                let (i, j) = safe[rng.gen_below(safe.len())];
+                refresh_board(&board, &mut game_board, vec![(i, j)]);
+                session.set_game_board(&game_board);
+            }
+            let (expected, expected_range) = cal_probability_onboard(&game_board, 99.0).unwrap();
+            assert_eq!(session.get_poss().unwrap(), &expected);
+            assert_eq!(session.get_mine_range().unwrap(), expected_range);
+        }
+    }
+}
+
+#[test]
+fn game_board_with_solver_session_works() {
+    let game_board = vec![
+        vec![10, 1, 0, 0, 0, 1, 10],
+        vec![10, 1, 0, 0, 0, 1, 10],
+        vec![10, 10, 10, 10, 10, 10, 10],
+    ];
+    let session = Arc::new(Mutex::new(SolverSession::new(3, 7, 2.0)));
+    let mut g_b = GameBoard::new(2);
+    g_b.set_game_board(&game_board);
+    g_b.set_solver_session(Arc::clone(&session));
+    // 共用会话的局面仍然可以传到其他线程中计算
+    let poss = thread::spawn(move || g_b.get_poss().clone()).join().unwrap();
+    assert_eq!(poss, cal_probability_onboard(&game_board, 2.0).unwrap().0);
+    assert!(session.lock().unwrap().get_block_solve_count() > 0);
+}
+
+#[test]
+fn solver_session_with_topology_works() {
+    let board = laymine_with_topology_seeded(8, 8, 10, 0, 0, true, Topology::Torus, 3);
+    let mut session = SolverSession::new_with_topology(8, 8, 10.0, Topology::Torus);
+    let mut game_board = vec![vec![10; 8]; 8];
+    for r in 0..8 {
+        for c in 0..8 {
+            if board[r][c] < 0 || (r + c) % 3 != 0 {
+                continue;
+            }
+            game_board[r][c] = board[r][c];
+            session.reveal((r, c), board[r][c]);
+            let expected =
+                cal_probability_onboard_with_topology(&game_board, 10.0, Topology::Torus).unwrap();
+            assert_eq!(session.get_poss().unwrap(), &expected.0);
+        }
+    }
+    // 平面局面的概率与环面的不同
+    let plane = cal_probability_onboard(&game_board, 10.0).unwrap();
+    assert_ne!(session.get_poss().unwrap(), &plane.0);
+    let mut g_b = GameBoard::new(10);
+    g_b.set_topology(Topology::Torus);
+    g_b.set_game_board(&game_board);
+    let expected =
+        cal_probability_onboard_with_topology(&game_board, 10.0, Topology::Torus).unwrap();
+    assert_eq!(g_b.get_poss(), &expected.0);
+}
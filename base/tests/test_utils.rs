@@ -1,4 +1,17 @@
-use ms_toollib::{cal_bbbv, cal_op};
+use ms_toollib::{
+    board_symmetries, cal_area, cal_bbbv, cal_bbbv_with_topology, cal_board_numbers,
+    cal_board_numbers_with_topology, cal_guess_stats_seeded, cal_isl, cal_isl_with_topology,
+    cal_op, cal_op_with_topology, cal_optimal_clicks, cal_probability_csp,
+    cal_probability_csp_with_topology, cal_probability_onboard, cal_solution_complexity,
+    cal_solution_complexity_with_topology, cal_zini,
+    canonical_board, canonical_board_hash, decode_board, decode_board_with_topology, encode_board,
+    encode_board_with_topology, find_patterns, get_covered_game_board, hint, is_solvable,
+    is_solvable_with_topology, laymine_masked_seeded, laymine_op_seeded, laymine_seeded,
+    laymine_solvable_seeded, laymine_with_topology_seeded, refresh_board,
+    refresh_board_with_topology, refresh_matrixs, solve_direct, solve_enumerate, solve_minus,
+    suggest_moves, try_solve_trace, zini_ops_to_step_flow, Board, GameBoardState, HintAction,
+    MinesweeperBoard, OptimalClicksLimits, SeededRng, SolutionComplexity, SolveRule, Topology,
+};
 
 #[test]
 fn cal_bbbv_works() {
@@ -17,3 +30,740 @@ fn cal_bbbv_works() {
     let op = cal_op(&game_board);
     print!("op: {:?}", op);
 }
+
+#[test]
+fn masked_board_metrics_works() {
+    // 中间的格子被遮挡
+    let mut board = vec![vec![-1, 0, 0], vec![0, -2, 0], vec![0, 0, 0]];
+    cal_board_numbers(&mut board);
+    assert_eq!(board, vec![vec![-1, 1, 0], vec![1, -2, 0], vec![0, 0, 0]]);
+    assert_eq!(cal_op(&board), 1);
+    assert_eq!(cal_isl(&board), 0);
+    assert_eq!(cal_bbbv(&board), 1);
+    assert_eq!(cal_area(&board), 8);
+    assert_eq!(
+        get_covered_game_board(&board),
+        vec![vec![10, 10, 10], vec![10, 19, 10], vec![10, 10, 10]]
+    );
+    assert!(is_solvable(&board, 2, 2));
+}
+
+#[test]
+fn laymine_masked_works() {
+    // 十字形的局面
+    let mut mask = vec![vec![false; 6]; 6];
+    for &(x, y) in &[(0, 0), (0, 5), (5, 0), (5, 5), (2, 2), (2, 3)] {
+        mask[x][y] = true;
+    }
+    let board = laymine_masked_seeded(&mask, 8, 3, 0, 7);
+    assert_eq!(board, laymine_masked_seeded(&mask, 8, 3, 0, 7));
+    assert_ne!(board[3][0], -1);
+    let mut mine_num = 0;
+    for i in 0..6 {
+        for j in 0..6 {
+            assert_eq!(board[i][j] == -2, mask[i][j]);
+            if board[i][j] == -1 {
+                mine_num += 1;
+            } else if board[i][j] >= 0 {
+                let mut n = 0;
+                for m in i.max(1) - 1..(i + 2).min(6) {
+                    for k in j.max(1) - 1..(j + 2).min(6) {
+                        if board[m][k] == -1 {
+                            n += 1;
+                        }
+                    }
+                }
+                assert_eq!(board[i][j], n);
+            }
+        }
+    }
+    assert_eq!(mine_num, 8);
+}
+
+#[test]
+fn masked_minesweeper_board_works() {
+    let board = vec![vec![-1, 1, 0], vec![1, -2, 0], vec![0, 0, 0]];
+    let mut my_board = MinesweeperBoard::<Vec<Vec<i32>>>::new(board);
+    assert_eq!(my_board.game_board[1][1], 19);
+    // 被遮挡的位置不能点击，也不能标雷
+    my_board
+        .step_flow(&vec![
+            ("rc".to_string(), (1, 1)),
+            ("rr".to_string(), (1, 1)),
+            ("lc".to_string(), (1, 1)),
+            ("lr".to_string(), (1, 1)),
+        ])
+        .unwrap();
+    assert_eq!(my_board.game_board[1][1], 19);
+    assert_eq!(my_board.game_board_state, GameBoardState::Ready);
+    my_board
+        .step_flow(&vec![
+            ("lc".to_string(), (2, 2)),
+            ("lr".to_string(), (2, 2)),
+        ])
+        .unwrap();
+    assert_eq!(my_board.game_board_state, GameBoardState::Win);
+    assert_eq!(
+        my_board.game_board,
+        vec![vec![10, 1, 0], vec![1, 19, 0], vec![0, 0, 0]]
+    );
+    my_board.reset();
+    assert_eq!(
+        my_board.game_board,
+        vec![vec![10, 10, 10], vec![10, 19, 10], vec![10, 10, 10]]
+    );
+}
+
+#[test]
+fn masked_probability_works() {
+    // 被遮挡的位置不是雷，也不提供信息
+    let game_board = vec![vec![10, 1, 10, 10, 10, 10], vec![19, 19, 19, 19, 19, 19]];
+    let (poss, range) = cal_probability_onboard(&game_board, 2.0).unwrap();
+    assert_eq!(range, [1, 2, 4]);
+    assert!((poss[0][0] - 0.5).abs() < 1e-9);
+    assert!((poss[0][4] - 1.0 / 3.0).abs() < 1e-9);
+    let (edge, inside, range, _) = cal_probability_csp(&game_board, 2.0).unwrap();
+    assert_eq!(range, [1, 2, 4]);
+    assert!((inside - 1.0 / 3.0).abs() < 1e-9);
+    for ((x, y), p) in edge {
+        assert!((poss[x][y] - p).abs() < 1e-9);
+    }
+    // 按比例给出的雷数只计算存在的格子
+    let (_, _, range, _) = cal_probability_csp(&game_board, 0.34).unwrap();
+    assert_eq!(range[1], 2);
+    let (_, range) = cal_probability_onboard(&game_board, 0.34).unwrap();
+    assert_eq!(range[1], 2);
+}
+
+#[test]
+fn torus_board_metrics_works() {
+    let mut board = vec![vec![0; 4]; 4];
+    board[0][0] = -1;
+    cal_board_numbers_with_topology(&mut board, Topology::Torus);
+    assert_eq!(
+        board,
+        vec![
+            vec![-1, 1, 0, 1],
+            vec![1, 1, 0, 1],
+            vec![0, 0, 0, 0],
+            vec![1, 1, 0, 1],
+        ]
+    );
+    // 3×3的环面上，所有格子都互相相邻
+    let mut board = vec![vec![0; 3]; 3];
+    board[0][0] = -1;
+    cal_board_numbers_with_topology(&mut board, Topology::Torus);
+    assert_eq!(board, vec![vec![-1, 1, 1], vec![1, 1, 1], vec![1, 1, 1]]);
+    assert_eq!(cal_op_with_topology(&board, Topology::Torus), 0);
+    assert_eq!(cal_isl_with_topology(&board, Topology::Torus), 1);
+    assert_eq!(cal_bbbv_with_topology(&board, Topology::Torus), 8);
+}
+
+#[test]
+fn plane_topology_matches_plane_functions() {
+    for seed in 0..20u64 {
+        let board = laymine_with_topology_seeded(16, 30, 99, 5, 7, false, Topology::Plane, seed);
+        assert_eq!(board, laymine_seeded(16, 30, 99, 5, 7, seed));
+        let board = laymine_with_topology_seeded(16, 30, 99, 0, 7, true, Topology::Plane, seed);
+        assert_eq!(board, laymine_op_seeded(16, 30, 99, 0, 7, seed));
+        assert_eq!(
+            cal_op_with_topology(&board, Topology::Plane),
+            cal_op(&board)
+        );
+        assert_eq!(
+            cal_isl_with_topology(&board, Topology::Plane),
+            cal_isl(&board)
+        );
+        assert_eq!(
+            cal_bbbv_with_topology(&board, Topology::Plane),
+            cal_bbbv(&board)
+        );
+        assert_eq!(
+            is_solvable_with_topology(&board, 0, 7, Topology::Plane),
+            is_solvable(&board, 0, 7)
+        );
+        let mut game_board = vec![vec![10; 30]; 16];
+        let mut game_board_plane = game_board.clone();
+        refresh_board_with_topology(&board, &mut game_board, vec![(0, 7)], Topology::Plane);
+        refresh_board(&board, &mut game_board_plane, vec![(0, 7)]);
+        assert_eq!(game_board, game_board_plane);
+    }
+}
+
+#[test]
+fn torus_laymine_works() {
+    for seed in 0..20u64 {
+        let board = laymine_with_topology_seeded(8, 8, 10, 0, 0, true, Topology::Torus, seed);
+        // 起手位置周围的3×3范围跨过边界
+        for &(x, y) in &[(7, 7), (7, 0), (0, 7), (1, 1), (0, 0)] {
+            assert_ne!(board[x][y], -1);
+        }
+        assert_eq!(board[0][0], 0);
+        assert_eq!(board.iter().flatten().filter(|&&c| c == -1).count(), 10);
+        let mut mines = board.clone();
+        mines
+            .iter_mut()
+            .flatten()
+            .for_each(|c| *c = if *c == -1 { -1 } else { 0 });
+        cal_board_numbers_with_topology(&mut mines, Topology::Torus);
+        assert_eq!(mines, board);
+    }
+}
+
+#[test]
+fn torus_solvable_and_probability_works() {
+    let mut board = vec![vec![0; 4]; 4];
+    board[0][0] = -1;
+    cal_board_numbers_with_topology(&mut board, Topology::Torus);
+    assert!(is_solvable_with_topology(&board, 2, 2, Topology::Torus));
+    assert!(!is_solvable_with_topology(&board, 0, 0, Topology::Torus));
+    let mut game_board = vec![vec![10; 4]; 4];
+    refresh_board_with_topology(&board, &mut game_board, vec![(2, 2)], Topology::Torus);
+    assert_eq!(game_board[0][0], 10);
+    assert_eq!(game_board[3][3], 1);
+    let (poss, _, mine_range, _) =
+        cal_probability_csp_with_topology(&game_board, 1.0, Topology::Torus).unwrap();
+    assert_eq!(poss, vec![((0, 0), 1.0)]);
+    assert_eq!(mine_range, [1, 1, 1]);
+    // 右上角的1在环面上有8个未打开的邻居，在平面上只有3个
+    let mut game_board = vec![vec![10; 4]; 4];
+    game_board[0][3] = 1;
+    let (poss, p_inside, _, _) =
+        cal_probability_csp_with_topology(&game_board, 1.0, Topology::Torus).unwrap();
+    assert_eq!(poss.len(), 8);
+    assert!(poss.iter().all(|&(_, p)| (p - 0.125).abs() < 1e-9));
+    assert!(p_inside.abs() < 1e-9);
+    let (poss, _, _, _) = cal_probability_csp(&game_board, 1.0).unwrap();
+    assert_eq!(poss.len(), 3);
+    assert!(poss.iter().all(|&(_, p)| (p - 1.0 / 3.0).abs() < 1e-9));
+}
+
+#[test]
+fn torus_minesweeper_board_works() {
+    let mut board = vec![vec![0; 4]; 4];
+    board[0][0] = -1;
+    cal_board_numbers_with_topology(&mut board, Topology::Torus);
+    let ops: Vec<(String, (usize, usize))> = [
+        ("lc", (3, 3)),
+        ("lr", (3, 3)),
+        ("rc", (0, 0)),
+        ("rr", (0, 0)),
+        ("lc", (3, 3)),
+        ("rc", (3, 3)),
+        ("lr", (3, 3)),
+        ("rr", (3, 3)),
+    ]
+    .iter()
+    .map(|&(e, pos)| (e.to_string(), pos))
+    .collect();
+    // 环面上(3, 3)与(0, 0)相邻，双击打开(2, 2)处的空后获胜
+    let mut my_board = MinesweeperBoard::<Vec<Vec<i32>>>::new(board.clone());
+    my_board.set_topology(Topology::Torus);
+    my_board.step_flow(&ops).unwrap();
+    assert_eq!(my_board.game_board_state, GameBoardState::Win);
+    // 平面上(3, 3)周围没有标雷，双击无效
+    let mut my_board = MinesweeperBoard::<Vec<Vec<i32>>>::new(board.clone());
+    my_board.step_flow(&ops).unwrap();
+    assert_eq!(my_board.game_board_state, GameBoardState::Playing);
+    assert_eq!(my_board.game_board[2][2], 10);
+
+    let mut wrap_board = Board::new_with_topology(board.clone(), Topology::Torus);
+    assert_eq!(wrap_board.get_op(), 1);
+    assert_eq!(wrap_board.get_isl(), 0);
+    assert_eq!(wrap_board.get_bbbv(), 1);
+    assert_eq!(
+        wrap_board.get_bbbv(),
+        cal_bbbv_with_topology(&board, Topology::Torus)
+    );
+    assert_eq!(wrap_board.openings().len(), 1);
+    assert!(wrap_board.islands().is_empty());
+}
+
+#[test]
+fn board_code_works() {
+    for seed in 0..10u64 {
+        let board = laymine_op_seeded(16, 30, 99, 7, 9, seed);
+        let code = encode_board(&board, Some((7, 9))).unwrap();
+        assert_eq!(decode_board(&code), Ok((board.clone(), Some((7, 9)))));
+        let code = encode_board(&board, None).unwrap();
+        assert_eq!(decode_board(&code), Ok((board, None)));
+    }
+    // 带遮挡的局面
+    let mut mask = vec![vec![false; 7]; 5];
+    mask[0][0] = true;
+    mask[4][6] = true;
+    let board = laymine_masked_seeded(&mask, 6, 2, 3, 3);
+    let code = encode_board(&board, None).unwrap();
+    assert_eq!(decode_board(&code), Ok((board.clone(), None)));
+    // 改动任意一个字符，校验都不通过
+    let mut chars: Vec<char> = code.chars().collect();
+    chars[4] = if chars[4] == 'A' { 'B' } else { 'A' };
+    let broken: String = chars.into_iter().collect();
+    assert_eq!(decode_board(&broken), Err(2));
+    assert_eq!(decode_board("not a board code"), Err(1));
+    assert_eq!(encode_board(&board, Some((5, 0))), Err(2));
+    assert_eq!(encode_board(&vec![], None), Err(1));
+    // 环面局面按环面计算数字，不能用decode_board解码
+    let board = laymine_with_topology_seeded(8, 8, 10, 0, 0, false, Topology::Torus, 5);
+    let code = encode_board_with_topology(&board, Some((0, 0)), Topology::Torus).unwrap();
+    assert_eq!(
+        decode_board_with_topology(&code),
+        Ok((board, Some((0, 0)), Topology::Torus))
+    );
+    assert_eq!(decode_board(&code), Err(5));
+}
+
+#[test]
+fn canonical_board_works() {
+    let board = laymine_op_seeded(8, 8, 10, 2, 3, 7);
+    let symmetries = board_symmetries(&board);
+    assert_eq!(symmetries.len(), 8);
+    let canonical = canonical_board(&board);
+    let hash = canonical_board_hash(&board);
+    for b in &symmetries {
+        assert_eq!(canonical_board(b), canonical);
+        assert_eq!(canonical_board_hash(b), hash);
+        assert_eq!(cal_bbbv(b), cal_bbbv(&board));
+    }
+    assert!(symmetries.contains(&canonical));
+    assert_ne!(
+        hash,
+        canonical_board_hash(&laymine_op_seeded(8, 8, 10, 2, 3, 8))
+    );
+    // 非方形局面只有4个像，尺寸不变
+    let board = laymine_op_seeded(16, 30, 99, 2, 3, 7);
+    let symmetries = board_symmetries(&board);
+    assert_eq!(symmetries.len(), 4);
+    let canonical = canonical_board(&board);
+    for b in &symmetries {
+        assert_eq!((b.len(), b[0].len()), (16, 30));
+        assert_eq!(canonical_board(b), canonical);
+    }
+}
+
+#[test]
+fn cal_optimal_clicks_works() {
+    for seed in 0..10u64 {
+        let board = laymine_op_seeded(6, 6, 6, 2, 2, seed);
+        let (clicks, ops, is_optimal) = cal_optimal_clicks(&board, OptimalClicksLimits::default());
+        assert!(is_optimal);
+        assert_eq!(clicks, ops.len());
+        assert!(clicks <= cal_zini(&board));
+        let mut mb = MinesweeperBoard::<Vec<Vec<i32>>>::new(board.clone());
+        mb.step_flow(&zini_ops_to_step_flow(&ops)).unwrap();
+        assert_eq!(mb.game_board_state, GameBoardState::Win);
+        assert_eq!(mb.lce + mb.rce + mb.dce, clicks);
+    }
+    // 超出限制时返回目前最好的结果
+    let board = laymine_op_seeded(16, 30, 99, 0, 0, 1);
+    let limits = OptimalClicksLimits {
+        max_nodes: 1000,
+        max_millis: 10_000,
+    };
+    let (clicks, ops, is_optimal) = cal_optimal_clicks(&board, limits);
+    assert!(!is_optimal);
+    assert_eq!(clicks, ops.len());
+    assert!(clicks <= cal_zini(&board));
+}
+
+#[test]
+fn board_structures_works() {
+    for seed in 0..20u64 {
+        let board = laymine_op_seeded(16, 30, 99, 8, 15, seed);
+        let mut wrap_board = Board::new(board.clone());
+        let openings = wrap_board.openings();
+        let islands = wrap_board.islands();
+        assert_eq!(openings.len(), cal_op(&board));
+        assert_eq!(islands.len(), cal_isl(&board));
+        assert_eq!(wrap_board.get_op(), openings.len());
+        let labels = wrap_board.bbbv_labels();
+        let island_bbbv: usize = islands.iter().map(|i| i.len()).sum();
+        assert_eq!(
+            labels.iter().flatten().filter(|&&l| l == 2).count(),
+            island_bbbv
+        );
+        assert_eq!(openings.len() + island_bbbv, cal_bbbv(&board));
+        // 所有的空加在一起，恰好覆盖所有的0和空边缘上的数字
+        let mut covered = vec![vec![false; 30]; 16];
+        for &(x, y) in openings.iter().flatten() {
+            assert!(labels[x][y] == 0 || labels[x][y] == 1);
+            covered[x][y] = true;
+        }
+        for x in 0..16 {
+            for y in 0..30 {
+                assert_eq!(covered[x][y], labels[x][y] == 0 || labels[x][y] == 1);
+            }
+        }
+    }
+}
+
+#[test]
+fn solution_complexity_works() {
+    for seed in 0..10u64 {
+        let (board, success) = laymine_solvable_seeded(16, 16, 40, 8, 8, 10000, seed);
+        assert!(success);
+        let complexity = cal_solution_complexity(&board, 8, 8);
+        assert_eq!(complexity.guess, 0);
+        let mut wrap_board = Board::new(board);
+        assert_eq!(wrap_board.get_solution_complexity(8, 8), complexity);
+    }
+    let mut guess_num = 0;
+    for seed in 0..10u64 {
+        let board = laymine_op_seeded(16, 30, 99, 8, 15, seed);
+        let complexity = cal_solution_complexity(&board, 8, 15);
+        if !is_solvable(&board, 8, 15) {
+            guess_num += complexity.guess;
+        }
+        assert!(complexity.direct > 0);
+    }
+    // 高级的局面大多需要猜
+    assert!(guess_num > 0);
+    // 起手踩雷
+    let board = vec![vec![-1, 1], vec![1, 1]];
+    assert_eq!(
+        cal_solution_complexity(&board, 0, 0),
+        SolutionComplexity::default()
+    );
+}
+
+#[test]
+fn solution_complexity_with_topology_works() {
+    for seed in 0..10u64 {
+        let board = laymine_with_topology_seeded(8, 8, 10, 0, 0, true, Topology::Torus, seed);
+        let complexity = cal_solution_complexity_with_topology(&board, 0, 0, Topology::Torus);
+        if is_solvable_with_topology(&board, 0, 0, Topology::Torus) {
+            assert_eq!(complexity.guess, 0);
+        }
+        let mut wrap_board = Board::new_with_topology(board, Topology::Torus);
+        assert_eq!(wrap_board.get_solution_complexity(0, 0), complexity);
+    }
+}
+
+#[test]
+fn try_solve_trace_works() {
+    let mut rules = vec![];
+    for seed in 0..20u64 {
+        let board = laymine_op_seeded(16, 16, 40, 8, 8, seed);
+        let (trace, solved) = try_solve_trace(&board, 8, 8).unwrap();
+        assert_eq!(solved, is_solvable(&board, 8, 8));
+        let mut last_step = 0;
+        for s in &trace {
+            assert!(s.step >= last_step);
+            last_step = s.step;
+            assert_eq!(s.is_mine, board[s.cell.0][s.cell.1] == -1);
+            match s.rule {
+                SolveRule::Direct => assert!(!s.witnesses.is_empty()),
+                SolveRule::Minus | SolveRule::Enumerate => assert!(s.witnesses.len() >= 2),
+            }
+            for &(x, y) in &s.witnesses {
+                assert!(board[x][y] >= 0);
+            }
+            if s.rule != SolveRule::Enumerate {
+                let (x, y) = s.witnesses[0];
+                assert!(x.abs_diff(s.cell.0) <= 1 && y.abs_diff(s.cell.1) <= 1);
+            }
+            rules.push(s.rule);
+        }
+        if solved {
+            // 各方法推理出的格子数与解题复杂度一致
+            let count = |rule| trace.iter().filter(|s| s.rule == rule).count();
+            let complexity = cal_solution_complexity(&board, 8, 8);
+            assert_eq!(count(SolveRule::Direct), complexity.direct);
+            assert_eq!(count(SolveRule::Minus), complexity.minus);
+            assert_eq!(count(SolveRule::Enumerate), complexity.enumerate);
+            let cells = trace
+                .iter()
+                .filter(|s| !s.is_mine)
+                .map(|s| s.cell)
+                .collect();
+            let mut game_board = get_covered_game_board(&board);
+            refresh_board(&board, &mut game_board, vec![(8, 8)]);
+            refresh_board(&board, &mut game_board, cells);
+            let covered = game_board.iter().flatten().filter(|&&x| x == 10).count();
+            assert_eq!(covered, 40);
+        }
+    }
+    assert!(rules.contains(&SolveRule::Direct));
+    assert!(rules.contains(&SolveRule::Minus));
+    assert!(rules.contains(&SolveRule::Enumerate));
+    // 起手踩雷
+    let board = vec![vec![-1, 1], vec![1, 1]];
+    assert_eq!(try_solve_trace(&board, 0, 0), Ok((vec![], false)));
+}
+
+#[test]
+fn find_patterns_works() {
+    // 竖着的1-2-1，下面的2旁边有标的雷，相当于1。两端还有两个1-1
+    let game_board = vec![
+        vec![0, 1, 10],
+        vec![0, 1, 10],
+        vec![0, 2, 10],
+        vec![1, 2, 10],
+        vec![11, 2, 10],
+    ];
+    let patterns = find_patterns(&game_board);
+    let names: Vec<_> = patterns.iter().map(|p| p.name).collect();
+    assert_eq!(names, vec!["1-1", "1-2-1", "1-1"]);
+    assert_eq!(patterns[1].numbers, vec![(1, 1), (2, 1), (3, 1)]);
+    assert_eq!(patterns[1].mines, vec![(1, 2), (3, 2)]);
+    assert_eq!(patterns[1].not_mines, vec![(0, 2), (2, 2), (4, 2)]);
+    assert_eq!(patterns[0].not_mines, vec![(2, 2)]);
+    assert_eq!(patterns[2].not_mines, vec![(2, 2)]);
+    // 靠边的1-1、1-2
+    let patterns = find_patterns(&vec![vec![1, 1, 1], vec![10, 10, 10]]);
+    let names: Vec<_> = patterns.iter().map(|p| p.name).collect();
+    assert_eq!(names, vec!["1-1", "1-1"]);
+    assert_eq!(patterns[0].not_mines, vec![(1, 2)]);
+    assert_eq!(patterns[1].not_mines, vec![(1, 0)]);
+    let patterns = find_patterns(&vec![vec![1, 2, 2], vec![10, 10, 10]]);
+    assert_eq!(patterns.len(), 1);
+    assert_eq!(patterns[0].name, "1-2");
+    assert_eq!(patterns[0].mines, vec![(1, 2)]);
+    // 1-2-1中的1-2不单独返回
+    let patterns = find_patterns(&vec![vec![1, 1, 2, 1], vec![10, 10, 10, 10]]);
+    let names: Vec<_> = patterns.iter().map(|p| p.name).collect();
+    assert_eq!(names, vec!["1-1", "1-2-1"]);
+    assert_eq!(patterns[1].not_mines, vec![(1, 0), (1, 2)]);
+    // 洞和角上的三角形
+    let game_board = vec![
+        vec![1, 1, 1, 0],
+        vec![1, 10, 1, 0],
+        vec![1, 1, 1, 0],
+        vec![0, 0, 1, 1],
+        vec![0, 0, 1, 10],
+    ];
+    let patterns = find_patterns(&game_board);
+    assert!(patterns
+        .iter()
+        .any(|p| p.name == "hole" && p.mines == vec![(1, 1)]));
+    assert!(patterns
+        .iter()
+        .any(|p| p.name == "triangle" && p.numbers == vec![(3, 2)]));
+    assert!(patterns.iter().all(|p| p.not_mines.is_empty()));
+    // 没有未打开的格子
+    assert!(find_patterns(&vec![vec![0, 0], vec![0, 0]]).is_empty());
+}
+
+#[test]
+fn hint_works() {
+    // 无猜的局面，按提示一直扫到结束，不需要猜
+    for seed in 0..5u64 {
+        let (board, success) = laymine_solvable_seeded(16, 16, 40, 8, 8, 10000, seed);
+        assert!(success);
+        let mut game_board = get_covered_game_board(&board);
+        refresh_board(&board, &mut game_board, vec![(8, 8)]);
+        let mut cursor = (8, 8);
+        let mut flags = 0;
+        for _ in 0..256 {
+            let Some(h) = hint(&game_board, 40.0, cursor).unwrap() else {
+                break;
+            };
+            assert!(h.rule.is_some());
+            assert!(!h.witnesses.is_empty());
+            if h.action == HintAction::Right {
+                assert_eq!(board[h.cell.0][h.cell.1], -1);
+                assert_eq!(h.safety, 0.0);
+                game_board[h.cell.0][h.cell.1] = 11;
+                flags += 1;
+            } else {
+                assert!(board[h.cell.0][h.cell.1] >= 0);
+                assert_eq!(h.safety, 1.0);
+                refresh_board(&board, &mut game_board, vec![h.cell]);
+            }
+            cursor = h.cell;
+        }
+        // 雷都标出来了，其他格子都打开了
+        assert_eq!(flags, 40);
+        assert!(game_board.iter().flatten().all(|&x| x != 10));
+    }
+    // 没有能确定的格子时，按概率选择离光标最近的
+    let game_board = vec![vec![10; 8]; 8];
+    let h = hint(&game_board, 10.0, (3, 5)).unwrap().unwrap();
+    assert_eq!(h.cell, (3, 5));
+    assert_eq!(h.rule, None);
+    assert!((h.safety - 54.0 / 64.0).abs() < 1e-9);
+    // 判出的雷都已经标出时，继续推理，提示判出的安全格子，而不是按概率选择
+    let game_board = vec![vec![0, 0, 0, 0], vec![1, 2, 2, 1], vec![10, 11, 11, 10]];
+    let h = hint(&game_board, 2.0, (2, 3)).unwrap().unwrap();
+    assert!(h.cell == (2, 0) || h.cell == (2, 3));
+    assert_eq!(h.action, HintAction::Left);
+    assert!(h.rule.is_some());
+    assert!(!h.witnesses.is_empty());
+    assert!(hint(&vec![vec![0, 0], vec![0, 0]], 0.0, (0, 0))
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn guess_stats_works() {
+    // 无猜的局面不用猜
+    for seed in 0..5u64 {
+        let (board, success) = laymine_solvable_seeded(8, 8, 10, 4, 4, 10000, seed);
+        assert!(success);
+        let stats = cal_guess_stats_seeded(&board, 4, 4, 100, seed).unwrap();
+        assert!(stats.is_exact);
+        assert_eq!(stats.mean_guesses, 0.0);
+        assert_eq!(stats.survival, 1.0);
+        assert!(stats.guesses.is_empty());
+    }
+    // 角上的50/50
+    let board = vec![vec![0, 1, -1], vec![0, 1, 1]];
+    let stats = cal_guess_stats_seeded(&board, 0, 0, 100, 0).unwrap();
+    assert!(stats.is_exact);
+    assert_eq!(stats.mean_guesses, 1.0);
+    assert_eq!(stats.survival, 0.5);
+    assert_eq!(stats.guesses, vec![((0, 2), 0.5, 0.5), ((1, 2), 0.5, 0.5)]);
+    // 走法过多时模拟，同一个种子结果相同
+    let board = laymine_op_seeded(16, 30, 99, 8, 15, 1);
+    let stats = cal_guess_stats_seeded(&board, 8, 15, 20, 7).unwrap();
+    assert_eq!(stats, cal_guess_stats_seeded(&board, 8, 15, 20, 7).unwrap());
+    assert!((stats.distribution.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+    assert!(stats.survival <= 1.0);
+    // 起手踩雷
+    let board = vec![vec![-1, 1], vec![1, 1]];
+    assert_eq!(
+        cal_guess_stats_seeded(&board, 0, 0, 100, 0)
+            .unwrap()
+            .survival,
+        0.0
+    );
+}
+
+// 判出所有能判的格子，直到扫开或必须猜。每次都检查整个局面是否扫开
+fn reference_advance(
+    board: &Vec<Vec<i32>>,
+    game_board: &mut Vec<Vec<i32>>,
+    minenum: usize,
+) -> Option<Vec<((usize, usize), f64)>> {
+    loop {
+        let won = board
+            .iter()
+            .flatten()
+            .zip(game_board.iter().flatten())
+            .all(|(&b, &g)| b < 0 || g < 10);
+        if won {
+            return None;
+        }
+        let (mut a_mats, mut xs, mut bs, _, _) = refresh_matrixs(game_board);
+        let ans = solve_direct(&mut a_mats, &mut xs, &mut bs, game_board).unwrap();
+        if !ans.0.is_empty() || !ans.1.is_empty() {
+            refresh_board(board, game_board, ans.0);
+            continue;
+        }
+        let ans = solve_minus(&mut a_mats, &mut xs, &mut bs, game_board).unwrap();
+        if !ans.0.is_empty() || !ans.1.is_empty() {
+            refresh_board(board, game_board, ans.0);
+            continue;
+        }
+        let ans = solve_enumerate(&a_mats, &xs, &bs);
+        if !ans.0.is_empty() || !ans.1.is_empty() {
+            for &(o, p) in &ans.1 {
+                game_board[o][p] = 11;
+            }
+            refresh_board(board, game_board, ans.0);
+            continue;
+        }
+        let moves = suggest_moves(game_board, minenum as f64).unwrap();
+        let safe: Vec<(usize, usize)> = moves
+            .iter()
+            .filter(|m| m.safety >= 1.0 - 1e-9)
+            .map(|m| m.pos)
+            .collect();
+        if !safe.is_empty() {
+            refresh_board(board, game_board, safe);
+            continue;
+        }
+        let best = &moves[0];
+        return Some(
+            moves
+                .iter()
+                .filter(|m| {
+                    (m.safety - best.safety).abs() < 1e-9
+                        && (m.progress - best.progress).abs() < 1e-9
+                        && m.dead == best.dead
+                })
+                .map(|m| (m.pos, m.safety))
+                .collect(),
+        );
+    }
+}
+
+// 与cal_guess_stats_seeded相同的走法，返回（平均猜的次数、各次猜的安全率之积的均值）
+fn reference_guess_stats(
+    board: &Vec<Vec<i32>>,
+    x0: usize,
+    y0: usize,
+    runs: usize,
+    seed: u64,
+) -> (f64, f64) {
+    let minenum = board.iter().flatten().filter(|&&x| x == -1).count();
+    let mut start = get_covered_game_board(board);
+    refresh_board(board, &mut start, vec![(x0, y0)]);
+    let mut leaves = vec![];
+    let mut stack = vec![(start.clone(), 1.0, 0, 1.0)];
+    let mut is_exact = true;
+    while let Some((mut game_board, weight, guess, safe)) = stack.pop() {
+        let Some(ties) = reference_advance(board, &mut game_board, minenum) else {
+            leaves.push((weight, guess, safe));
+            continue;
+        };
+        let weight = weight / ties.len() as f64;
+        for (cell, safety) in ties {
+            if board[cell.0][cell.1] == -1 {
+                leaves.push((weight, guess + 1, safe * safety));
+            } else {
+                let mut game_board = game_board.clone();
+                refresh_board(board, &mut game_board, vec![cell]);
+                stack.push((game_board, weight, guess + 1, safe * safety));
+            }
+        }
+        if leaves.len() + stack.len() > runs {
+            is_exact = false;
+            break;
+        }
+    }
+    if !is_exact {
+        leaves.clear();
+        let mut rng = SeededRng::new(seed);
+        for _ in 0..runs {
+            let mut game_board = start.clone();
+            let mut guess = 0;
+            let mut safe = 1.0;
+            while let Some(ties) = reference_advance(board, &mut game_board, minenum) {
+                let (cell, safety) = ties[rng.gen_below(ties.len())];
+                guess += 1;
+                safe *= safety;
+                if board[cell.0][cell.1] == -1 {
+                    break;
+                }
+                refresh_board(board, &mut game_board, vec![cell]);
+            }
+            leaves.push((1.0 / runs as f64, guess, safe));
+        }
+    }
+    let mean = leaves.iter().map(|&(w, g, _)| w * g as f64).sum();
+    let survival = leaves.iter().map(|&(w, _, s)| w * s).sum();
+    (mean, survival)
+}
+
+#[test]
+fn guess_stats_matches_full_board_check() {
+    for seed in [0, 145, 155, 279] {
+        let board = laymine_seeded(9, 9, 14, 4, 4, seed);
+        if board[4][4] == -1 {
+            continue;
+        }
+        for runs in [3, 50] {
+            let stats = cal_guess_stats_seeded(&board, 4, 4, runs, seed).unwrap();
+            let (mean, survival) = reference_guess_stats(&board, 4, 4, runs, seed);
+            assert!(
+                (stats.mean_guesses - mean).abs() < 1e-9,
+                "seed {seed}, runs {runs}"
+            );
+            assert!(
+                (stats.survival - survival).abs() < 1e-9,
+                "seed {seed}, runs {runs}"
+            );
+        }
+    }
+}
@@ -5,20 +5,87 @@ from typing import Any, Sequence, Union, Tuple, List, Optional, Callable
 
 def __getattr__(name: str) -> Any: ...
 
-# === Functions (27) ===
+# === Functions (52) ===
 
 
 def cal_all_solution(a: List[List[int]], b: List[int]) -> List[List[int]]: ...
-def cal_bbbv(board: List[List[int]]) -> int: ...
+def cal_bbbv(board: List[List[int]], topology: int = 0) -> int: ...
 def cal_zini(board: List[List[int]], loop_count: int) -> int: ...
-def cal_board_numbers(board: List[List[int]]) -> List[List[int]]: ...
-def cal_op(board: List[List[int]]) -> int: ...
+
+
+def cal_zini_ops(board: List[List[int]], method: str = "greedy", loop_count: int = 100) -> List[tuple[str, tuple[int, int]]]:
+    """ZiNi的路线，即扫完局面的操作序列。
+
+# 参数
+- `board`：真实局面。
+- `method`：算法，"greedy"（同cal_zini）、"human"（同cal_hzini）、"random"（同cal_rzini）或"nf"（不标雷，等于3BV）。
+- `loop_count`：method为"random"时的迭代轮数。
+
+# 返回值
+按顺序排列的操作，每个操作为（操作类型, (行, 列)），操作类型为"left"、"right"、"chord"。
+操作的个数等于对应的ZiNi。
+
+# 异常
+- `PyValueError`：未知的算法。"""
+
+
+def cal_optimal_clicks(board: List[List[int]], max_nodes: int = 2000000, max_millis: int = 10000) -> tuple[int, List[tuple[str, tuple[int, int]]], bool]:
+    """最少点击数（精确的ZiNi），用分支定界搜索。搜索量随局面增大急剧增加，适合初级、中级及以下的局面。
+
+# 参数
+- `board`：真实局面。
+- `max_nodes`：最多搜索的状态数。
+- `max_millis`：最长搜索时间，单位为毫秒。
+
+# 返回值
+（点击数, 操作序列, 是否为最优解）。超出限制时返回目前找到的最好的结果，至少不比cal_zini差。
+操作序列的格式与cal_zini_ops相同。"""
+
+
+def cal_guess_stats(board: List[List[int]], x0: int, y0: int, runs: int = 100, seed: Optional[int] = None) -> GuessStats:
+    """估计按最优策略玩某个局面时，猜的次数与存活率，用于给生成的或录像中的局面评级。
+从起手位置开始，能判的格子都判出来；判不出时，在概率引擎的最佳候选（见suggest_moves）中等概率地选一个猜，猜到雷则失败。
+
+# 参数
+- `board`：真实局面。
+- `x0`、`y0`：起手位置（行，列）。
+- `runs`：最佳候选并列时走法会分叉，不同走法的数量不超过runs时精确计算，否则模拟runs局。
+- `seed`：可选的种子。
+
+# 返回值
+猜的次数的均值、分布，存活率，以及猜的位置。起手位置是雷时存活率为0。
+
+# 异常
+- `PyRuntimeError`: `无解的局面`、`枚举过长`两种。"""
+
+
+def cal_board_numbers(board: List[List[int]], topology: int = 0) -> List[List[int]]: ...
+def cal_op(board: List[List[int]], topology: int = 0) -> int: ...
+
+
+def cal_isl(board: List[List[int]], topology: int = 0) -> int: ...
 
 
 def cal_probability(game_board: List[List[int]], mine_num: float) -> tuple[List[tuple[tuple[int,
                                                                                             int], float]], float, List[int], int]: ...
 
 
+def cal_probability_csp(game_board: List[List[int]], mine_num: float, topology: int = 0) -> tuple[List[tuple[tuple[int,
+                                                                                                   int], float]], float, List[int], int]:
+    """用概率引擎计算局面中边缘格子是雷的概率。
+
+# 参数
+- `game_board`: 游戏局面。自动纠正错误的标雷。
+- `mine_num`：雷数。>=1时，理解为总的雷数；<1时，理解为雷的比例。
+- `topology`：局面的拓扑结构，0-平面，1-环面。
+
+# 返回值
+- 所有边缘格子是雷的概率、内部未知格子是雷的概率、最小雷数、当前雷数、最大雷数组成的列表、边缘格子数
+
+# 异常
+- `PyRuntimeError`: `标记阶段无解的局面`和`枚举阶段无解的局面`两种。"""
+
+
 def cal_probability_onboard(game_board: List[List[int]], mine_num: float) -> tuple[List[List[float]], List[int]]:
     """计算局面中各位置是雷的概率，按照所在的位置返回。
 
@@ -34,6 +101,170 @@ def cal_probability_onboard(game_board: List[List[int]], mine_num: float) -> tup
 - `PyRuntimeError`: `标记阶段无解的局面`和`枚举阶段无解的局面`两种。"""
 
 
+def cal_mine_count_distribution(game_board: List[List[int]], mine_num: float) -> tuple[List[tuple[int, float]], List[tuple[int, float]]]:
+    """计算边缘与内部未打开的格子中雷数的概率分布，可用于估计内部还有多少雷。
+边缘格子指与数字相邻的未打开的格子，内部格子指其余未打开的格子，均不包括已经标出的雷。
+
+# 参数
+- `game_board`: 游戏局面。
+- `mine_num`：雷数。>=1时，理解为总的雷数；<1时，理解为雷的比例。
+
+# 返回值
+边缘雷数的分布、内部雷数的分布，均为按雷数从小到大排列的（雷数, 概率），只包含概率不为0的雷数。
+
+# 异常
+- `PyRuntimeError`: `无解的局面`、`枚举过长`、`非法的局面`三种。"""
+
+
+def cal_board_percentile(board: List[List[int]], x0: int, y0: int,
+                         op_start: bool = False) -> tuple[float, float]:
+    """计算局面的3BV与ZiNi在同尺寸、同雷数、同起手位置、同起手规则的局面中的百分位（0~100），越大越难。
+
+# 参数
+- `board`: 局面。
+- `x0`：起手位置在第几行。
+- `y0`：起手位置在第几列。
+- `op_start`：是否起手开空。True时与laymine_op的局面比较，否则与laymine的局面比较。
+
+# 返回值
+(3BV的百分位, ZiNi的百分位)。
+
+# 异常
+- `PyRuntimeError`: `没有设置这种局面的分布表`。须先用set_board_histograms设置，不会自动采样。"""
+
+
+def cal_solutions_count_exact(game_board: List[List[int]], mine_num: float) -> tuple[int, List[List[int]]]:
+    """精确计算与局面相容的雷的排布总数，以及每个格子是雷的排布数。格子是雷的概率即为二者之比。
+
+# 参数
+- `game_board`: 游戏局面。
+- `mine_num`：雷数。>=1时，理解为总的雷数；<1时，理解为雷的比例。
+
+# 返回值
+排布总数、与局面位置对应的是雷的排布数（已打开的格子为0，标雷的格子等于排布总数）。
+
+# 异常
+- `PyRuntimeError`: `无解的局面`、`枚举过长`、`非法的局面`三种。"""
+
+
+def canonical_board(board: List[List[int]]) -> List[List[int]]:
+    """局面在旋转、翻转下的标准形式。经过旋转、翻转能互相得到的局面，标准形式相同。
+
+# 参数
+- `board`：真实局面或游戏局面。
+
+# 返回值
+所有像中按行优先排列时字典序最小的一个。方形局面考虑8种变换，其他局面考虑4种。"""
+
+
+def canonical_board_hash(board: List[List[int]]) -> int:
+    """局面的标准形式的64位哈希值，用于大量局面的去重。在各个平台上都相同。
+
+# 参数
+- `board`：真实局面或游戏局面。
+
+# 返回值
+哈希值。经过旋转、翻转能互相得到的局面，哈希值相同。"""
+
+
+def check_for_unavoidable_5050(game_board: List[List[int]], mine_num: float) -> Optional[List[tuple[int, int]]]:
+    """检查局面中是否有无法避免的50/50，包括由若干个二选一首尾相连组成的链。
+
+# 参数
+- `game_board`: 游戏局面。自动纠正错误的标雷。
+- `mine_num`：雷数。>=1时，理解为总的雷数；<1时，理解为雷的比例。
+
+# 返回值
+涉及的格子的位置列表，没有则返回None。
+
+# 异常
+- `PyRuntimeError`: `无解的局面`、`枚举过长`、`非法的局面`三种。"""
+
+
+def check_for_unavoidable_5050_or_pseudo(game_board: List[List[int]], mine_num: float) -> Optional[List[tuple[int, int]]]:
+    """检查局面中是否有无法避免的50/50或伪50/50（一个数字周围的多个格子中，有两个格子构成的二选一）。
+
+# 参数
+- `game_board`: 游戏局面。自动纠正错误的标雷。
+- `mine_num`：雷数。>=1时，理解为总的雷数；<1时，理解为雷的比例。
+
+# 返回值
+涉及的格子的位置列表，没有则返回None。
+
+# 异常
+- `PyRuntimeError`: `无解的局面`、`枚举过长`、`非法的局面`三种。"""
+
+
+def check_for_unavoidable_guess(game_board: List[List[int]], mine_num: float) -> Optional[List[tuple[int, int]]]:
+    """检查局面中是否有无法避免的猜测，即无论先打开其他哪些格子，最终都必须猜的格子。
+
+# 参数
+- `game_board`: 游戏局面。自动纠正错误的标雷。
+- `mine_num`：雷数。>=1时，理解为总的雷数；<1时，理解为雷的比例。
+
+# 返回值
+涉及的格子的位置列表，没有则返回None。
+
+# 异常
+- `PyRuntimeError`: `无解的局面`、`枚举过长`、`非法的局面`三种。"""
+
+
+def decode_board(code: str) -> tuple[List[List[int]], Optional[tuple[int, int]], int]:
+    """把encode_board生成的字符串解码成真实局面。
+
+# 参数
+- `code`：局面的编码。
+
+# 返回值
+真实局面（数字按编码中的拓扑结构重新计算）、起手位置（行，列），编码时没有给出起手位置时为None，
+以及拓扑结构，0-平面，1-环面。
+
+# 异常
+- `PyValueError`：编码含有非法字符、校验不通过、版本号不支持或内容不合法。"""
+
+
+def encode_board(board: List[List[int]], first_click: Optional[tuple[int, int]] = None,
+                 topology: int = 0) -> str:
+    """把真实局面编码成短字符串，便于分享。
+
+# 参数
+- `board`：真实局面。-1代表雷，-2代表被遮挡的位置，其余的数字被忽略。
+- `first_click`：可选的起手位置（行，列）。
+- `topology`：局面的拓扑结构，0-平面，1-环面。
+
+# 返回值
+base64url格式的字符串，包含版本号、尺寸、拓扑结构、雷的位置、起手位置和校验。
+
+# 异常
+- `PyValueError`：局面为空、过大，起手位置超出局面，或拓扑结构不合法。"""
+
+
+def find_patterns(game_board: List[List[int]]) -> List[PatternMatch]:
+    """识别游戏局面上的定式，会尝试所有的旋转和翻转。
+
+# 参数
+- `game_board`：游戏局面。标的雷当作雷，数字减去周围标的雷以后再识别。
+
+# 返回值
+识别出的定式，按第一个数字的位置排列。被更大的定式包含的定式（例如1-2-1中的1-2）不返回。"""
+
+
+def hint(game_board: List[List[int]], mine_num: float, cursor: tuple[int, int]) -> Optional[Hint]:
+    """提示下一步。依次用单集合、双集合、枚举判雷，使用最简单的、能判出格子的方法，选择判出的格子中离光标最近的；
+都判不出时，用总雷数计算概率，选择最安全的格子中离光标最近的。
+
+# 参数
+- `game_board`: 游戏局面。玩家标的雷可能是错的，不参与推理；推理出的雷若已经标出，则不再提示。
+- `mine_num`：雷数。>=1时，理解为总的雷数；<1时，理解为雷的比例。
+- `cursor`：光标位置（行，列）。
+
+# 返回值
+提示，没有未打开的格子时返回None。
+
+# 异常
+- `PyRuntimeError`: `无解的局面`、`枚举过长`、`非法的局面`三种。"""
+
+
 def get_all_not_and_is_mine_on_board(
     game_board: List[List[int]]) -> tuple[List[List[int]], List[tuple[int, int]], List[tuple[int, int]]]: ...
 
@@ -44,10 +275,27 @@ def is_guess_while_needless(
     board_of_game: List[List[int]], xy: tuple[int, int]) -> int: ...
 
 
-def is_solvable(board: List[List[int]], x0: int, y0: int) -> bool: ...
+def is_solvable(board: List[List[int]], x0: int, y0: int, topology: int = 0) -> bool: ...
+
+
+def try_solve_trace(board: List[List[int]], x0: int, y0: int) -> tuple[List[SolveStep], bool]:
+    """带推理过程的判雷。从起手位置开始，与is_solvable相同，每轮依次尝试单集合、双集合、枚举判雷，
+并记录推理出的每个格子、所用的判雷方法、作为依据的数字。不考虑剩余雷数。
+
+# 参数
+- `board`：真实局面。
+- `x0`、`y0`：起手位置（行，列）。
+
+# 返回值
+（推理过程，是否无猜扫开）。推理过程按推理的先后排列，起手和随空一起打开的格子不记录。
+同一轮中后推理出的格子，witnesses除了推理它的数字（排在第一个），还包括推理出同一轮中先前的格子所用的数字。
+
+# 异常
+- `PyRuntimeError`：局面矛盾，或推理出的格子找不到作为依据的数字。"""
 
 
-def laymine(row: int, column: int, mine_num: int, x0: int, y0: int) -> List[List[int]]:
+def laymine(row: int, column: int, mine_num: int, x0: int, y0: int, seed: Optional[int] = None,
+            topology: int = 0) -> List[List[int]]:
     """通用标准埋雷引擎。起手位置非雷，其余位置的雷服从均匀分布。
 
 # 参数
@@ -56,12 +304,30 @@ def laymine(row: int, column: int, mine_num: int, x0: int, y0: int) -> List[List
 - `mine_num`：雷数。
 - `x0`：起手位置在第几行。
 - `y0`：起手位置在第几列。
+- `seed`：可选的种子。给定种子时，相同的种子在各个平台上生成相同的局面。
+- `topology`：局面的拓扑结构，0-平面，1-环面。环面上，上下、左右的边界相连。
 
 # 返回值
 二维的局面，其中0代表空，1~8代表1~8，-1代表雷。"""
 
 
-def laymine_op(row: int, column: int, mine_num: int, x0: int, y0: int) -> List[List[int]]:
+def laymine_masked(mask: List[List[bool]], mine_num: int, x0: int, y0: int, seed: Optional[int] = None) -> List[List[int]]:
+    """带遮挡的标准埋雷引擎，用于非矩形的局面。起手位置非雷，其余没有被遮挡的位置的雷服从均匀分布。
+被遮挡的位置既不是雷，也不能点击，游戏局面中显示为19。
+
+# 参数
+- `mask`：遮挡，True代表该位置没有格子。局面的尺寸与遮挡相同。
+- `mine_num`：雷数。
+- `x0`：起手位置在第几行。
+- `y0`：起手位置在第几列。
+- `seed`：可选的种子。给定种子时，相同的种子在各个平台上生成相同的局面。
+
+# 返回值
+二维的局面，其中0代表空，1~8代表1~8，-1代表雷，-2代表被遮挡的位置。"""
+
+
+def laymine_op(row: int, column: int, mine_num: int, x0: int, y0: int, seed: Optional[int] = None,
+               topology: int = 0) -> List[List[int]]:
     """通用win7规则埋雷引擎。起手位置开空，其余位置的雷服从均匀分布。
 
 # 参数
@@ -70,21 +336,55 @@ def laymine_op(row: int, column: int, mine_num: int, x0: int, y0: int) -> List[L
 - `mine_num`：雷数。
 - `x0`：起手位置在第几行。
 - `y0`：起手位置在第几列。
+- `seed`：可选的种子。给定种子时，相同的种子在各个平台上生成相同的局面。
+- `topology`：局面的拓扑结构，0-平面，1-环面。环面上，上下、左右的边界相连。
 
 # 返回值
 二维的局面，其中0代表空，1~8代表1~8，-1代表雷。"""
 
 
+def laymine_filtered(row: int, column: int, mine_num: int, x0: int, y0: int,
+                     bbbv: Optional[tuple[int, int]] = None, op: Optional[tuple[int, int]] = None,
+                     isl: Optional[tuple[int, int]] = None, zini: Optional[tuple[int, int]] = None,
+                     hzini: Optional[tuple[int, int]] = None, cell0: Optional[tuple[int, int]] = None,
+                     cell1: Optional[tuple[int, int]] = None, cell2: Optional[tuple[int, int]] = None,
+                     cell3: Optional[tuple[int, int]] = None, cell4: Optional[tuple[int, int]] = None,
+                     cell5: Optional[tuple[int, int]] = None, cell6: Optional[tuple[int, int]] = None,
+                     cell7: Optional[tuple[int, int]] = None, cell8: Optional[tuple[int, int]] = None,
+                     solvable: Optional[bool] = None, op_start: bool = False, max_times: int = 1000000,
+                     seed: Optional[int] = None, thread_num: int = 8,
+                     control: Optional["ThreadControl"] = None) -> tuple[List[List[int]], bool, int]:
+    """筛选法多线程埋雷。反复埋雷，直到局面满足全部筛选条件。
+
+# 参数
+- `row`: 局面行数。
+- `column`：局面列数。
+- `mine_num`：雷数。
+- `x0`：起手位置在第几行。
+- `y0`：起手位置在第几列。
+- `bbbv`、`op`、`isl`、`zini`、`hzini`、`cell0`~`cell8`：各指标的闭区间(最小值, 最大值)，None代表不限制。
+- `solvable`：True时要求无猜，False时要求有猜，None代表不限制。
+- `op_start`：是否起手开空。True时按laymine_op的规则埋雷，否则按laymine的规则埋雷。
+- `max_times`：最大尝试次数。
+- `seed`：可选的种子。
+- `thread_num`：线程数，为0时使用系统可用的并行度。传入`control`时以`control`的线程数为准。
+- `control`：可选的控制器，用于查询尝试次数、取消计算。
+
+# 返回值
+(局面, 是否成功, 被拒绝的局面数)。"""
+
+
 def laymine_solvable(row: int, column: int, mine_num: int, x0: int,
-                     y0: int, max_times: int = 1000000) -> tuple[List[List[int]], bool]: ...
+                     y0: int, max_times: int = 1000000, seed: Optional[int] = None) -> tuple[List[List[int]], bool]: ...
 
 
 def laymine_solvable_adjust(row: int, column: int, mine_num: int,
-                            x0: int, y0: int) -> tuple[List[List[int]], bool]: ...
+                            x0: int, y0: int, seed: Optional[int] = None) -> tuple[List[List[int]], bool]: ...
 
 
 def laymine_solvable_thread(row: int, column: int, mine_num: int, x0: int,
-                            y0: int, max_times: int = 1000000) -> tuple[List[List[int]], bool]: ...
+                            y0: int, max_times: int = 1000000, seed: Optional[int] = None,
+                            thread_num: int = 8, control: Optional["ThreadControl"] = None) -> tuple[List[List[int]], bool]: ...
 
 
 def mark_board(game_board: List[List[int]],
@@ -96,7 +396,7 @@ def obr_board(data_vec: List[int], height: int,
 
 
 def refresh_board(board: List[List[int]], board_of_game: List[List[int]],
-                  clicked_poses: List[tuple[int, int]]) -> List[List[int]]: ...
+                  clicked_poses: List[tuple[int, int]], topology: int = 0) -> List[List[int]]: ...
 
 
 def refresh_matrix(game_board: List[List[int]]) -> tuple[List[List[int]],
@@ -109,7 +409,44 @@ def refresh_matrixses(board_of_game: List[List[int]]) -> tuple[List[List[List[Li
                                                                List[List[List[tuple[int, int]]]], List[List[List[int]]]]: ...
 
 
-def sample_bbbvs_exp(x0: int, y0: int, n: int) -> List[int]: ...
+def sample_bbbvs_exp(x0: int, y0: int, n: int, seed: Optional[int] = None,
+                     thread_num: int = 16, control: Optional["ThreadControl"] = None) -> List[int]: ...
+
+
+def sample_board_histograms(row: int, column: int, mine_num: int, x0: int, y0: int, op_start: bool, n: int,
+                            seed: Optional[int] = None, thread_num: int = 8,
+                            control: Optional["ThreadControl"] = None) -> BoardHistograms:
+    """任意尺寸的局面指标采样引擎，用于研究3BV、op、isl、ZiNi的分布。
+
+# 参数
+- `row`: 局面行数。
+- `column`：局面列数。
+- `mine_num`：雷数。
+- `x0`：起手位置在第几行。
+- `y0`：起手位置在第几列。
+- `op_start`：是否起手开空。True时按laymine_op的规则埋雷，否则按laymine的规则埋雷。
+- `n`：局数。
+- `seed`：可选的种子。
+- `thread_num`：线程数。传入`control`时以`control`的线程数为准。
+- `control`：可选的控制器。
+
+# 返回值
+各指标的分布，以及实际采样的局数（被取消时少于n）。"""
+
+
+def set_board_histograms(row: int, column: int, mine_num: int, x0: int, y0: int, op_start: bool,
+                         histograms: BoardHistograms) -> None:
+    """设置某种局面的指标分布表，例如sample_board_histograms的结果，或者从文件中读取的预先计算好的表。
+计算这种局面的百分位之前，必须先设置分布表。
+
+# 参数
+- `row`: 局面行数。
+- `column`：局面列数。
+- `mine_num`：雷数。
+- `x0`：起手位置在第几行。
+- `y0`：起手位置在第几列。
+- `op_start`：是否起手开空。
+- `histograms`：分布表，例如sample_board_histograms的结果。"""
 
 
 def solve_direct(a_mats: List[List[List[int]]], xs: List[List[tuple[int, int]]], bs: List[List[int]], board_of_game: List[List[int]]) -> tuple[List[List[List[int]]],
@@ -122,6 +459,21 @@ def solve_minus(a_mats: List[List[List[int]]], xs: List[List[tuple[int, int]]],
                                                                                                                                               List[List[tuple[int, int]]], List[List[int]], List[List[int]], List[tuple[int, int]], List[tuple[int, int]]]: ...
 
 
+def suggest_moves(game_board: List[List[int]], mine_num: float) -> List[tuple[tuple[int, int], float, float, bool]]:
+    """基于概率引擎的走法建议。
+
+# 参数
+- `game_board`: 游戏局面。自动纠正错误的标雷。
+- `mine_num`：雷数。>=1时，理解为总的雷数；<1时，理解为雷的比例。
+
+# 返回值
+按推荐程度从高到低排序的候选格子列表，每个元素为(位置, 安全率, 进展概率, 是否是死格)。
+存在必然安全的格子时，只返回所有必然安全的格子。进展概率指点开该格后，能推理出新的必然安全的格子的概率。
+
+# 异常
+- `PyRuntimeError`: `无解的局面`、`枚举过长`、`非法的局面`三种。"""
+
+
 def unsolvable_structure(board_check: List[List[int]]) -> bool: ...
 
 
@@ -134,7 +486,20 @@ def valid_time_period(software: str) -> tuple[str, str]:
 # 返回值
 秒为单位的开始时间戳字符串、秒为单位的结束时间戳字符串"""
 
-# === Classes (15) ===
+
+def zini_ops_to_step_flow(ops: List[tuple[str, tuple[int, int]]]) -> List[tuple[str, tuple[int, int]]]:
+    """把ZiNi的操作序列转换成MinesweeperBoard.step_flow可以执行的鼠标事件，用于回放、验证路线。
+
+# 参数
+- `ops`：cal_zini_ops返回的操作序列。
+
+# 返回值
+鼠标事件的序列。左键转换为lc、lr，右键转换为rc、rr，双击转换为lc、rc、lr、rr。
+
+# 异常
+- `PyValueError`：未知的操作类型。"""
+
+# === Classes (22) ===
 
 
 class AvfVideo:
@@ -173,6 +538,10 @@ class AvfVideo:
     cell6: int
     cell7: int
     cell8: int
+    direct_cells: int
+    minus_cells: int
+    enum_cells: int
+    guesses: int
     rtime: float
     rtime_ms: int
     etime: float
@@ -198,6 +567,8 @@ class AvfVideo:
     ce_s: float
     ioe: float
     thrp: float
+    zini_solved: int
+    zioe: float
     corr: float
     pluck: float
     events: List[VideoActionStateRecorder]
@@ -246,6 +617,10 @@ class BaseVideo:
     cell6: int
     cell7: int
     cell8: int
+    direct_cells: int
+    minus_cells: int
+    enum_cells: int
+    guesses: int
     rtime: float
     rtime_ms: int
     etime: float
@@ -273,6 +648,8 @@ class BaseVideo:
     ce_s: float
     ioe: float
     thrp: float
+    zini_solved: int
+    zioe: float
     corr: float
     pluck: float
     events: List[VideoActionStateRecorder]
@@ -299,13 +676,27 @@ class BaseVideo:
     def loss_then_open_all_mine(self) -> None: ...
     def reset(self, row: int, column: int, pix_size: int) -> None: ...
     def save_to_evf_file(self, file_name: str) -> None: ...
+    def set_first_click_policy(self, policy: int, seed: int = 0) -> None:
+        """设置第一下点击的规则，第一下左键打开格子时按规则移动雷并重新计算数字。
+只能在游戏开始前设置，录像中记录移动后的局面。
+# 参数
+- policy: 0-不移动雷；1-第一下是雷时移到左上角（Windows XP）；2-第一下是雷时随机移到别处（阿比特）；3-第一下及周围3×3的雷都随机移走，保证开空（Win7）。
+- seed: 随机移动雷时的种子，相同的局面和种子得到相同的结果。"""
+    def set_topology(self, topology: int) -> None:
+        """设置局面的拓扑结构，只能在游戏开始前设置。双击、打开空以及3BV、Op、Isl按此计算。
+# 参数
+- topology: 0-平面，1-环面。局面上的数字须已按此拓扑结构计算。"""
     def step(self, e: str, pos: tuple[int, int]) -> None: ...
     def step_game_state(self, e: str) -> None: ...
     def win_then_flag_all_mine(self) -> None: ...
 
 
 class Board:
-    def __init__(self, board: List[List[int]]) -> None: ...
+    def __init__(self, board: List[List[int]], topology: int = 0) -> None:
+        """包装真实局面，按需计算并缓存各项指标。
+# 参数
+- board: 真实局面，数字须已按topology计算。
+- topology: 局面的拓扑结构，0-平面，1-环面。3BV、Op、Isl及空、岛按此计算，ZiNi、解题复杂度和百分位仍按平面计算。"""
 
     bbbv: int
     zini: int
@@ -322,6 +713,22 @@ class Board:
     cell7: int
     cell8: int
 
+    def solution_complexity(self, x0: int, y0: int) -> tuple[int, int, int, int]:
+        """从(x0, y0)起手的解题复杂度：(单集合判出的格子数, 双集合判出的格子数, 枚举或总雷数判出的格子数, 必须猜的次数)。
+每次猜未知格子中概率最小的一个（选择时不看真实局面），猜到雷时把它标出并继续。随空一起打开的格子不计入。"""
+    def openings(self) -> List[List[tuple[int, int]]]:
+        """每一片空的格子，包括其中的0和边缘上的数字，即点开这片空时打开的所有格子。与两片空相邻的数字在两片空中都会出现。"""
+    def islands(self) -> List[List[tuple[int, int]]]:
+        """每一个岛（不与空相邻的数字的8连通域）的格子。岛上的每个数字都计1个3BV，因此岛的3BV即格子数。"""
+    def bbbv_labels(self) -> List[List[int]]:
+        """每个格子在3BV中的分类：-1为雷，0为空中的0，1为空边缘上的数字（不单独计3BV），2为岛上的数字（每个计1个3BV）。"""
+    def percentiles(self, x0: int, y0: int, op_start: bool = False) -> tuple[float, float]:
+        """3BV与ZiNi在同尺寸、同雷数、同起手位置、同起手规则的局面中的百分位（0~100）。
+分布表须先用set_board_histograms设置，不会自动采样，因此只需计算本局面的3BV与ZiNi。
+没有设置分布表时抛出`PyRuntimeError`: `没有设置这种局面的分布表`，不是平面局面时抛出`不是平面局面`。"""
+    def difficulty(self, x0: int, y0: int, op_start: bool = False) -> float:
+        """综合难度（0~100），为3BV与ZiNi的百分位的平均值，越大越难。代价与异常同percentiles。"""
+
 
 class EvfVideo:
     def __init__(self, file_name: str = '',
@@ -359,6 +766,10 @@ class EvfVideo:
     cell6: int
     cell7: int
     cell8: int
+    direct_cells: int
+    minus_cells: int
+    enum_cells: int
+    guesses: int
     rtime: float
     rtime_ms: int
     etime: float
@@ -384,6 +795,8 @@ class EvfVideo:
     ce_s: float
     ioe: float
     thrp: float
+    zini_solved: int
+    zioe: float
     corr: float
     pluck: float
     events: List[VideoActionStateRecorder]
@@ -456,6 +869,7 @@ class KeyDynamicParams:
     bbbv_solved: int
     op_solved: int
     isl_solved: int
+    zini_solved: int
 
 
 class MinesweeperBoard:
@@ -480,6 +894,15 @@ class MinesweeperBoard:
     def get_game_board_2(self, mine_num: float) -> List[List[List[float]]]: ...
     def reset(self) -> None: ...
     def step(self, e: str, pos: tuple[int, int]) -> None: ...
+    def set_first_click_policy(self, policy: int, seed: int = 0) -> None:
+        """设置第一下点击的规则，第一下左键打开格子时按规则移动雷并重新计算数字。
+# 参数
+- policy: 0-不移动雷；1-第一下是雷时移到左上角（Windows XP）；2-第一下是雷时随机移到别处（阿比特）；3-第一下及周围3×3的雷都随机移走，保证开空（Win7）。
+- seed: 随机移动雷时的种子，相同的局面和种子得到相同的结果。"""
+    def set_topology(self, topology: int) -> None:
+        """设置局面的拓扑结构，双击、打开空时按此计算周围的格子。
+# 参数
+- topology: 0-平面，1-环面。局面上的数字须已按此拓扑结构计算。"""
     def step_flow(
         self, operation: List[tuple[str, tuple[int, int]]]) -> None: ...
 
@@ -520,6 +943,10 @@ class MvfVideo:
     cell6: int
     cell7: int
     cell8: int
+    direct_cells: int
+    minus_cells: int
+    enum_cells: int
+    guesses: int
     rtime: float
     rtime_ms: int
     etime: float
@@ -545,6 +972,8 @@ class MvfVideo:
     ce_s: float
     ioe: float
     thrp: float
+    zini_solved: int
+    zioe: float
     corr: float
     pluck: float
     events: List[VideoActionStateRecorder]
@@ -596,6 +1025,10 @@ class RmvVideo:
     cell6: int
     cell7: int
     cell8: int
+    direct_cells: int
+    minus_cells: int
+    enum_cells: int
+    guesses: int
     rtime: float
     rtime_ms: int
     etime: float
@@ -621,6 +1054,8 @@ class RmvVideo:
     ce_s: float
     ioe: float
     thrp: float
+    zini_solved: int
+    zioe: float
     corr: float
     pluck: float
     events: List[VideoActionStateRecorder]
@@ -668,10 +1103,94 @@ class SafeMinesweeperBoard:
 
     def get_game_board_2(self, mine_num: float) -> List[List[List[float]]]: ...
     def step(self, e: str, pos: tuple[int, int]) -> None: ...
+    def set_first_click_policy(self, policy: int, seed: int = 0) -> None:
+        """设置第一下点击的规则，第一下左键打开格子时按规则移动雷并重新计算数字。
+# 参数
+- policy: 0-不移动雷；1-第一下是雷时移到左上角（Windows XP）；2-第一下是雷时随机移到别处（阿比特）；3-第一下及周围3×3的雷都随机移走，保证开空（Win7）。
+- seed: 随机移动雷时的种子，相同的局面和种子得到相同的结果。"""
+    def set_topology(self, topology: int) -> None:
+        """设置局面的拓扑结构，双击、打开空时按此计算周围的格子。
+# 参数
+- topology: 0-平面，1-环面。局面上的数字须已按此拓扑结构计算。"""
     def step_flow(
         self, operation: List[tuple[str, tuple[int, int]]]) -> None: ...
 
 
+class BoardHistograms:
+    """局面指标的分布。samples为采样的局数；bbbv、op、isl、zini的下标为指标的值，元素为该值出现的局数。"""
+
+    def __init__(self, samples: int, bbbv: List[int], op: List[int], isl: List[int],
+                 zini: List[int]) -> None: ...
+
+    samples: int
+    bbbv: List[int]
+    op: List[int]
+    isl: List[int]
+    zini: List[int]
+
+
+class GuessStats:
+    """按最优策略玩某个局面时，猜的次数与存活率的统计。is_exact为是否精确计算；runs为不同走法的数量或模拟的局数；
+mean_guesses为平均猜的次数，包括猜错的那一次；distribution的下标为猜的次数，元素为概率；survival为扫开的概率，即各次猜的安全率（概率引擎给出）的乘积的均值，而不是在这个局面上是否猜对；
+guesses为猜的位置（行，列）、在此处猜的概率、在此处猜时的平均安全率，按概率从大到小排列。"""
+
+    is_exact: bool
+    runs: int
+    mean_guesses: float
+    distribution: List[float]
+    survival: float
+    guesses: List[tuple[tuple[int, int], float, float]]
+
+
+class Hint:
+    """提示：推荐的下一步操作，以及理由。action为"left"（打开）或"right"（标雷）；
+rule为"direct"、"minus"、"enumerate"（与SolveStep相同），没有能确定的格子、按概率选择时为None；
+witnesses为作为依据的数字的位置；safety为不是雷的概率。"""
+
+    cell: tuple[int, int]
+    action: str
+    rule: Optional[str]
+    witnesses: List[tuple[int, int]]
+    safety: float
+
+
+class PatternMatch:
+    """游戏局面上识别出的一个定式。name为"1-1"、"1-2"、"1-2-1"、"1-2-2-1"、"hole"（洞）或"triangle"（角上的三角形）。"""
+
+    name: str
+    numbers: List[tuple[int, int]]
+    mines: List[tuple[int, int]]
+    not_mines: List[tuple[int, int]]
+
+
+class SolveStep:
+    """判雷过程中推理出的一个格子。rule为"direct"（单集合，依据是一个数字）、"minus"（双集合，依据是两个数字）
+或"enumerate"（枚举，依据是同一段中的所有数字）。同一轮（step）中的格子是在同一个局面上推理出来的，
+后推理出的格子的witnesses还包括推理出同一轮中先前的格子所用的数字。"""
+
+    step: int
+    cell: tuple[int, int]
+    is_mine: bool
+    rule: str
+    witnesses: List[tuple[int, int]]
+
+
+class ThreadControl:
+    """多线程计算的控制器：指定线程数、查询进度、提前取消。
+可以在另一个Python线程中查询progress或调用cancel()，计算时会释放GIL。
+每次开始计算时，progress会被清零；取消后要先调用reset()才能再次使用。"""
+
+    def __init__(self, thread_num: int = 8) -> None: ...
+
+    thread_num: int
+    progress: int
+    is_cancelled: bool
+
+    def cancel(self) -> None: ...
+
+    def reset(self) -> None: ...
+
+
 class VideoActionStateRecorder:
     def __init__(self, *args, **kwargs) -> None: ...
 
@@ -93,6 +93,10 @@ impl PyKeyDynamicParams {
     fn get_isl_solved(&self) -> PyResult<usize> {
         Ok(self.core.isl_solved)
     }
+    #[getter]
+    fn get_zini_solved(&self) -> PyResult<usize> {
+        Ok(self.core.zini_solved)
+    }
 }
 
 #[pyclass(name = "Event", unsendable)]
@@ -529,6 +533,26 @@ impl PyBaseVideo {
         Ok(self.core.static_params.hzini)
     }
     #[getter]
+    fn get_nf_zini(&self) -> PyResult<usize> {
+        Ok(self.core.static_params.nf_zini)
+    }
+    #[getter]
+    fn get_direct_cells(&mut self) -> PyResult<usize> {
+        Ok(self.core.get_solution_complexity().unwrap_or_default().direct)
+    }
+    #[getter]
+    fn get_minus_cells(&mut self) -> PyResult<usize> {
+        Ok(self.core.get_solution_complexity().unwrap_or_default().minus)
+    }
+    #[getter]
+    fn get_enum_cells(&mut self) -> PyResult<usize> {
+        Ok(self.core.get_solution_complexity().unwrap_or_default().enumerate)
+    }
+    #[getter]
+    fn get_guesses(&mut self) -> PyResult<usize> {
+        Ok(self.core.get_solution_complexity().unwrap_or_default().guess)
+    }
+    #[getter]
     fn get_start_time(&self) -> PyResult<u64> {
         Ok(self.core.start_time)
     }
@@ -693,6 +717,14 @@ impl PyBaseVideo {
         Ok(self.core.get_thrp().unwrap())
     }
     #[getter]
+    fn get_zini_solved(&self) -> PyResult<usize> {
+        Ok(self.core.get_zini_solved().unwrap())
+    }
+    #[getter]
+    fn get_zioe(&self) -> PyResult<f64> {
+        Ok(self.core.get_zioe().unwrap())
+    }
+    #[getter]
     fn get_corr(&self) -> PyResult<f64> {
         Ok(self.core.get_corr().unwrap())
     }
@@ -735,6 +767,23 @@ impl PyBaseVideo {
     pub fn set_board(&mut self, board: Vec<Vec<i32>>) {
         self.core.set_board(board).unwrap();
     }
+    #[pyo3(signature = (policy, seed = 0))]
+    pub fn set_first_click_policy(&mut self, policy: usize, seed: u64) -> PyResult<()> {
+        self.core
+            .set_first_click_policy(crate::board::get_first_click_policy(policy)?, seed)
+            .map_err(|_| {
+                pyo3::exceptions::PyRuntimeError::new_err("the game has already started")
+            })?;
+        Ok(())
+    }
+    pub fn set_topology(&mut self, topology: usize) -> PyResult<()> {
+        self.core
+            .set_topology(crate::get_topology(topology)?)
+            .map_err(|_| {
+                pyo3::exceptions::PyRuntimeError::new_err("the game has already started")
+            })?;
+        Ok(())
+    }
     #[getter]
     fn get_board(&self) -> PyResult<PySafeBoard> {
         let t = PySafeBoard::new(self.core.minesweeper_board.board.into_vec_vec());
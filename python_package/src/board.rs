@@ -12,6 +12,19 @@ pub struct PySafeMinesweeperBoard {
     pub core: MinesweeperBoard<SafeBoard>,
 }
 
+/// 按ffi约定把整数转成第一下点击的规则：0-不移动雷，1-移到左上角，2-随机移到别处，3-保证开空
+pub fn get_first_click_policy(policy: usize) -> PyResult<FirstClickPolicy> {
+    match policy {
+        0 => Ok(FirstClickPolicy::None),
+        1 => Ok(FirstClickPolicy::SafeCellCorner),
+        2 => Ok(FirstClickPolicy::SafeCell),
+        3 => Ok(FirstClickPolicy::SafeOpening),
+        _ => Err(pyo3::exceptions::PyValueError::new_err(
+            "first click policy must be 0, 1, 2 or 3",
+        )),
+    }
+}
+
 #[pymethods]
 impl PyMinesweeperBoard {
     #[new]
@@ -28,6 +41,16 @@ impl PyMinesweeperBoard {
     pub fn step_flow(&mut self, operation: Vec<(String, (usize, usize))>) {
         self.core.step_flow(&operation).unwrap();
     }
+    #[pyo3(signature = (policy, seed = 0))]
+    pub fn set_first_click_policy(&mut self, policy: usize, seed: u64) -> PyResult<()> {
+        self.core
+            .set_first_click_policy(get_first_click_policy(policy)?, seed);
+        Ok(())
+    }
+    pub fn set_topology(&mut self, topology: usize) -> PyResult<()> {
+        self.core.set_topology(crate::get_topology(topology)?);
+        Ok(())
+    }
     // 这个方法与强可猜、弱可猜、埋雷有关
     #[setter]
     fn set_board(&mut self, board: Vec<Vec<i32>>) {
@@ -164,6 +187,16 @@ impl PySafeMinesweeperBoard {
     pub fn step_flow(&mut self, operation: Vec<(String, (usize, usize))>) {
         self.core.step_flow(&operation).unwrap();
     }
+    #[pyo3(signature = (policy, seed = 0))]
+    pub fn set_first_click_policy(&mut self, policy: usize, seed: u64) -> PyResult<()> {
+        self.core
+            .set_first_click_policy(get_first_click_policy(policy)?, seed);
+        Ok(())
+    }
+    pub fn set_topology(&mut self, topology: usize) -> PyResult<()> {
+        self.core.set_topology(crate::get_topology(topology)?);
+        Ok(())
+    }
     // 这个方法与强可猜、弱可猜、埋雷有关
     #[setter]
     fn set_board(&mut self, board: Vec<Vec<i32>>) {
@@ -1,4 +1,5 @@
 use ms_toollib_original::*;
+use pyo3::exceptions::PyRuntimeError;
 use pyo3::prelude::*;
 
 #[pyclass(name = "GameBoard")]
@@ -58,9 +59,10 @@ pub struct PyBoard {
 #[pymethods]
 impl PyBoard {
     #[new]
-    pub fn new(board: Vec<Vec<i32>>) -> PyBoard {
-        let c = Board::new(board);
-        PyBoard { core: c }
+    #[pyo3(signature = (board, topology = 0))]
+    pub fn new(board: Vec<Vec<i32>>, topology: usize) -> PyResult<PyBoard> {
+        let c = Board::new_with_topology(board, crate::get_topology(topology)?);
+        Ok(PyBoard { core: c })
     }
     #[getter]
     fn get_bbbv(&mut self) -> PyResult<usize> {
@@ -118,4 +120,46 @@ impl PyBoard {
     fn get_cell8(&mut self) -> PyResult<usize> {
         Ok(self.core.get_cell8())
     }
+    /// 返回(单集合判出的格子数, 双集合判出的格子数, 枚举判出的格子数, 必须猜的次数)
+    fn solution_complexity(&mut self, x0: usize, y0: usize) -> PyResult<(usize, usize, usize, usize)> {
+        let c = self.core.get_solution_complexity(x0, y0);
+        Ok((c.direct, c.minus, c.enumerate, c.guess))
+    }
+    fn openings(&mut self) -> PyResult<Vec<Vec<(usize, usize)>>> {
+        Ok(self.core.openings())
+    }
+    fn islands(&mut self) -> PyResult<Vec<Vec<(usize, usize)>>> {
+        Ok(self.core.islands())
+    }
+    fn bbbv_labels(&self) -> PyResult<Vec<Vec<i32>>> {
+        Ok(self.core.bbbv_labels())
+    }
+    #[pyo3(signature = (x0, y0, op_start = false))]
+    fn percentiles(
+        &mut self,
+        py: Python<'_>,
+        x0: usize,
+        y0: usize,
+        op_start: bool,
+    ) -> PyResult<(f64, f64)> {
+        match py.detach(|| self.core.get_percentiles(x0, y0, op_start)) {
+            Ok(p) => Ok(p),
+            Err(1) => Err(PyRuntimeError::new_err("没有设置这种局面的分布表")),
+            Err(_) => Err(PyRuntimeError::new_err("不是平面局面")),
+        }
+    }
+    #[pyo3(signature = (x0, y0, op_start = false))]
+    fn difficulty(
+        &mut self,
+        py: Python<'_>,
+        x0: usize,
+        y0: usize,
+        op_start: bool,
+    ) -> PyResult<f64> {
+        match py.detach(|| self.core.get_difficulty(x0, y0, op_start)) {
+            Ok(d) => Ok(d),
+            Err(1) => Err(PyRuntimeError::new_err("没有设置这种局面的分布表")),
+            Err(_) => Err(PyRuntimeError::new_err("不是平面局面")),
+        }
+    }
 }
@@ -1,6 +1,7 @@
 use pyo3::exceptions::PyRuntimeError;
 use pyo3::prelude::*;
 use pyo3::wrap_pyfunction;
+use num_bigint::BigUint;
 // use pyo3::PyTraverseError;
 // use pyo3::class::basic::PyObjectProtocol;
 // use std::cmp::{max, min};
@@ -84,13 +85,33 @@ fn py_refresh_matrixses(
     Ok(refresh_matrixses(&board_of_game))
 }
 
+/// 按ffi约定把整数转成局面的拓扑结构：0-平面，1-环面
+pub fn get_topology(topology: usize) -> PyResult<Topology> {
+    match topology {
+        0 => Ok(Topology::Plane),
+        1 => Ok(Topology::Torus),
+        _ => Err(pyo3::exceptions::PyValueError::new_err(
+            "topology must be 0 or 1",
+        )),
+    }
+}
+
 #[pyfunction]
 #[pyo3(
     name = "cal_op",
-    signature = (board)
+    signature = (board, topology = 0)
+)]
+fn py_cal_op(board: Vec<Vec<i32>>, topology: usize) -> PyResult<usize> {
+    Ok(cal_op_with_topology(&board, get_topology(topology)?))
+}
+
+#[pyfunction]
+#[pyo3(
+    name = "cal_isl",
+    signature = (board, topology = 0)
 )]
-fn py_cal_op(board: Vec<Vec<i32>>) -> PyResult<usize> {
-    Ok(cal_op(&board))
+fn py_cal_isl(board: Vec<Vec<i32>>, topology: usize) -> PyResult<usize> {
+    Ok(cal_isl_with_topology(&board, get_topology(topology)?))
 }
 
 ///  通用标准埋雷引擎。起手位置非雷，其余位置的雷服从均匀分布。
@@ -101,28 +122,68 @@ fn py_cal_op(board: Vec<Vec<i32>>) -> PyResult<usize> {
 /// - `mine_num`：雷数。
 /// - `x0`：起手位置在第几行。
 /// - `y0`：起手位置在第几列。
+/// - `seed`：可选的种子。给定种子时，相同的种子在各个平台上生成相同的局面。
+/// - `topology`：局面的拓扑结构，0-平面，1-环面。
 ///
 /// # 返回值
 /// 二维的局面，其中0代表空，1~8代表1~8，-1代表雷。
 #[pyfunction]
-#[pyo3(name = "laymine", signature = (row, column, mine_num, x0, y0))]
+#[pyo3(name = "laymine", signature = (row, column, mine_num, x0, y0, seed = None, topology = 0))]
 fn py_laymine(
     row: usize,
     column: usize,
     mine_num: usize,
     x0: usize,
     y0: usize,
+    seed: Option<u64>,
+    topology: usize,
+) -> PyResult<Vec<Vec<i32>>> {
+    let topology = get_topology(topology)?;
+    match (seed, topology) {
+        (Some(seed), Topology::Plane) => Ok(laymine_seeded(row, column, mine_num, x0, y0, seed)),
+        (None, Topology::Plane) => Ok(laymine(row, column, mine_num, x0, y0)),
+        (Some(seed), _) => Ok(laymine_with_topology_seeded(
+            row, column, mine_num, x0, y0, false, topology, seed,
+        )),
+        (None, _) => Ok(laymine_with_topology(
+            row, column, mine_num, x0, y0, false, topology,
+        )),
+    }
+}
+
+/// 带遮挡的标准埋雷引擎，用于非矩形的局面。起手位置非雷，其余没有被遮挡的位置的雷服从均匀分布。
+///
+/// # 参数
+/// - `mask`：遮挡，True代表该位置没有格子。局面的尺寸与遮挡相同。
+/// - `mine_num`：雷数。
+/// - `x0`：起手位置在第几行。
+/// - `y0`：起手位置在第几列。
+/// - `seed`：可选的种子。给定种子时，相同的种子在各个平台上生成相同的局面。
+///
+/// # 返回值
+/// 二维的局面，其中0代表空，1~8代表1~8，-1代表雷，-2代表被遮挡的位置。
+#[pyfunction]
+#[pyo3(name = "laymine_masked", signature = (mask, mine_num, x0, y0, seed = None))]
+fn py_laymine_masked(
+    mask: Vec<Vec<bool>>,
+    mine_num: usize,
+    x0: usize,
+    y0: usize,
+    seed: Option<u64>,
 ) -> PyResult<Vec<Vec<i32>>> {
-    Ok(laymine(row, column, mine_num, x0, y0))
+    match seed {
+        Some(seed) => Ok(laymine_masked_seeded(&mask, mine_num, x0, y0, seed)),
+        None => Ok(laymine_masked(&mask, mine_num, x0, y0)),
+    }
 }
 
 #[pyfunction]
 #[pyo3(
     name = "cal_bbbv",
-    signature = (board)
+    signature = (board, topology = 0)
 )]
-fn py_cal_bbbv(board: Vec<Vec<i32>>) -> PyResult<usize> {
-    Ok(cal_bbbv(&board))
+fn py_cal_bbbv(board: Vec<Vec<i32>>, topology: usize) -> PyResult<usize> {
+    Ok(cal_bbbv_with_topology(&board, get_topology(topology)?))
 }
 
 #[pyfunction]
@@ -152,6 +213,110 @@ fn py_cal_rzini(board: Vec<Vec<i32>>, loop_count: usize) -> PyResult<usize> {
     Ok(cal_rzini(&board, loop_count))
 }
 
+/// ZiNi的路线，即扫完局面的操作序列。
+///
+/// # 参数
+/// - `board`：真实局面。
+/// - `method`：算法，"greedy"（同cal_zini）、"human"（同cal_hzini）、"random"（同cal_rzini）或"nf"（不标雷，等于3BV）。
+/// - `loop_count`：method为"random"时的迭代轮数。
+///
+/// # 返回值
+/// 按顺序排列的操作，每个操作为（操作类型, (行, 列)），操作类型为"left"、"right"、"chord"。
+/// 操作的个数等于对应的ZiNi。
+///
+/// # 异常
+/// - `PyValueError`：未知的算法。
+#[pyfunction]
+#[pyo3(
+    name = "cal_zini_ops",
+    signature = (board, method = "greedy", loop_count = 100)
+)]
+fn py_cal_zini_ops(
+    board: Vec<Vec<i32>>,
+    method: &str,
+    loop_count: usize,
+) -> PyResult<Vec<(String, (usize, usize))>> {
+    let ops = match method {
+        "greedy" => cal_zini_ops(&board),
+        "human" => cal_hzini_ops(&board),
+        "random" => cal_rzini_ops(&board, loop_count),
+        "nf" => cal_nf_zini_ops(&board),
+        _ => {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "method must be \"greedy\", \"human\", \"random\" or \"nf\"",
+            ))
+        }
+    };
+    Ok(zini_ops_to_strings(ops))
+}
+
+// 操作类型转换成字符串，用于返回给python
+fn zini_ops_to_strings(ops: Vec<(ZiniOp, (usize, usize))>) -> Vec<(String, (usize, usize))> {
+    ops.into_iter()
+        .map(|(op, pos)| (op.as_str().to_string(), pos))
+        .collect()
+}
+
+/// 把ZiNi的操作序列转换成MinesweeperBoard.step_flow可以执行的鼠标事件，用于回放、验证路线。
+///
+/// # 参数
+/// - `ops`：cal_zini_ops返回的操作序列。
+///
+/// # 返回值
+/// 鼠标事件的序列。左键转换为lc、lr，右键转换为rc、rr，双击转换为lc、rc、lr、rr。
+///
+/// # 异常
+/// - `PyValueError`：未知的操作类型。
+#[pyfunction]
+#[pyo3(name = "zini_ops_to_step_flow", signature = (ops))]
+fn py_zini_ops_to_step_flow(
+    ops: Vec<(String, (usize, usize))>,
+) -> PyResult<Vec<(String, (usize, usize))>> {
+    let ops = ops
+        .iter()
+        .map(|(op, pos)| match op.parse::<ZiniOp>() {
+            Ok(op) => Ok((op, *pos)),
+            Err(_) => Err(pyo3::exceptions::PyValueError::new_err(
+                "op must be \"left\", \"right\" or \"chord\"",
+            )),
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+    Ok(zini_ops_to_step_flow(&ops))
+}
+
+/// 最少点击数（精确的ZiNi），用分支定界搜索。搜索量随局面增大急剧增加，适合初级、中级及以下的局面。
+///
+/// # 参数
+/// - `board`：真实局面。
+/// - `max_nodes`：最多搜索的状态数。
+/// - `max_millis`：最长搜索时间，单位为毫秒。
+///
+/// # 返回值
+/// （点击数, 操作序列, 是否为最优解）。超出限制时返回目前找到的最好的结果，至少不比cal_zini差。
+/// 操作序列的格式与cal_zini_ops相同。
+#[pyfunction]
+#[pyo3(
+    name = "cal_optimal_clicks",
+    signature = (board, max_nodes = 2_000_000, max_millis = 10_000)
+)]
+fn py_cal_optimal_clicks(
+    py: Python<'_>,
+    board: Vec<Vec<i32>>,
+    max_nodes: usize,
+    max_millis: u64,
+) -> PyResult<(usize, Vec<(String, (usize, usize))>, bool)> {
+    let (clicks, ops, is_optimal) = py.detach(|| {
+        cal_optimal_clicks(
+            &board,
+            OptimalClicksLimits {
+                max_nodes,
+                max_millis,
+            },
+        )
+    });
+    Ok((clicks, zini_ops_to_strings(ops), is_optimal))
+}
+
 #[pyfunction]
 #[pyo3(name = "solve_minus")]
 fn py_solve_minus(
@@ -181,13 +346,17 @@ fn py_solve_minus(
 }
 
 #[pyfunction]
-#[pyo3(name = "refresh_board")]
+#[pyo3(
+    name = "refresh_board",
+    signature = (board, board_of_game, clicked_poses, topology = 0)
+)]
 fn py_refresh_board(
     board: Vec<Vec<i32>>,
     mut board_of_game: Vec<Vec<i32>>,
     clicked_poses: Vec<(usize, usize)>,
+    topology: usize,
 ) -> PyResult<Vec<Vec<i32>>> {
-    refresh_board(&board, &mut board_of_game, clicked_poses);
+    refresh_board_with_topology(&board, &mut board_of_game, clicked_poses, get_topology(topology)?);
     Ok(board_of_game)
 }
 
@@ -249,7 +418,7 @@ fn py_solve_direct(
 #[pyfunction]
 #[pyo3(
     name = "laymine_op",
-    signature = (row, column, mine_num, x0, y0)
+    signature = (row, column, mine_num, x0, y0, seed = None, topology = 0)
 )]
 fn py_laymine_op(
     row: usize,
@@ -257,8 +426,20 @@ fn py_laymine_op(
     mine_num: usize,
     x0: usize,
     y0: usize,
+    seed: Option<u64>,
+    topology: usize,
 ) -> PyResult<Vec<Vec<i32>>> {
-    Ok(laymine_op(row, column, mine_num, x0, y0))
+    let topology = get_topology(topology)?;
+    match (seed, topology) {
+        (Some(seed), Topology::Plane) => Ok(laymine_op_seeded(row, column, mine_num, x0, y0, seed)),
+        (None, Topology::Plane) => Ok(laymine_op(row, column, mine_num, x0, y0)),
+        (Some(seed), _) => Ok(laymine_with_topology_seeded(
+            row, column, mine_num, x0, y0, true, topology, seed,
+        )),
+        (None, _) => Ok(laymine_with_topology(
+            row, column, mine_num, x0, y0, true, topology,
+        )),
+    }
 }
 
 #[pyfunction]
@@ -280,16 +461,58 @@ fn py_unsolvable_structure(board_check: Vec<Vec<i32>>) -> PyResult<bool> {
 #[pyfunction]
 #[pyo3(
     name = "is_solvable",
-    signature = (board, x0, y0)
+    signature = (board, x0, y0, topology = 0)
 )]
-fn py_is_solvable(board: Vec<Vec<i32>>, x0: usize, y0: usize) -> PyResult<bool> {
-    Ok(is_solvable(&board, x0, y0))
+fn py_is_solvable(board: Vec<Vec<i32>>, x0: usize, y0: usize, topology: usize) -> PyResult<bool> {
+    Ok(is_solvable_with_topology(&board, x0, y0, get_topology(topology)?))
+}
+
+/// 判雷过程中推理出的一个格子。
+#[pyclass(name = "SolveStep")]
+pub struct PySolveStep {
+    pub core: SolveStep,
+}
+
+#[pymethods]
+impl PySolveStep {
+    #[getter]
+    fn get_step(&self) -> PyResult<usize> {
+        Ok(self.core.step)
+    }
+    #[getter]
+    fn get_cell(&self) -> PyResult<(usize, usize)> {
+        Ok(self.core.cell)
+    }
+    #[getter]
+    fn get_is_mine(&self) -> PyResult<bool> {
+        Ok(self.core.is_mine)
+    }
+    #[getter]
+    fn get_rule(&self) -> PyResult<&'static str> {
+        Ok(self.core.rule.as_str())
+    }
+    #[getter]
+    fn get_witnesses(&self) -> PyResult<Vec<(usize, usize)>> {
+        Ok(self.core.witnesses.clone())
+    }
+}
+
+#[pyfunction]
+#[pyo3(name = "try_solve_trace", signature = (board, x0, y0))]
+fn py_try_solve_trace(board: Vec<Vec<i32>>, x0: usize, y0: usize) -> PyResult<(Vec<PySolveStep>, bool)> {
+    match try_solve_trace(&board, x0, y0) {
+        Ok((trace, solved)) => {
+            Ok((trace.into_iter().map(|core| PySolveStep { core }).collect(), solved))
+        }
+        Err(6) => Err(PyErr::new::<PyRuntimeError, _>("无解的局面")),
+        Err(_) => Err(PyErr::new::<PyRuntimeError, _>("找不到作为依据的数字")),
+    }
 }
 
 #[pyfunction]
 #[pyo3(
     name = "laymine_solvable",
-    signature = (row, column, mine_num, x0, y0, max_times = 1000000)
+    signature = (row, column, mine_num, x0, y0, max_times = 1000000, seed = None)
 )]
 pub fn py_laymine_solvable(
     row: usize,
@@ -298,32 +521,188 @@ pub fn py_laymine_solvable(
     x0: usize,
     y0: usize,
     max_times: usize,
+    seed: Option<u64>,
 ) -> PyResult<(Vec<Vec<i32>>, bool)> {
-    Ok(laymine_solvable(row, column, mine_num, x0, y0, max_times))
+    match seed {
+        Some(seed) => Ok(laymine_solvable_seeded(
+            row, column, mine_num, x0, y0, max_times, seed,
+        )),
+        None => Ok(laymine_solvable(row, column, mine_num, x0, y0, max_times)),
+    }
+}
+
+/// 多线程计算的控制器：指定线程数、查询进度、提前取消。
+/// 可以在另一个Python线程中查询progress或调用cancel()，计算时会释放GIL。
+#[pyclass(name = "ThreadControl")]
+pub struct PyThreadControl {
+    pub core: ThreadControl,
+}
+
+#[pymethods]
+impl PyThreadControl {
+    #[new]
+    #[pyo3(signature = (thread_num = 8))]
+    pub fn new(thread_num: usize) -> PyThreadControl {
+        PyThreadControl {
+            core: ThreadControl::new(thread_num),
+        }
+    }
+    #[getter]
+    fn get_thread_num(&self) -> PyResult<usize> {
+        Ok(self.core.get_thread_num())
+    }
+    #[getter]
+    fn get_progress(&self) -> PyResult<usize> {
+        Ok(self.core.get_progress())
+    }
+    #[getter]
+    fn get_is_cancelled(&self) -> PyResult<bool> {
+        Ok(self.core.is_cancelled())
+    }
+    fn cancel(&self) {
+        self.core.cancel();
+    }
+    /// 清除进度和取消标志，使被取消的控制器可以再次使用。
+    fn reset(&self) {
+        self.core.reset();
+    }
+}
+
+fn get_thread_control(
+    thread_num: usize,
+    control: Option<PyRef<PyThreadControl>>,
+) -> ThreadControl {
+    match control {
+        Some(c) => c.core.clone(),
+        None => ThreadControl::new(thread_num),
+    }
 }
 
+/// 删选法多线程无猜埋雷。
+///
+/// # 参数
+/// - `thread_num`：线程数，为0时使用系统可用的并行度。传入`control`时以`control`的线程数为准。
+/// - `control`：可选的控制器，用于查询尝试次数、取消计算。
 #[pyfunction]
 #[pyo3(
     name = "laymine_solvable_thread",
-    signature = (row, column, mine_num, x0, y0, max_times = 1000000)
+    signature = (row, column, mine_num, x0, y0, max_times = 1000000, seed = None,
+        thread_num = 8, control = None)
 )]
 pub fn py_laymine_solvable_thread(
+    py: Python<'_>,
     row: usize,
     column: usize,
     mine_num: usize,
     x0: usize,
     y0: usize,
     max_times: usize,
+    seed: Option<u64>,
+    thread_num: usize,
+    control: Option<PyRef<PyThreadControl>>,
 ) -> PyResult<(Vec<Vec<i32>>, bool)> {
-    Ok(laymine_solvable_thread(
-        row, column, mine_num, x0, y0, max_times,
-    ))
+    let control = get_thread_control(thread_num, control);
+    let (board, flag, _) = py.detach(|| {
+        laymine_solvable_thread_with_control(
+            row,
+            column,
+            mine_num,
+            x0,
+            y0,
+            max_times,
+            seed.unwrap_or_else(SeededRng::random_seed),
+            &control,
+        )
+    });
+    Ok((board, flag))
+}
+
+/// 筛选法多线程埋雷。反复埋雷，直到局面满足全部筛选条件。
+///
+/// # 参数
+/// - `row`: 局面行数。
+/// - `column`：局面列数。
+/// - `mine_num`：雷数。
+/// - `x0`：起手位置在第几行。
+/// - `y0`：起手位置在第几列。
+/// - `bbbv`、`op`、`isl`、`zini`、`hzini`、`cell0`~`cell8`：各指标的闭区间(最小值, 最大值)，None代表不限制。
+/// - `solvable`：True时要求无猜，False时要求有猜，None代表不限制。
+/// - `op_start`：是否起手开空。True时按laymine_op的规则埋雷，否则按laymine的规则埋雷。
+/// - `max_times`：最大尝试次数。
+/// - `seed`：可选的种子。
+/// - `thread_num`：线程数，为0时使用系统可用的并行度。传入`control`时以`control`的线程数为准。
+/// - `control`：可选的控制器，用于查询尝试次数、取消计算。
+///
+/// # 返回值
+/// (局面, 是否成功, 被拒绝的局面数)。
+#[pyfunction]
+#[pyo3(
+    name = "laymine_filtered",
+    signature = (row, column, mine_num, x0, y0, bbbv = None, op = None, isl = None, zini = None,
+        hzini = None, cell0 = None, cell1 = None, cell2 = None, cell3 = None, cell4 = None,
+        cell5 = None, cell6 = None, cell7 = None, cell8 = None, solvable = None,
+        op_start = false, max_times = 1000000, seed = None, thread_num = 8, control = None)
+)]
+pub fn py_laymine_filtered(
+    py: Python<'_>,
+    row: usize,
+    column: usize,
+    mine_num: usize,
+    x0: usize,
+    y0: usize,
+    bbbv: Option<(usize, usize)>,
+    op: Option<(usize, usize)>,
+    isl: Option<(usize, usize)>,
+    zini: Option<(usize, usize)>,
+    hzini: Option<(usize, usize)>,
+    cell0: Option<(usize, usize)>,
+    cell1: Option<(usize, usize)>,
+    cell2: Option<(usize, usize)>,
+    cell3: Option<(usize, usize)>,
+    cell4: Option<(usize, usize)>,
+    cell5: Option<(usize, usize)>,
+    cell6: Option<(usize, usize)>,
+    cell7: Option<(usize, usize)>,
+    cell8: Option<(usize, usize)>,
+    solvable: Option<bool>,
+    op_start: bool,
+    max_times: usize,
+    seed: Option<u64>,
+    thread_num: usize,
+    control: Option<PyRef<PyThreadControl>>,
+) -> PyResult<(Vec<Vec<i32>>, bool, usize)> {
+    let filter = BoardFilter {
+        bbbv,
+        op,
+        isl,
+        zini,
+        hzini,
+        cells: [
+            cell0, cell1, cell2, cell3, cell4, cell5, cell6, cell7, cell8,
+        ],
+        solvable,
+    };
+    let control = get_thread_control(thread_num, control);
+    Ok(py.detach(|| {
+        laymine_filtered_with_control(
+            row,
+            column,
+            mine_num,
+            x0,
+            y0,
+            op_start,
+            &filter,
+            max_times,
+            seed.unwrap_or_else(SeededRng::random_seed),
+            &control,
+        )
+    }))
 }
 
 #[pyfunction]
 #[pyo3(
     name = "laymine_solvable_adjust",
-    signature = (row, column, mine_num, x0, y0)
+    signature = (row, column, mine_num, x0, y0, seed = None)
 )]
 pub fn py_laymine_solvable_adjust(
     row: usize,
@@ -331,8 +710,14 @@ pub fn py_laymine_solvable_adjust(
     mine_num: usize,
     x0: usize,
     y0: usize,
+    seed: Option<u64>,
 ) -> PyResult<(Vec<Vec<i32>>, bool)> {
-    Ok(laymine_solvable_adjust(row, column, mine_num, x0, y0))
+    match seed {
+        Some(seed) => Ok(laymine_solvable_adjust_seeded(
+            row, column, mine_num, x0, y0, seed,
+        )),
+        None => Ok(laymine_solvable_adjust(row, column, mine_num, x0, y0)),
+    }
 }
 
 #[pyfunction]
@@ -359,17 +744,19 @@ fn py_cal_probability_enum(
 #[pyfunction]
 #[pyo3(
     name = "cal_probability_csp",
-    signature = (game_board, mine_num)
+    signature = (game_board, mine_num, topology = 0)
 )]
 fn py_cal_probability_csp(
     mut game_board: Vec<Vec<i32>>,
     mine_num: f64,
+    topology: usize,
 ) -> PyResult<(Vec<((usize, usize), f64)>, f64, [usize; 3], usize)> {
     // mine_num为局面中雷的总数，不管有没有标
     // 还返回局面中雷数的范围
-    mark_board(&mut game_board, true)
+    let topology = get_topology(topology)?;
+    mark_board_with_topology(&mut game_board, true, topology)
         .map_err(|_| PyErr::new::<PyRuntimeError, _>("标记阶段无解的局面"))?;
-    match cal_probability_csp(&game_board, mine_num) {
+    match cal_probability_csp_with_topology(&game_board, mine_num, topology) {
         Ok(t) => return Ok(t),
         Err(1) => return Err(PyErr::new::<PyRuntimeError, _>("枚举阶段无解的局面")),
         _ => return Err(PyErr::new::<PyRuntimeError, _>("未知的错误")),
@@ -411,13 +798,466 @@ fn py_cal_probability_onboard(
     };
 }
 
+/// 基于概率引擎的走法建议。
+///
+/// # 参数
+/// - `game_board`: 游戏局面。自动纠正错误的标雷。
+/// - `mine_num`：雷数。>=1时，理解为总的雷数；<1时，理解为雷的比例。
+///
+/// # 返回值
+/// 按推荐程度从高到低排序的候选格子列表，每个元素为(位置, 安全率, 进展概率, 是否是死格)。
+///
+/// # 异常
+/// - `PyRuntimeError`: `无解的局面`、`枚举过长`、`非法的局面`三种。
+#[pyfunction]
+#[pyo3(
+    name = "suggest_moves",
+    signature = (game_board, mine_num)
+)]
+fn py_suggest_moves(
+    game_board: Vec<Vec<i32>>,
+    mine_num: f64,
+) -> PyResult<Vec<((usize, usize), f64, f64, bool)>> {
+    match suggest_moves(&game_board, mine_num) {
+        Ok(t) => Ok(t
+            .into_iter()
+            .map(|m| (m.pos, m.safety, m.progress, m.dead))
+            .collect()),
+        Err(1) => Err(PyErr::new::<PyRuntimeError, _>("无解的局面")),
+        Err(2) => Err(PyErr::new::<PyRuntimeError, _>("枚举过长")),
+        Err(_) => Err(PyErr::new::<PyRuntimeError, _>("非法的局面")),
+    }
+}
+
+/// 提示：推荐的下一步操作，以及理由。
+#[pyclass(name = "Hint")]
+pub struct PyHint {
+    pub core: Hint,
+}
+
+#[pymethods]
+impl PyHint {
+    #[getter]
+    fn get_cell(&self) -> PyResult<(usize, usize)> {
+        Ok(self.core.cell)
+    }
+    #[getter]
+    fn get_action(&self) -> PyResult<&'static str> {
+        Ok(self.core.action.as_str())
+    }
+    #[getter]
+    fn get_rule(&self) -> PyResult<Option<&'static str>> {
+        Ok(self.core.rule.map(|r| r.as_str()))
+    }
+    #[getter]
+    fn get_witnesses(&self) -> PyResult<Vec<(usize, usize)>> {
+        Ok(self.core.witnesses.clone())
+    }
+    #[getter]
+    fn get_safety(&self) -> PyResult<f64> {
+        Ok(self.core.safety)
+    }
+}
+
+/// 提示下一步。依次用单集合、双集合、枚举判雷，选择离光标最近的格子；都判不出时按概率选择。
+///
+/// # 参数
+/// - `game_board`: 游戏局面。玩家标的雷不参与推理。
+/// - `mine_num`：雷数。>=1时，理解为总的雷数；<1时，理解为雷的比例。
+/// - `cursor`：光标位置（行，列）。
+///
+/// # 返回值
+/// 提示，没有未打开的格子时返回None。
+///
+/// # 异常
+/// - `PyRuntimeError`: `无解的局面`、`枚举过长`、`非法的局面`三种。
+#[pyfunction]
+#[pyo3(name = "hint", signature = (game_board, mine_num, cursor))]
+fn py_hint(
+    game_board: Vec<Vec<i32>>,
+    mine_num: f64,
+    cursor: (usize, usize),
+) -> PyResult<Option<PyHint>> {
+    match hint(&game_board, mine_num, cursor) {
+        Ok(h) => Ok(h.map(|core| PyHint { core })),
+        Err(1) | Err(6) => Err(PyErr::new::<PyRuntimeError, _>("无解的局面")),
+        Err(2) => Err(PyErr::new::<PyRuntimeError, _>("枚举过长")),
+        Err(_) => Err(PyErr::new::<PyRuntimeError, _>("非法的局面")),
+    }
+}
+
+/// 按最优策略玩某个局面时，猜的次数与存活率的统计。
+#[pyclass(name = "GuessStats")]
+pub struct PyGuessStats {
+    pub core: GuessStats,
+}
+
+#[pymethods]
+impl PyGuessStats {
+    #[getter]
+    fn get_is_exact(&self) -> PyResult<bool> {
+        Ok(self.core.is_exact)
+    }
+    #[getter]
+    fn get_runs(&self) -> PyResult<usize> {
+        Ok(self.core.runs)
+    }
+    #[getter]
+    fn get_mean_guesses(&self) -> PyResult<f64> {
+        Ok(self.core.mean_guesses)
+    }
+    #[getter]
+    fn get_distribution(&self) -> PyResult<Vec<f64>> {
+        Ok(self.core.distribution.clone())
+    }
+    #[getter]
+    fn get_survival(&self) -> PyResult<f64> {
+        Ok(self.core.survival)
+    }
+    #[getter]
+    fn get_guesses(&self) -> PyResult<Vec<((usize, usize), f64, f64)>> {
+        Ok(self.core.guesses.clone())
+    }
+}
+
+/// 估计按最优策略玩某个局面时，猜的次数与存活率。判不出时在概率引擎的最佳候选中等概率地选一个猜。
+///
+/// # 参数
+/// - `board`: 真实局面。
+/// - `x0`：起手位置在第几行。
+/// - `y0`：起手位置在第几列。
+/// - `runs`：不同走法的数量不超过runs时精确计算，否则模拟runs局。
+/// - `seed`：可选的种子。
+///
+/// # 返回值
+/// 猜的次数的均值、分布，存活率，以及猜的位置。存活率为每种走法上各次猜的安全率之积的均值。
+///
+/// # 异常
+/// - `PyRuntimeError`: `无解的局面`、`枚举过长`两种。
+#[pyfunction]
+#[pyo3(name = "cal_guess_stats", signature = (board, x0, y0, runs = 100, seed = None))]
+fn py_cal_guess_stats(
+    py: Python<'_>,
+    board: Vec<Vec<i32>>,
+    x0: usize,
+    y0: usize,
+    runs: usize,
+    seed: Option<u64>,
+) -> PyResult<PyGuessStats> {
+    let seed = seed.unwrap_or_else(SeededRng::random_seed);
+    match py.detach(|| cal_guess_stats_seeded(&board, x0, y0, runs, seed)) {
+        Ok(core) => Ok(PyGuessStats { core }),
+        Err(2) => Err(PyErr::new::<PyRuntimeError, _>("枚举过长")),
+        Err(_) => Err(PyErr::new::<PyRuntimeError, _>("无解的局面")),
+    }
+}
+
+/// 计算边缘与内部未打开的格子中雷数的概率分布。
+///
+/// # 参数
+/// - `game_board`: 游戏局面。
+/// - `mine_num`：雷数。>=1时，理解为总的雷数；<1时，理解为雷的比例。
+///
+/// # 返回值
+/// 边缘雷数的分布、内部雷数的分布，均为按雷数从小到大排列的（雷数, 概率）。
+#[pyfunction]
+#[pyo3(
+    name = "cal_mine_count_distribution",
+    signature = (game_board, mine_num)
+)]
+fn py_cal_mine_count_distribution(
+    game_board: Vec<Vec<i32>>,
+    mine_num: f64,
+) -> PyResult<(Vec<(usize, f64)>, Vec<(usize, f64)>)> {
+    match cal_mine_count_distribution(&game_board, mine_num) {
+        Ok(t) => Ok(t),
+        Err(1) => Err(PyErr::new::<PyRuntimeError, _>("无解的局面")),
+        Err(2) => Err(PyErr::new::<PyRuntimeError, _>("枚举过长")),
+        Err(_) => Err(PyErr::new::<PyRuntimeError, _>("非法的局面")),
+    }
+}
+
+/// 精确计算与局面相容的雷的排布总数，以及每个格子是雷的排布数。
+///
+/// # 参数
+/// - `game_board`: 游戏局面。
+/// - `mine_num`：雷数。>=1时，理解为总的雷数；<1时，理解为雷的比例。
+///
+/// # 返回值
+/// 排布总数、与局面位置对应的是雷的排布数，均为Python的整数。
+#[pyfunction]
+#[pyo3(
+    name = "cal_solutions_count_exact",
+    signature = (game_board, mine_num)
+)]
+fn py_cal_solutions_count_exact(
+    game_board: Vec<Vec<i32>>,
+    mine_num: f64,
+) -> PyResult<(BigUint, Vec<Vec<BigUint>>)> {
+    match cal_solutions_count_exact(&game_board, mine_num) {
+        Ok(t) => Ok(t),
+        Err(1) => Err(PyErr::new::<PyRuntimeError, _>("无解的局面")),
+        Err(2) => Err(PyErr::new::<PyRuntimeError, _>("枚举过长")),
+        Err(_) => Err(PyErr::new::<PyRuntimeError, _>("非法的局面")),
+    }
+}
+
+/// 检查局面中是否有无法避免的猜测。
+///
+/// # 参数
+/// - `game_board`: 游戏局面。自动纠正错误的标雷。
+/// - `mine_num`：雷数。>=1时，理解为总的雷数；<1时，理解为雷的比例。
+///
+/// # 返回值
+/// 涉及的格子的位置列表，没有则返回None。
+#[pyfunction]
+#[pyo3(
+    name = "check_for_unavoidable_guess",
+    signature = (game_board, mine_num)
+)]
+fn py_check_for_unavoidable_guess(
+    game_board: Vec<Vec<i32>>,
+    mine_num: f64,
+) -> PyResult<Option<Vec<(usize, usize)>>> {
+    match check_for_unavoidable_guess(&game_board, mine_num) {
+        Ok(t) => Ok(t),
+        Err(1) => Err(PyErr::new::<PyRuntimeError, _>("无解的局面")),
+        Err(2) => Err(PyErr::new::<PyRuntimeError, _>("枚举过长")),
+        Err(_) => Err(PyErr::new::<PyRuntimeError, _>("非法的局面")),
+    }
+}
+
+/// 检查局面中是否有无法避免的50/50，包括由若干个二选一首尾相连组成的链。
+///
+/// # 参数
+/// - `game_board`: 游戏局面。自动纠正错误的标雷。
+/// - `mine_num`：雷数。>=1时，理解为总的雷数；<1时，理解为雷的比例。
+///
+/// # 返回值
+/// 涉及的格子的位置列表，没有则返回None。
+#[pyfunction]
+#[pyo3(
+    name = "check_for_unavoidable_5050",
+    signature = (game_board, mine_num)
+)]
+fn py_check_for_unavoidable_5050(
+    game_board: Vec<Vec<i32>>,
+    mine_num: f64,
+) -> PyResult<Option<Vec<(usize, usize)>>> {
+    match check_for_unavoidable_5050(&game_board, mine_num) {
+        Ok(t) => Ok(t),
+        Err(1) => Err(PyErr::new::<PyRuntimeError, _>("无解的局面")),
+        Err(2) => Err(PyErr::new::<PyRuntimeError, _>("枚举过长")),
+        Err(_) => Err(PyErr::new::<PyRuntimeError, _>("非法的局面")),
+    }
+}
+
+/// 检查局面中是否有无法避免的50/50或伪50/50。
+///
+/// # 参数
+/// - `game_board`: 游戏局面。自动纠正错误的标雷。
+/// - `mine_num`：雷数。>=1时，理解为总的雷数；<1时，理解为雷的比例。
+///
+/// # 返回值
+/// 涉及的格子的位置列表，没有则返回None。
+#[pyfunction]
+#[pyo3(
+    name = "check_for_unavoidable_5050_or_pseudo",
+    signature = (game_board, mine_num)
+)]
+fn py_check_for_unavoidable_5050_or_pseudo(
+    game_board: Vec<Vec<i32>>,
+    mine_num: f64,
+) -> PyResult<Option<Vec<(usize, usize)>>> {
+    match check_for_unavoidable_5050_or_pseudo(&game_board, mine_num) {
+        Ok(t) => Ok(t),
+        Err(1) => Err(PyErr::new::<PyRuntimeError, _>("无解的局面")),
+        Err(2) => Err(PyErr::new::<PyRuntimeError, _>("枚举过长")),
+        Err(_) => Err(PyErr::new::<PyRuntimeError, _>("非法的局面")),
+    }
+}
+
 #[pyfunction]
 #[pyo3(
     name = "sample_bbbvs_exp",
-    signature = (x0, y0, n)
+    signature = (x0, y0, n, seed = None, thread_num = 16, control = None)
 )]
-fn py_sample_bbbvs_exp(x0: usize, y0: usize, n: usize) -> PyResult<[usize; 382]> {
-    Ok(sample_bbbvs_exp(x0, y0, n))
+fn py_sample_bbbvs_exp(
+    py: Python<'_>,
+    x0: usize,
+    y0: usize,
+    n: usize,
+    seed: Option<u64>,
+    thread_num: usize,
+    control: Option<PyRef<PyThreadControl>>,
+) -> PyResult<[usize; 382]> {
+    let control = get_thread_control(thread_num, control);
+    let (record, _) = py.detach(|| {
+        sample_bbbvs_exp_with_control(
+            x0,
+            y0,
+            n,
+            seed.unwrap_or_else(SeededRng::random_seed),
+            &control,
+        )
+    });
+    Ok(record)
+}
+
+/// 局面指标的分布。各个直方图的下标为指标的值，元素为该值出现的局数。
+#[pyclass(name = "BoardHistograms")]
+pub struct PyBoardHistograms {
+    pub core: BoardHistograms,
+}
+
+#[pymethods]
+impl PyBoardHistograms {
+    #[new]
+    #[pyo3(signature = (samples, bbbv, op, isl, zini))]
+    pub fn new(
+        samples: usize,
+        bbbv: Vec<usize>,
+        op: Vec<usize>,
+        isl: Vec<usize>,
+        zini: Vec<usize>,
+    ) -> PyBoardHistograms {
+        PyBoardHistograms {
+            core: BoardHistograms {
+                samples,
+                bbbv,
+                op,
+                isl,
+                zini,
+            },
+        }
+    }
+    #[getter]
+    fn get_samples(&self) -> PyResult<usize> {
+        Ok(self.core.samples)
+    }
+    #[getter]
+    fn get_bbbv(&self) -> PyResult<Vec<usize>> {
+        Ok(self.core.bbbv.clone())
+    }
+    #[getter]
+    fn get_op(&self) -> PyResult<Vec<usize>> {
+        Ok(self.core.op.clone())
+    }
+    #[getter]
+    fn get_isl(&self) -> PyResult<Vec<usize>> {
+        Ok(self.core.isl.clone())
+    }
+    #[getter]
+    fn get_zini(&self) -> PyResult<Vec<usize>> {
+        Ok(self.core.zini.clone())
+    }
+}
+
+/// 任意尺寸的局面指标采样引擎，用于研究3BV、op、isl、ZiNi的分布。
+///
+/// # 参数
+/// - `row`: 局面行数。
+/// - `column`：局面列数。
+/// - `mine_num`：雷数。
+/// - `x0`：起手位置在第几行。
+/// - `y0`：起手位置在第几列。
+/// - `op_start`：是否起手开空。True时按laymine_op的规则埋雷，否则按laymine的规则埋雷。
+/// - `n`：局数。
+/// - `seed`：可选的种子。
+/// - `thread_num`：线程数。传入`control`时以`control`的线程数为准。
+/// - `control`：可选的控制器。
+///
+/// # 返回值
+/// 各指标的分布，以及实际采样的局数（被取消时少于n）。
+#[pyfunction]
+#[pyo3(
+    name = "sample_board_histograms",
+    signature = (row, column, mine_num, x0, y0, op_start, n, seed = None,
+        thread_num = 8, control = None)
+)]
+fn py_sample_board_histograms(
+    py: Python<'_>,
+    row: usize,
+    column: usize,
+    mine_num: usize,
+    x0: usize,
+    y0: usize,
+    op_start: bool,
+    n: usize,
+    seed: Option<u64>,
+    thread_num: usize,
+    control: Option<PyRef<PyThreadControl>>,
+) -> PyResult<PyBoardHistograms> {
+    let control = get_thread_control(thread_num, control);
+    let core = py.detach(|| {
+        sample_board_histograms(
+            row,
+            column,
+            mine_num,
+            x0,
+            y0,
+            op_start,
+            n,
+            seed.unwrap_or_else(SeededRng::random_seed),
+            &control,
+        )
+    });
+    Ok(PyBoardHistograms { core })
+}
+
+/// 设置某种局面的指标分布表，例如sample_board_histograms的结果，或者从文件中读取的预先计算好的表。
+/// 计算这种局面的百分位之前，必须先设置分布表。
+///
+/// # 参数
+/// - `row`: 局面行数。
+/// - `column`：局面列数。
+/// - `mine_num`：雷数。
+/// - `x0`：起手位置在第几行。
+/// - `y0`：起手位置在第几列。
+/// - `op_start`：是否起手开空。
+/// - `histograms`：分布表，例如sample_board_histograms的结果。
+#[pyfunction]
+#[pyo3(
+    name = "set_board_histograms",
+    signature = (row, column, mine_num, x0, y0, op_start, histograms)
+)]
+fn py_set_board_histograms(
+    row: usize,
+    column: usize,
+    mine_num: usize,
+    x0: usize,
+    y0: usize,
+    op_start: bool,
+    histograms: PyRef<PyBoardHistograms>,
+) -> PyResult<()> {
+    set_board_histograms(row, column, mine_num, x0, y0, op_start, histograms.core.clone());
+    Ok(())
+}
+
+/// 计算局面的3BV与ZiNi在同尺寸、同雷数、同起手位置、同起手规则的局面中的百分位（0~100），越大越难。
+///
+/// # 参数
+/// - `board`: 局面。
+/// - `x0`：起手位置在第几行。
+/// - `y0`：起手位置在第几列。
+/// - `op_start`：是否起手开空。True时与laymine_op的局面比较，否则与laymine的局面比较。
+///
+/// # 返回值
+/// (3BV的百分位, ZiNi的百分位)。
+///
+/// # 异常
+/// - `PyRuntimeError`: `没有设置这种局面的分布表`。须先用set_board_histograms设置，不会自动采样。
+#[pyfunction]
+#[pyo3(name = "cal_board_percentile", signature = (board, x0, y0, op_start = false))]
+fn py_cal_board_percentile(
+    py: Python<'_>,
+    board: Vec<Vec<i32>>,
+    x0: usize,
+    y0: usize,
+    op_start: bool,
+) -> PyResult<(f64, f64)> {
+    py.detach(|| cal_board_percentile(&board, x0, y0, op_start))
+        .map_err(|_| PyErr::new::<PyRuntimeError, _>("没有设置这种局面的分布表"))
 }
 
 // #[pyfunction]
@@ -479,10 +1319,140 @@ fn py_cal_all_solution(a: Vec<Vec<i32>>, b: Vec<i32>) -> PyResult<Vec<Vec<u8>>>
     Ok(cal_all_solution(&a, &b))
 }
 
+/// 把真实局面编码成短字符串，便于分享。
+///
+/// # 参数
+/// - `board`：真实局面。-1代表雷，-2代表被遮挡的位置，其余的数字被忽略。
+/// - `first_click`：可选的起手位置（行，列）。
+/// - `topology`：局面的拓扑结构，0-平面，1-环面。
+///
+/// # 返回值
+/// base64url格式的字符串，包含版本号、尺寸、拓扑结构、雷的位置、起手位置和校验。
+///
+/// # 异常
+/// - `PyValueError`：局面为空、过大，起手位置超出局面，或拓扑结构不合法。
+#[pyfunction]
+#[pyo3(name = "encode_board", signature = (board, first_click = None, topology = 0))]
+fn py_encode_board(
+    board: Vec<Vec<i32>>,
+    first_click: Option<(usize, usize)>,
+    topology: usize,
+) -> PyResult<String> {
+    match encode_board_with_topology(&board, first_click, get_topology(topology)?) {
+        Ok(code) => Ok(code),
+        Err(1) => Err(pyo3::exceptions::PyValueError::new_err(
+            "board must have 1 to 65535 rows and columns",
+        )),
+        Err(_) => Err(pyo3::exceptions::PyValueError::new_err(
+            "first click is out of the board",
+        )),
+    }
+}
+
+/// 游戏局面上识别出的一个定式。
+#[pyclass(name = "PatternMatch")]
+pub struct PyPatternMatch {
+    pub core: PatternMatch,
+}
+
+#[pymethods]
+impl PyPatternMatch {
+    #[getter]
+    fn get_name(&self) -> PyResult<&'static str> {
+        Ok(self.core.name)
+    }
+    #[getter]
+    fn get_numbers(&self) -> PyResult<Vec<(usize, usize)>> {
+        Ok(self.core.numbers.clone())
+    }
+    #[getter]
+    fn get_mines(&self) -> PyResult<Vec<(usize, usize)>> {
+        Ok(self.core.mines.clone())
+    }
+    #[getter]
+    fn get_not_mines(&self) -> PyResult<Vec<(usize, usize)>> {
+        Ok(self.core.not_mines.clone())
+    }
+}
+
+/// 识别游戏局面上的定式（1-1、1-2、1-2-1、1-2-2-1、hole、triangle），会尝试所有的旋转和翻转。
+///
+/// # 参数
+/// - `game_board`：游戏局面。标的雷当作雷。
+///
+/// # 返回值
+/// 识别出的定式，按第一个数字的位置排列。
+#[pyfunction]
+#[pyo3(name = "find_patterns", signature = (game_board))]
+fn py_find_patterns(game_board: Vec<Vec<i32>>) -> PyResult<Vec<PyPatternMatch>> {
+    Ok(find_patterns(&game_board)
+        .into_iter()
+        .map(|core| PyPatternMatch { core })
+        .collect())
+}
+
+/// 把encode_board生成的字符串解码成真实局面。
+///
+/// # 参数
+/// - `code`：局面的编码。
+///
+/// # 返回值
+/// 真实局面（数字按编码中的拓扑结构重新计算）、起手位置（行，列），编码时没有给出起手位置时为None，
+/// 以及拓扑结构，0-平面，1-环面。
+///
+/// # 异常
+/// - `PyValueError`：编码含有非法字符、校验不通过、版本号不支持或内容不合法。
+#[pyfunction]
+#[pyo3(name = "decode_board", signature = (code))]
+fn py_decode_board(code: &str) -> PyResult<(Vec<Vec<i32>>, Option<(usize, usize)>, usize)> {
+    match decode_board_with_topology(code) {
+        Ok((board, first_click, topology)) => Ok((
+            board,
+            first_click,
+            match topology {
+                Topology::Plane => 0,
+                Topology::Torus => 1,
+            },
+        )),
+        Err(e) => Err(pyo3::exceptions::PyValueError::new_err(match e {
+            1 => "illegal board code",
+            2 => "board code checksum mismatch",
+            3 => "unsupported board code version",
+            _ => "board code content is invalid",
+        })),
+    }
+}
+
+/// 局面在旋转、翻转下的标准形式。经过旋转、翻转能互相得到的局面，标准形式相同。
+///
+/// # 参数
+/// - `board`：真实局面或游戏局面。
+///
+/// # 返回值
+/// 所有像中按行优先排列时字典序最小的一个。方形局面考虑8种变换，其他局面考虑4种。
+#[pyfunction]
+#[pyo3(name = "canonical_board", signature = (board))]
+fn py_canonical_board(board: Vec<Vec<i32>>) -> PyResult<Vec<Vec<i32>>> {
+    Ok(canonical_board(&board))
+}
+
+/// 局面的标准形式的64位哈希值，用于大量局面的去重。在各个平台上都相同。
+///
+/// # 参数
+/// - `board`：真实局面或游戏局面。
+///
+/// # 返回值
+/// 哈希值。经过旋转、翻转能互相得到的局面，哈希值相同。
+#[pyfunction]
+#[pyo3(name = "canonical_board_hash", signature = (board))]
+fn py_canonical_board_hash(board: Vec<Vec<i32>>) -> PyResult<u64> {
+    Ok(canonical_board_hash(&board))
+}
+
 #[pyfunction]
-#[pyo3(name = "cal_board_numbers")]
-fn py_cal_board_numbers(mut board: Vec<Vec<i32>>) -> PyResult<Vec<Vec<i32>>> {
-    cal_board_numbers(&mut board);
+#[pyo3(name = "cal_board_numbers", signature = (board, topology = 0))]
+fn py_cal_board_numbers(mut board: Vec<Vec<i32>>, topology: usize) -> PyResult<Vec<Vec<i32>>> {
+    cal_board_numbers_with_topology(&mut board, get_topology(topology)?);
     Ok(board)
 }
 
@@ -541,12 +1511,17 @@ fn ms_toollib(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(py_refresh_matrixs, m)?)?;
     m.add_function(wrap_pyfunction!(py_refresh_matrixses, m)?)?;
     m.add_function(wrap_pyfunction!(py_cal_op, m)?)?;
+    m.add_function(wrap_pyfunction!(py_cal_isl, m)?)?;
     m.add_function(wrap_pyfunction!(py_cal_bbbv, m)?)?;
     m.add_function(wrap_pyfunction!(py_cal_zini, m)?)?;
     m.add_function(wrap_pyfunction!(py_cal_hzini, m)?)?;
     m.add_function(wrap_pyfunction!(py_cal_rzini, m)?)?;
+    m.add_function(wrap_pyfunction!(py_cal_zini_ops, m)?)?;
+    m.add_function(wrap_pyfunction!(py_zini_ops_to_step_flow, m)?)?;
+    m.add_function(wrap_pyfunction!(py_cal_optimal_clicks, m)?)?;
     m.add_function(wrap_pyfunction!(py_refresh_board, m)?)?;
     m.add_function(wrap_pyfunction!(py_laymine, m)?)?;
+    m.add_function(wrap_pyfunction!(py_laymine_masked, m)?)?;
     m.add_function(wrap_pyfunction!(py_get_all_not_and_is_mine_on_board, m)?)?;
     m.add_function(wrap_pyfunction!(py_laymine_op, m)?)?;
     m.add_function(wrap_pyfunction!(py_solve_direct, m)?)?;
@@ -554,20 +1529,38 @@ fn ms_toollib(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(py_solve_enumerate, m)?)?;
     m.add_function(wrap_pyfunction!(py_unsolvable_structure, m)?)?;
     m.add_function(wrap_pyfunction!(py_is_solvable, m)?)?;
+    m.add_function(wrap_pyfunction!(py_try_solve_trace, m)?)?;
     m.add_function(wrap_pyfunction!(py_laymine_solvable, m)?)?;
     m.add_function(wrap_pyfunction!(py_laymine_solvable_thread, m)?)?;
     m.add_function(wrap_pyfunction!(py_laymine_solvable_adjust, m)?)?;
+    m.add_function(wrap_pyfunction!(py_laymine_filtered, m)?)?;
     m.add_function(wrap_pyfunction!(py_cal_probability_enum, m)?)?;
     m.add_function(wrap_pyfunction!(py_cal_probability_csp, m)?)?;
     m.add_function(wrap_pyfunction!(py_sample_bbbvs_exp, m)?)?;
+    m.add_function(wrap_pyfunction!(py_sample_board_histograms, m)?)?;
+    m.add_function(wrap_pyfunction!(py_set_board_histograms, m)?)?;
+    m.add_function(wrap_pyfunction!(py_cal_board_percentile, m)?)?;
     m.add_function(wrap_pyfunction!(py_obr_board, m)?)?;
     // m.add_function(wrap_pyfunction!(py_obr_board_old, m)?)?;
     m.add_function(wrap_pyfunction!(py_cal_probability_onboard, m)?)?;
+    m.add_function(wrap_pyfunction!(py_suggest_moves, m)?)?;
+    m.add_function(wrap_pyfunction!(py_hint, m)?)?;
+    m.add_function(wrap_pyfunction!(py_cal_guess_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(py_cal_solutions_count_exact, m)?)?;
+    m.add_function(wrap_pyfunction!(py_cal_mine_count_distribution, m)?)?;
+    m.add_function(wrap_pyfunction!(py_check_for_unavoidable_guess, m)?)?;
+    m.add_function(wrap_pyfunction!(py_check_for_unavoidable_5050, m)?)?;
+    m.add_function(wrap_pyfunction!(py_check_for_unavoidable_5050_or_pseudo, m)?)?;
     m.add_function(wrap_pyfunction!(py_mark_board, m)?)?;
     m.add_function(wrap_pyfunction!(py_is_guess_while_needless, m)?)?;
     m.add_function(wrap_pyfunction!(py_is_able_to_solve, m)?)?;
     m.add_function(wrap_pyfunction!(py_cal_all_solution, m)?)?;
     m.add_function(wrap_pyfunction!(py_cal_board_numbers, m)?)?;
+    m.add_function(wrap_pyfunction!(py_encode_board, m)?)?;
+    m.add_function(wrap_pyfunction!(py_find_patterns, m)?)?;
+    m.add_function(wrap_pyfunction!(py_canonical_board, m)?)?;
+    m.add_function(wrap_pyfunction!(py_canonical_board_hash, m)?)?;
+    m.add_function(wrap_pyfunction!(py_decode_board, m)?)?;
     m.add_function(wrap_pyfunction!(py_valid_time_period, m)?)?;
     m.add_class::<PyMinesweeperBoard>()?;
     m.add_class::<PySafeMinesweeperBoard>()?;
@@ -578,6 +1571,12 @@ fn ms_toollib(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<PyBaseVideo>()?;
     m.add_class::<PyGameBoard>()?;
     m.add_class::<PyBoard>()?;
+    m.add_class::<PyThreadControl>()?;
+    m.add_class::<PyBoardHistograms>()?;
+    m.add_class::<PySolveStep>()?;
+    m.add_class::<PyPatternMatch>()?;
+    m.add_class::<PyHint>()?;
+    m.add_class::<PyGuessStats>()?;
     m.add_class::<PySafeBoard>()?;
     m.add_class::<PySafeBoardRow>()?;
     m.add_class::<PyVideoActionStateRecorder>()?;
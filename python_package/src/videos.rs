@@ -151,6 +151,26 @@ macro_rules! generate_video {
                 Ok(self.core.data.static_params.hzini)
             }
             #[getter]
+            fn get_nf_zini(&self) -> PyResult<usize> {
+                Ok(self.core.data.static_params.nf_zini)
+            }
+            #[getter]
+            fn get_direct_cells(&mut self) -> PyResult<usize> {
+                Ok(self.core.data.get_solution_complexity().unwrap_or_default().direct)
+            }
+            #[getter]
+            fn get_minus_cells(&mut self) -> PyResult<usize> {
+                Ok(self.core.data.get_solution_complexity().unwrap_or_default().minus)
+            }
+            #[getter]
+            fn get_enum_cells(&mut self) -> PyResult<usize> {
+                Ok(self.core.data.get_solution_complexity().unwrap_or_default().enumerate)
+            }
+            #[getter]
+            fn get_guesses(&mut self) -> PyResult<usize> {
+                Ok(self.core.data.get_solution_complexity().unwrap_or_default().guess)
+            }
+            #[getter]
             fn get_cell0(&self) -> PyResult<usize> {
                 Ok(self.core.data.static_params.cell0)
             }
@@ -299,6 +319,14 @@ macro_rules! generate_video {
                 Ok(self.core.data.get_thrp().unwrap())
             }
             #[getter]
+            fn get_zini_solved(&self) -> PyResult<usize> {
+                Ok(self.core.data.get_zini_solved().unwrap())
+            }
+            #[getter]
+            fn get_zioe(&self) -> PyResult<f64> {
+                Ok(self.core.data.get_zioe().unwrap())
+            }
+            #[getter]
             fn get_corr(&self) -> PyResult<f64> {
                 Ok(self.core.data.get_corr().unwrap())
             }
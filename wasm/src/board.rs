@@ -67,6 +67,37 @@ impl Board {
     pub fn get_cell8(&mut self) -> usize {
         self.core.get_cell8()
     }
+    // 从(x0, y0)起手的解题复杂度：[单集合判出的格子数, 双集合判出的格子数, 枚举判出的格子数, 必须猜的次数]
+    pub fn solution_complexity(&mut self, x0: usize, y0: usize) -> Vec<usize> {
+        let c = self.core.get_solution_complexity(x0, y0);
+        vec![c.direct, c.minus, c.enumerate, c.guess]
+    }
+    // 每一片空的格子：[[[行, 列], ...], ...]
+    pub fn openings(&mut self) -> JsValue {
+        cells_list_to_js_value(self.core.openings())
+    }
+    // 每一个岛的格子，格式同openings
+    pub fn islands(&mut self) -> JsValue {
+        cells_list_to_js_value(self.core.islands())
+    }
+    pub fn bbbv_labels(&self) -> JsValue {
+        vec_vec_to_js_value(self.core.bbbv_labels())
+    }
+}
+
+fn cells_list_to_js_value(cells_list: Vec<Vec<(usize, usize)>>) -> JsValue {
+    let array = Array::new();
+    for cells in cells_list {
+        let cells_array = Array::new();
+        for (x, y) in cells {
+            let pos = Array::new();
+            pos.push(&JsValue::from(x));
+            pos.push(&JsValue::from(y));
+            cells_array.push(&pos);
+        }
+        array.push(&cells_array);
+    }
+    array.into()
 }
 
 #[wasm_bindgen]
@@ -144,6 +175,17 @@ impl GameBoard {
     }
 }
 
+// 第一下点击的规则：0-不移动雷，1-移到左上角，2-随机移到别处，3-保证开空，其他值返回None
+fn first_click_policy(policy: usize) -> Option<ms::FirstClickPolicy> {
+    match policy {
+        0 => Some(ms::FirstClickPolicy::None),
+        1 => Some(ms::FirstClickPolicy::SafeCellCorner),
+        2 => Some(ms::FirstClickPolicy::SafeCell),
+        3 => Some(ms::FirstClickPolicy::SafeOpening),
+        _ => None,
+    }
+}
+
 // 局面自动机
 #[wasm_bindgen]
 pub struct MinesweeperBoard {
@@ -185,6 +227,20 @@ impl MinesweeperBoard {
             .collect();
         self.core.step_flow(&operation).unwrap();
     }
+    // 第一下点击的规则：0-不移动雷，1-移到左上角，2-随机移到别处，3-保证开空。未知的规则返回false
+    pub fn set_first_click_policy(&mut self, policy: usize, seed: u64) -> bool {
+        match first_click_policy(policy) {
+            Some(policy) => {
+                self.core.set_first_click_policy(policy, seed);
+                true
+            }
+            None => false,
+        }
+    }
+    // 局面的拓扑结构：0-平面，1-环面。局面上的数字须已按此拓扑结构计算
+    pub fn set_topology(&mut self, topology_id: usize) {
+        self.core.set_topology(crate::topology(topology_id));
+    }
     // 这个方法与强可猜、弱可猜有关
     #[wasm_bindgen(setter = board)]
     pub fn set_board(&mut self, board: JsValue) {
@@ -320,6 +376,10 @@ impl KeyDynamicParams {
     pub fn get_isl_solved(&self) -> usize {
         self.core.isl_solved
     }
+    #[wasm_bindgen(getter = zini_solved)]
+    pub fn get_zini_solved(&self) -> usize {
+        self.core.zini_solved
+    }
 }
 
 #[wasm_bindgen]
@@ -670,6 +730,26 @@ macro_rules! generate_video {
                 pub fn get_hzini(&self) -> usize {
                     self.core.data.static_params.hzini
                 }
+                #[wasm_bindgen(getter = nf_zini)]
+                pub fn get_nf_zini(&self) -> usize {
+                    self.core.data.static_params.nf_zini
+                }
+                #[wasm_bindgen(getter = direct_cells)]
+                pub fn get_direct_cells(&mut self) -> usize {
+                    self.core.data.get_solution_complexity().unwrap_or_default().direct
+                }
+                #[wasm_bindgen(getter = minus_cells)]
+                pub fn get_minus_cells(&mut self) -> usize {
+                    self.core.data.get_solution_complexity().unwrap_or_default().minus
+                }
+                #[wasm_bindgen(getter = enum_cells)]
+                pub fn get_enum_cells(&mut self) -> usize {
+                    self.core.data.get_solution_complexity().unwrap_or_default().enumerate
+                }
+                #[wasm_bindgen(getter = guesses)]
+                pub fn get_guesses(&mut self) -> usize {
+                    self.core.data.get_solution_complexity().unwrap_or_default().guess
+                }
                 #[wasm_bindgen(getter = cell0)]
                 pub fn get_cell0(&self) -> usize {
                     self.core.data.static_params.cell0
@@ -814,6 +894,14 @@ macro_rules! generate_video {
                 pub fn get_thrp(&self) -> f64 {
                     self.core.data.get_thrp().unwrap()
                 }
+                #[wasm_bindgen(getter = zini_solved)]
+                pub fn get_zini_solved(&self) -> usize {
+                    self.core.data.get_zini_solved().unwrap()
+                }
+                #[wasm_bindgen(getter = zioe)]
+                pub fn get_zioe(&self) -> f64 {
+                    self.core.data.get_zioe().unwrap()
+                }
                 #[wasm_bindgen(getter = corr)]
                 pub fn get_corr(&self) -> f64 {
                     self.core.data.get_corr().unwrap()
@@ -929,6 +1017,17 @@ impl BaseVideo {
         let board_vec = js_value_to_vec_vec(board);
         self.core.set_board(board_vec).unwrap();
     }
+    // 第一下点击的规则，同MinesweeperBoard。只能在游戏开始前设置；未知的规则或游戏已经开始时返回false
+    pub fn set_first_click_policy(&mut self, policy: usize, seed: u64) -> bool {
+        match first_click_policy(policy) {
+            Some(policy) => self.core.set_first_click_policy(policy, seed).is_ok(),
+            None => false,
+        }
+    }
+    // 局面的拓扑结构：0-平面，1-环面。只能在游戏开始前设置，否则返回false
+    pub fn set_topology(&mut self, topology_id: usize) -> bool {
+        self.core.set_topology(crate::topology(topology_id)).is_ok()
+    }
     pub fn reset(&mut self, row: usize, column: usize, pix_size: u8) {
         self.core.reset(row, column, pix_size);
     }
@@ -1014,6 +1113,26 @@ impl BaseVideo {
     pub fn get_hzini(&self) -> usize {
         self.core.static_params.hzini
     }
+    #[wasm_bindgen(getter = nf_zini)]
+    pub fn get_nf_zini(&self) -> usize {
+        self.core.static_params.nf_zini
+    }
+    #[wasm_bindgen(getter = direct_cells)]
+    pub fn get_direct_cells(&mut self) -> usize {
+        self.core.get_solution_complexity().unwrap_or_default().direct
+    }
+    #[wasm_bindgen(getter = minus_cells)]
+    pub fn get_minus_cells(&mut self) -> usize {
+        self.core.get_solution_complexity().unwrap_or_default().minus
+    }
+    #[wasm_bindgen(getter = enum_cells)]
+    pub fn get_enum_cells(&mut self) -> usize {
+        self.core.get_solution_complexity().unwrap_or_default().enumerate
+    }
+    #[wasm_bindgen(getter = guesses)]
+    pub fn get_guesses(&mut self) -> usize {
+        self.core.get_solution_complexity().unwrap_or_default().guess
+    }
     #[wasm_bindgen(getter = start_time)]
     pub fn get_start_time(&self) -> u64 {
         self.core.start_time
@@ -1178,6 +1297,14 @@ impl BaseVideo {
     pub fn get_thrp(&self) -> f64 {
         self.core.get_thrp().unwrap()
     }
+    #[wasm_bindgen(getter = zini_solved)]
+    pub fn get_zini_solved(&self) -> usize {
+        self.core.get_zini_solved().unwrap()
+    }
+    #[wasm_bindgen(getter = zioe)]
+    pub fn get_zioe(&self) -> f64 {
+        self.core.get_zioe().unwrap()
+    }
     #[wasm_bindgen(getter = corr)]
     pub fn get_corr(&self) -> f64 {
         self.core.get_corr().unwrap()
@@ -44,7 +44,7 @@ use wasm_bindgen::prelude::*;
 mod board;
 // use board::{MinesweeperBoard,AvfVideo};
 mod transfor;
-use transfor::{js_value_to_vec_vec, vec_vec_to_js_value};
+use transfor::{js_value_to_vec_vec, pos_list_to_js_value, vec_vec_to_js_value};
 
 #[wasm_bindgen(start)]
 pub fn init() {
@@ -82,6 +82,152 @@ pub fn cal_probability_onboard(js_board: JsValue, mine_num: f64) -> JsValue {
     }
 }
 
+/// 走法建议。返回按推荐程度排序的数组，每个元素为[行, 列, 安全率, 进展概率, 是否是死格]
+#[wasm_bindgen]
+pub fn suggest_moves(js_board: JsValue, mine_num: f64) -> JsValue {
+    let game_board = js_value_to_vec_vec(js_board);
+    match ms::suggest_moves(&game_board, mine_num) {
+        Ok(moves) => {
+            let array = js_sys::Array::new();
+            for m in moves {
+                let item = Array::new_with_length(5);
+                item.set(0, JsValue::from(m.pos.0));
+                item.set(1, JsValue::from(m.pos.1));
+                item.set(2, JsValue::from(m.safety));
+                item.set(3, JsValue::from(m.progress));
+                item.set(4, JsValue::from_bool(m.dead));
+                array.push(&item);
+            }
+            array.into()
+        }
+        Err(t) => JsValue::from(t),
+    }
+}
+
+// 提示：推荐的下一步操作，以及理由。action为"left"或"right"，rule为"direct"、"minus"、"enumerate"，按概率选择时为undefined
+#[wasm_bindgen]
+pub struct Hint {
+    core: ms::Hint,
+}
+
+#[wasm_bindgen]
+impl Hint {
+    // [行, 列]
+    #[wasm_bindgen(getter = cell)]
+    pub fn get_cell(&self) -> Vec<usize> {
+        vec![self.core.cell.0, self.core.cell.1]
+    }
+    #[wasm_bindgen(getter = action)]
+    pub fn get_action(&self) -> String {
+        self.core.action.as_str().to_string()
+    }
+    #[wasm_bindgen(getter = rule)]
+    pub fn get_rule(&self) -> Option<String> {
+        self.core.rule.map(|r| r.as_str().to_string())
+    }
+    #[wasm_bindgen(getter = witnesses)]
+    pub fn get_witnesses(&self) -> JsValue {
+        pos_list_to_js_value(self.core.witnesses.clone())
+    }
+    #[wasm_bindgen(getter = safety)]
+    pub fn get_safety(&self) -> f64 {
+        self.core.safety
+    }
+}
+
+/// 提示下一步，光标位置为(x, y)（行，列）。没有未打开的格子时返回null，出错时返回错误码
+#[wasm_bindgen]
+pub fn hint(js_board: JsValue, mine_num: f64, x: usize, y: usize) -> JsValue {
+    let game_board = js_value_to_vec_vec(js_board);
+    match ms::hint(&game_board, mine_num, (x, y)) {
+        Ok(Some(core)) => JsValue::from(Hint { core }),
+        Ok(None) => JsValue::NULL,
+        Err(t) => JsValue::from(t),
+    }
+}
+
+/// 计算边缘与内部未打开的格子中雷数的概率分布。返回[边缘雷数的分布, 内部雷数的分布]，每一项为[雷数, 概率]
+#[wasm_bindgen]
+pub fn cal_mine_count_distribution(js_board: JsValue, mine_num: f64) -> JsValue {
+    let game_board = js_value_to_vec_vec(js_board);
+    let dist_to_js_value = |dist: Vec<(usize, f64)>| {
+        let js_dist = Array::new();
+        for (mines, p) in dist {
+            let item = Array::new();
+            item.push(&JsValue::from(mines));
+            item.push(&JsValue::from(p));
+            js_dist.push(&item);
+        }
+        js_dist
+    };
+    match ms::cal_mine_count_distribution(&game_board, mine_num) {
+        Ok((edge, off_edge)) => {
+            let result = Array::new();
+            result.push(&dist_to_js_value(edge));
+            result.push(&dist_to_js_value(off_edge));
+            result.into()
+        }
+        Err(t) => JsValue::from(t),
+    }
+}
+
+/// 精确计算雷的排布总数，以及每个格子是雷的排布数。返回[排布总数, 排布数矩阵]，均为BigInt
+#[wasm_bindgen]
+pub fn cal_solutions_count_exact(js_board: JsValue, mine_num: f64) -> JsValue {
+    let game_board = js_value_to_vec_vec(js_board);
+    let to_big_int = |n: String| JsValue::from(js_sys::BigInt::new(&JsValue::from(n)).unwrap());
+    match ms::cal_solutions_count_exact(&game_board, mine_num) {
+        Ok((solutions, mine_counts)) => {
+            let js_counts = Array::new();
+            for row in mine_counts {
+                let js_row = Array::new();
+                for n in row {
+                    js_row.push(&to_big_int(n.to_string()));
+                }
+                js_counts.push(&js_row);
+            }
+            let result = Array::new();
+            result.push(&to_big_int(solutions.to_string()));
+            result.push(&js_counts);
+            result.into()
+        }
+        Err(t) => JsValue::from(t),
+    }
+}
+
+/// 检查局面中是否有无法避免的猜测。返回涉及的格子的[行, 列]数组，没有则返回null
+#[wasm_bindgen]
+pub fn check_for_unavoidable_guess(js_board: JsValue, mine_num: f64) -> JsValue {
+    let game_board = js_value_to_vec_vec(js_board);
+    match ms::check_for_unavoidable_guess(&game_board, mine_num) {
+        Ok(Some(tiles)) => pos_list_to_js_value(tiles),
+        Ok(None) => JsValue::NULL,
+        Err(t) => JsValue::from(t),
+    }
+}
+
+/// 检查局面中是否有无法避免的50/50。返回涉及的格子的[行, 列]数组，没有则返回null
+#[wasm_bindgen]
+pub fn check_for_unavoidable_5050(js_board: JsValue, mine_num: f64) -> JsValue {
+    let game_board = js_value_to_vec_vec(js_board);
+    match ms::check_for_unavoidable_5050(&game_board, mine_num) {
+        Ok(Some(tiles)) => pos_list_to_js_value(tiles),
+        Ok(None) => JsValue::NULL,
+        Err(t) => JsValue::from(t),
+    }
+}
+
+/// 检查局面中是否有无法避免的50/50或伪50/50。返回涉及的格子的[行, 列]数组，没有则返回null
+#[wasm_bindgen]
+pub fn check_for_unavoidable_5050_or_pseudo(js_board: JsValue, mine_num: f64) -> JsValue {
+    let game_board = js_value_to_vec_vec(js_board);
+    match ms::check_for_unavoidable_5050_or_pseudo(&game_board, mine_num) {
+        Ok(Some(tiles)) => pos_list_to_js_value(tiles),
+        Ok(None) => JsValue::NULL,
+        Err(t) => JsValue::from(t),
+    }
+}
+
 #[wasm_bindgen]
 pub fn laymine(row: usize, column: usize, mine_num: usize, x0: usize, y0: usize) -> JsValue {
     let board = ms::laymine(row, column, mine_num, x0, y0);
@@ -110,12 +256,392 @@ pub fn laymine_solvable(
     array.into()
 }
 
+// 带种子的埋雷，相同的种子与Rust、Python版本生成相同的局面。种子在js中为BigInt
+#[wasm_bindgen]
+pub fn laymine_seeded(
+    row: usize,
+    column: usize,
+    mine_num: usize,
+    x0: usize,
+    y0: usize,
+    seed: u64,
+) -> JsValue {
+    let board = ms::laymine_seeded(row, column, mine_num, x0, y0, seed);
+    vec_vec_to_js_value(board)
+}
+
+#[wasm_bindgen]
+pub fn laymine_op_seeded(
+    row: usize,
+    column: usize,
+    mine_num: usize,
+    x0: usize,
+    y0: usize,
+    seed: u64,
+) -> JsValue {
+    let board = ms::laymine_op_seeded(row, column, mine_num, x0, y0, seed);
+    vec_vec_to_js_value(board)
+}
+
+#[wasm_bindgen]
+pub fn laymine_solvable_seeded(
+    row: usize,
+    column: usize,
+    mine_num: usize,
+    x0: usize,
+    y0: usize,
+    max_times: usize,
+    seed: u64,
+) -> JsValue {
+    let board_flag = ms::laymine_solvable_seeded(row, column, mine_num, x0, y0, max_times, seed);
+    let array = js_sys::Array::new();
+    array.push(&vec_vec_to_js_value(board_flag.0));
+    array.push(&JsValue::from_bool(board_flag.1));
+    array.into()
+}
+
+#[wasm_bindgen]
+pub fn laymine_solvable_adjust_seeded(
+    row: usize,
+    column: usize,
+    mine_num: usize,
+    x0: usize,
+    y0: usize,
+    seed: u64,
+) -> JsValue {
+    let board_flag = ms::laymine_solvable_adjust_seeded(row, column, mine_num, x0, y0, seed);
+    let array = js_sys::Array::new();
+    array.push(&vec_vec_to_js_value(board_flag.0));
+    array.push(&JsValue::from_bool(board_flag.1));
+    array.into()
+}
+
 #[wasm_bindgen]
 pub fn is_solvable(js_board: JsValue, x0: usize, y0: usize) -> bool {
     let board = js_value_to_vec_vec(js_board);
     ms::is_solvable(&board, x0, y0)
 }
 
+// 按最优策略玩某个局面时，猜的次数与存活率的统计
+#[wasm_bindgen]
+pub struct GuessStats {
+    core: ms::GuessStats,
+}
+
+#[wasm_bindgen]
+impl GuessStats {
+    #[wasm_bindgen(getter = is_exact)]
+    pub fn get_is_exact(&self) -> bool {
+        self.core.is_exact
+    }
+    #[wasm_bindgen(getter = runs)]
+    pub fn get_runs(&self) -> usize {
+        self.core.runs
+    }
+    #[wasm_bindgen(getter = mean_guesses)]
+    pub fn get_mean_guesses(&self) -> f64 {
+        self.core.mean_guesses
+    }
+    // 下标为猜的次数，元素为概率
+    #[wasm_bindgen(getter = distribution)]
+    pub fn get_distribution(&self) -> Vec<f64> {
+        self.core.distribution.clone()
+    }
+    #[wasm_bindgen(getter = survival)]
+    pub fn get_survival(&self) -> f64 {
+        self.core.survival
+    }
+    // 每一项为[[行, 列], 在此处猜的概率, 在此处猜时的平均安全率]
+    #[wasm_bindgen(getter = guesses)]
+    pub fn get_guesses(&self) -> JsValue {
+        let js_guesses = Array::new();
+        for &((x, y), p, safety) in &self.core.guesses {
+            let pos = Array::new();
+            pos.push(&JsValue::from(x));
+            pos.push(&JsValue::from(y));
+            let item = Array::new();
+            item.push(&pos);
+            item.push(&JsValue::from(p));
+            item.push(&JsValue::from(safety));
+            js_guesses.push(&item);
+        }
+        js_guesses.into()
+    }
+}
+
+/// 用指定种子估计按最优策略玩某个局面时，猜的次数与存活率。不同走法的数量不超过runs时精确计算，否则模拟runs局。出错时返回错误码
+#[wasm_bindgen]
+pub fn cal_guess_stats_seeded(js_board: JsValue, x0: usize, y0: usize, runs: usize, seed: u64) -> JsValue {
+    let board = js_value_to_vec_vec(js_board);
+    match ms::cal_guess_stats_seeded(&board, x0, y0, runs, seed) {
+        Ok(core) => JsValue::from(GuessStats { core }),
+        Err(t) => JsValue::from(t),
+    }
+}
+
+// 判雷过程中推理出的一个格子。rule为"direct"、"minus"或"enumerate"
+#[wasm_bindgen]
+pub struct SolveStep {
+    core: ms::SolveStep,
+}
+
+#[wasm_bindgen]
+impl SolveStep {
+    #[wasm_bindgen(getter = step)]
+    pub fn get_step(&self) -> usize {
+        self.core.step
+    }
+    // [行, 列]
+    #[wasm_bindgen(getter = cell)]
+    pub fn get_cell(&self) -> Vec<usize> {
+        vec![self.core.cell.0, self.core.cell.1]
+    }
+    #[wasm_bindgen(getter = is_mine)]
+    pub fn get_is_mine(&self) -> bool {
+        self.core.is_mine
+    }
+    #[wasm_bindgen(getter = rule)]
+    pub fn get_rule(&self) -> String {
+        self.core.rule.as_str().to_string()
+    }
+    // [[行, 列], ...]
+    #[wasm_bindgen(getter = witnesses)]
+    pub fn get_witnesses(&self) -> JsValue {
+        pos_list_to_js_value(self.core.witnesses.clone())
+    }
+}
+
+// 带推理过程的判雷，返回[SolveStep的数组, 是否无猜扫开]，出错时返回错误码
+#[wasm_bindgen]
+pub fn try_solve_trace(js_board: JsValue, x0: usize, y0: usize) -> JsValue {
+    let board = js_value_to_vec_vec(js_board);
+    let (trace, solved) = match ms::try_solve_trace(&board, x0, y0) {
+        Ok(t) => t,
+        Err(t) => return JsValue::from(t),
+    };
+    let steps = Array::new();
+    for core in trace {
+        steps.push(&JsValue::from(SolveStep { core }));
+    }
+    let array = Array::new();
+    array.push(&steps);
+    array.push(&JsValue::from_bool(solved));
+    array.into()
+}
+
+// 把真实局面编码成短字符串，x0、y0为可选的起手位置，topology为0-平面，1-环面。失败时返回null
+#[wasm_bindgen]
+pub fn encode_board(
+    js_board: JsValue,
+    x0: Option<usize>,
+    y0: Option<usize>,
+    topology: usize,
+) -> Option<String> {
+    let board = js_value_to_vec_vec(js_board);
+    let first_click = x0.zip(y0);
+    ms::encode_board_with_topology(&board, first_click, self::topology(topology)).ok()
+}
+
+// 解码局面，返回[局面, 起手位置, 拓扑结构]，没有起手位置时为null，拓扑结构为0-平面，1-环面；失败时返回null
+#[wasm_bindgen]
+pub fn decode_board(code: &str) -> JsValue {
+    match ms::decode_board_with_topology(code) {
+        Ok((board, first_click, topology)) => {
+            let array = js_sys::Array::new();
+            array.push(&vec_vec_to_js_value(board));
+            match first_click {
+                Some((x0, y0)) => {
+                    let pos = js_sys::Array::new();
+                    pos.push(&JsValue::from(x0 as u32));
+                    pos.push(&JsValue::from(y0 as u32));
+                    array.push(&pos);
+                }
+                None => {
+                    array.push(&JsValue::NULL);
+                }
+            }
+            array.push(&JsValue::from(match topology {
+                ms::Topology::Plane => 0,
+                ms::Topology::Torus => 1,
+            }));
+            array.into()
+        }
+        Err(_) => JsValue::NULL,
+    }
+}
+
+// 游戏局面上识别出的一个定式。name为"1-1"、"1-2"、"1-2-1"、"1-2-2-1"、"hole"或"triangle"
+#[wasm_bindgen]
+pub struct PatternMatch {
+    core: ms::PatternMatch,
+}
+
+#[wasm_bindgen]
+impl PatternMatch {
+    #[wasm_bindgen(getter = name)]
+    pub fn get_name(&self) -> String {
+        self.core.name.to_string()
+    }
+    #[wasm_bindgen(getter = numbers)]
+    pub fn get_numbers(&self) -> JsValue {
+        pos_list_to_js_value(self.core.numbers.clone())
+    }
+    #[wasm_bindgen(getter = mines)]
+    pub fn get_mines(&self) -> JsValue {
+        pos_list_to_js_value(self.core.mines.clone())
+    }
+    #[wasm_bindgen(getter = not_mines)]
+    pub fn get_not_mines(&self) -> JsValue {
+        pos_list_to_js_value(self.core.not_mines.clone())
+    }
+}
+
+// 识别游戏局面上的定式，返回PatternMatch的数组
+#[wasm_bindgen]
+pub fn find_patterns(js_board: JsValue) -> Vec<PatternMatch> {
+    let game_board = js_value_to_vec_vec(js_board);
+    ms::find_patterns(&game_board)
+        .into_iter()
+        .map(|core| PatternMatch { core })
+        .collect()
+}
+
+// 操作序列与step_flow的格式相同：[[操作, [行, 列]], ...]
+fn ops_to_js_value(ops: Vec<(String, (usize, usize))>) -> JsValue {
+    let array = Array::new();
+    for (op, (x, y)) in ops {
+        let pos = Array::new();
+        pos.push(&JsValue::from(x));
+        pos.push(&JsValue::from(y));
+        let item = Array::new();
+        item.push(&JsValue::from(op));
+        item.push(&pos);
+        array.push(&item);
+    }
+    array.into()
+}
+
+// ZiNi的操作类型转换成字符串"left"、"right"、"chord"
+fn zini_ops_to_js_value(ops: Vec<(ms::ZiniOp, (usize, usize))>) -> JsValue {
+    ops_to_js_value(
+        ops.into_iter()
+            .map(|(op, pos)| (op.as_str().to_string(), pos))
+            .collect(),
+    )
+}
+
+// ZiNi的路线。method为"greedy"、"human"、"random"或"nf"，未知的算法按"greedy"处理
+#[wasm_bindgen]
+pub fn cal_zini_ops(js_board: JsValue, method: &str, loop_count: usize) -> JsValue {
+    let board = js_value_to_vec_vec(js_board);
+    let ops = match method {
+        "human" => ms::cal_hzini_ops(&board),
+        "random" => ms::cal_rzini_ops(&board, loop_count),
+        "nf" => ms::cal_nf_zini_ops(&board),
+        _ => ms::cal_zini_ops(&board),
+    };
+    zini_ops_to_js_value(ops)
+}
+
+// 把ZiNi的路线转换成MinesweeperBoard.step_flow可以执行的鼠标事件。有未知的操作类型时返回null
+#[wasm_bindgen]
+pub fn zini_ops_to_step_flow(js_ops: JsValue) -> JsValue {
+    let ops: Option<Vec<_>> = Array::from(&js_ops)
+        .iter()
+        .map(|item| {
+            let item = Array::from(&item);
+            let pos = Array::from(&item.get(1));
+            let op = item.get(0).as_string()?.parse::<ms::ZiniOp>().ok()?;
+            Some((
+                op,
+                (
+                    pos.get(0).as_f64().unwrap() as usize,
+                    pos.get(1).as_f64().unwrap() as usize,
+                ),
+            ))
+        })
+        .collect();
+    match ops {
+        Some(ops) => ops_to_js_value(ms::zini_ops_to_step_flow(&ops)),
+        None => JsValue::NULL,
+    }
+}
+
+// 最少点击数及其路线，返回[点击数, 路线, 是否为最优解]。超出限制时返回目前最好的结果
+#[wasm_bindgen]
+pub fn cal_optimal_clicks(js_board: JsValue, max_nodes: usize, max_millis: u64) -> JsValue {
+    let board = js_value_to_vec_vec(js_board);
+    let (clicks, ops, is_optimal) = ms::cal_optimal_clicks(
+        &board,
+        ms::OptimalClicksLimits {
+            max_nodes,
+            max_millis,
+        },
+    );
+    let array = Array::new();
+    array.push(&JsValue::from(clicks));
+    array.push(&zini_ops_to_js_value(ops));
+    array.push(&JsValue::from(is_optimal));
+    array.into()
+}
+
+#[wasm_bindgen]
+pub fn canonical_board(js_board: JsValue) -> JsValue {
+    let board = js_value_to_vec_vec(js_board);
+    vec_vec_to_js_value(ms::canonical_board(&board))
+}
+
+// 局面的标准形式的哈希值，在js中为BigInt
+#[wasm_bindgen]
+pub fn canonical_board_hash(js_board: JsValue) -> u64 {
+    let board = js_value_to_vec_vec(js_board);
+    ms::canonical_board_hash(&board)
+}
+
+// 局面的拓扑结构：0-平面，1-环面，其他值按平面处理
+pub(crate) fn topology(topology: usize) -> ms::Topology {
+    match topology {
+        1 => ms::Topology::Torus,
+        _ => ms::Topology::Plane,
+    }
+}
+
+#[wasm_bindgen]
+pub fn laymine_with_topology_seeded(
+    row: usize,
+    column: usize,
+    mine_num: usize,
+    x0: usize,
+    y0: usize,
+    op_start: bool,
+    topology_id: usize,
+    seed: u64,
+) -> JsValue {
+    let board = ms::laymine_with_topology_seeded(
+        row,
+        column,
+        mine_num,
+        x0,
+        y0,
+        op_start,
+        topology(topology_id),
+        seed,
+    );
+    vec_vec_to_js_value(board)
+}
+
+#[wasm_bindgen]
+pub fn cal_bbbv_with_topology(js_board: JsValue, topology_id: usize) -> usize {
+    let board = js_value_to_vec_vec(js_board);
+    ms::cal_bbbv_with_topology(&board, topology(topology_id))
+}
+
+#[wasm_bindgen]
+pub fn is_solvable_with_topology(js_board: JsValue, x0: usize, y0: usize, topology_id: usize) -> bool {
+    let board = js_value_to_vec_vec(js_board);
+    ms::is_solvable_with_topology(&board, x0, y0, topology(topology_id))
+}
+
 #[wasm_bindgen(getter_with_clone)]
 pub struct TimePeriod {
     pub start_time: String,
@@ -31,3 +31,14 @@ pub fn js_value_to_vec_vec(vec: JsValue) -> Vec<Vec<i32>> {
     board
 }
 
+
+pub fn pos_list_to_js_value(poses: Vec<(usize, usize)>) -> JsValue {
+    let js_array = Array::new();
+    for (r, c) in poses {
+        let pos = Array::new();
+        pos.push(&JsValue::from(r));
+        pos.push(&JsValue::from(c));
+        js_array.push(&pos);
+    }
+    js_array.into()
+}